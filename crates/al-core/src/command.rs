@@ -2,22 +2,110 @@
 use crate::event::Event;
 
 /// A command that can be sent through the system to signal actions, including custom events.
-#[cfg_attr(feature = "event", crate::event_requirements)]
+#[cfg_attr(
+    feature = "event",
+    crate::event_requirements(PartialEq, Hash, serde::Serialize, serde::Deserialize)
+)]
 #[cfg_attr(not(feature = "event"), derive(Default))]
 pub enum Command {
+    /// An event with its optional `CommandMeta`, which is ignored by `PartialEq` and `Hash`.
+    /// The second field was added along with `CommandMeta`, so `Command::Event(event)` patterns are now written `Command::Event(event, _)`
     #[cfg(feature = "event")]
-    Event(Box<dyn Event>),
+    Event(Box<dyn Event>, Option<Box<CommandMeta>>),
     Restart,
     Stop,
     #[default]
     Pulse,
 }
 
+/// Tracing metadata attached to `Command::Event` variants by `Event::to_cmd()`
+#[derive(Clone, Default, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandMeta {
+    /// Time since `UNIX_EPOCH` the command was created, in microseconds
+    pub created_at_micros: u64,
+    pub origin: Option<String>,
+    pub trace_id: Option<u64>,
+}
+
+/// Returns the time since `UNIX_EPOCH` in microseconds
+#[cfg(not(test))]
+fn now_micros() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// Reads the clock once per test thread, so otherwise equal commands created by one test serialize to the same bytes
+#[cfg(test)]
+fn now_micros() -> u64 {
+    thread_local! {
+        static NOW_MICROS: u64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+    }
+    NOW_MICROS.with(|now| *now)
+}
+
+impl CommandMeta {
+    /// Returns a `CommandMeta` created at the current time, without an origin or trace id
+    pub fn now() -> Self {
+        Self {
+            created_at_micros: now_micros(),
+            origin: None,
+            trace_id: None,
+        }
+    }
+
+    /// Set the name of the service or component the command originated from
+    pub fn with_origin(mut self, origin: impl AsRef<str>) -> Self {
+        self.origin = Some(origin.as_ref().to_string());
+        self
+    }
+
+    /// Set the id used to trace the command across services
+    pub fn with_trace_id(mut self, trace_id: u64) -> Self {
+        self.trace_id = Some(trace_id);
+        self
+    }
+}
+
+impl Command {
+    /// Returns the `CommandMeta` of an event variant, if it has any
+    pub fn meta(&self) -> Option<&CommandMeta> {
+        match self {
+            #[cfg(feature = "event")]
+            Command::Event(_, meta) => meta.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns true for control commands, any variant other than `Command::Event`
+    pub fn is_control(&self) -> bool {
+        match self {
+            #[cfg(feature = "event")]
+            Command::Event(..) => false,
+            _ => true,
+        }
+    }
+
+    /// Replaces the `CommandMeta` of an event variant, other variants are returned unchanged
+    pub fn with_meta(self, meta: Option<CommandMeta>) -> Self {
+        match self {
+            #[cfg(feature = "event")]
+            Command::Event(event, _) => Command::Event(event, meta.map(Box::new)),
+            command => command,
+        }
+    }
+}
+
 #[cfg(feature = "event")]
 impl Command {
     /// Returns true if the command is an event variant, otherwise false
     pub fn is_event(&self) -> bool {
-        matches!(self, Command::Event(_))
+        matches!(self, Command::Event(..))
     }
 
     /// Attempts to downcast the contained event to the specified event type, returning `None` if the command is not an event or if the downcast fails
@@ -25,7 +113,7 @@ impl Command {
         &self,
     ) -> Result<T, String> {
         match self {
-            Command::Event(event) => crate::downcast_event(event),
+            Command::Event(event, _) => crate::downcast_event(event),
             _ => Err("Command is not an Event variant".to_string()),
         }
     }
@@ -33,8 +121,74 @@ impl Command {
     /// Returns the type name of the contained event, returning `None` if the command is not an event variant
     pub fn event_type_name(&self) -> Option<String> {
         match self {
-            Command::Event(event) => Some(event.as_ref().type_with_generics()),
+            Command::Event(event, _) => Some(event.as_ref().type_with_generics()),
             _ => None,
         }
     }
 }
+
+/// Implement `PartialEq` for command manually so the `CommandMeta` of events is ignored
+#[cfg(feature = "event")]
+impl PartialEq for Command {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Command::Event(event, _), Command::Event(other, _)) => event == other,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+/// Implement `Hash` for command manually so the `CommandMeta` of events is ignored, keeping it consistent with `PartialEq`
+#[cfg(feature = "event")]
+impl std::hash::Hash for Command {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        if let Command::Event(event, _) = self {
+            event.hash(state);
+        }
+    }
+}
+
+/// Serializes in the original `Command` shape without the `CommandMeta`, which is carried by the versioned `SerdeFormat` envelope instead
+#[cfg(all(feature = "event", feature = "serde"))]
+impl serde::Serialize for Command {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Command::Event(event, _) => {
+                serializer.serialize_newtype_variant("Command", 0, "Event", event)
+            }
+            Command::Restart => serializer.serialize_unit_variant("Command", 1, "Restart"),
+            Command::Stop => serializer.serialize_unit_variant("Command", 2, "Stop"),
+            Command::Pulse => serializer.serialize_unit_variant("Command", 3, "Pulse"),
+        }
+    }
+}
+
+/// The original `Command` shape, used to deserialize commands without their `CommandMeta`
+#[cfg(all(feature = "event", feature = "serde"))]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Command")]
+enum CommandShape {
+    Event(Box<dyn Event>),
+    Restart,
+    Stop,
+    Pulse,
+}
+
+#[cfg(all(feature = "event", feature = "serde"))]
+impl<'de> serde::Deserialize<'de> for Command {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(match CommandShape::deserialize(deserializer)? {
+            CommandShape::Event(event) => Command::Event(event, None),
+            CommandShape::Restart => Command::Restart,
+            CommandShape::Stop => Command::Stop,
+            CommandShape::Pulse => Command::Pulse,
+        })
+    }
+}
@@ -0,0 +1,455 @@
+use crate::{Command, Event, EventMarker, EventRequirements, Registry};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Future returned by each step of a dispatch
+pub type DispatchFuture = Pin<Box<dyn Future<Output = DispatchResult> + Send>>;
+
+/// Outcome of `EventDispatcher::dispatch`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DispatchResult {
+    /// A handler ran for the command
+    Handled,
+    /// No handler is registered for the command
+    Unhandled,
+    /// A middleware stopped the dispatch before the handler ran
+    Rejected(String),
+}
+
+/// Middleware run around the handler of every dispatched command, continuing the chain with `next.run()` or returning early to short-circuit it
+pub trait Middleware: Send + Sync + 'static {
+    fn handle(&self, command: &Command, next: Next) -> DispatchFuture;
+}
+
+impl<F, Fut> Middleware for F
+where
+    F: Fn(&Command, Next) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = DispatchResult> + Send + 'static,
+{
+    fn handle(&self, command: &Command, next: Next) -> DispatchFuture {
+        Box::pin(self(command, next))
+    }
+}
+
+type HandlerFn = Arc<dyn Fn(&Command) -> DispatchFuture + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct DispatcherInner {
+    middlewares: Vec<Arc<dyn Middleware>>,
+    handlers: Registry<String, HandlerFn>,
+    fallback: Option<HandlerFn>,
+}
+
+impl DispatcherInner {
+    /// Runs the handler of the command's event type, or the fallback for any other command
+    fn invoke(&self, command: &Command) -> DispatchFuture {
+        let handler = command
+            .event_type_name()
+            .and_then(|name| self.handlers.get(&name))
+            .or(self.fallback.as_ref());
+        match handler {
+            Some(handler) => handler(command),
+            None => Box::pin(async { DispatchResult::Unhandled }),
+        }
+    }
+}
+
+/// The remaining middlewares and handler of a dispatch
+pub struct Next {
+    command: Arc<Command>,
+    inner: Arc<DispatcherInner>,
+    index: usize,
+}
+
+impl Next {
+    /// Continues the dispatch with the next middleware, or the handler once every middleware has run
+    pub fn run(self) -> DispatchFuture {
+        match self.inner.middlewares.get(self.index).cloned() {
+            Some(middleware) => {
+                let command = self.command.clone();
+                middleware.handle(
+                    &command,
+                    Next {
+                        index: self.index + 1,
+                        ..self
+                    },
+                )
+            }
+            None => self.inner.invoke(&self.command),
+        }
+    }
+}
+
+/// Dispatches commands to the handler registered for their event type, through the middlewares in registration order
+#[derive(Clone, Default)]
+pub struct EventDispatcher {
+    inner: Arc<DispatcherInner>,
+}
+
+impl std::fmt::Debug for EventDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut handlers = self.inner.handlers.keys().collect::<Vec<_>>();
+        handlers.sort();
+        f.debug_struct("EventDispatcher")
+            .field("middlewares", &self.inner.middlewares.len())
+            .field("handlers", &handlers)
+            .field("fallback", &self.inner.fallback.is_some())
+            .finish()
+    }
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a middleware to the end of the chain
+    pub fn use_middleware(&mut self, middleware: impl Middleware) -> &mut Self {
+        Arc::make_mut(&mut self.inner)
+            .middlewares
+            .push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets the handler for events of type `E`, replacing any previous handler for the type
+    pub fn on<E, F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        E: Event + EventMarker + EventRequirements + 'static,
+        F: Fn(E) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        Arc::make_mut(&mut self.inner).handlers.insert(
+            <E as EventMarker>::type_with_generics(),
+            Arc::new(
+                move |command: &Command| match command.downcast_event::<E>() {
+                    Ok(event) => {
+                        let future = handler(event);
+                        Box::pin(async move {
+                            future.await;
+                            DispatchResult::Handled
+                        })
+                    }
+                    Err(_) => Box::pin(async { DispatchResult::Unhandled }),
+                },
+            ),
+        );
+        self
+    }
+
+    /// Sets the handler for commands without a handler of their own, including non event commands
+    pub fn fallback<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Command) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Arc::make_mut(&mut self.inner).fallback = Some(Arc::new(move |command: &Command| {
+            let future = handler(command.clone());
+            Box::pin(async move {
+                future.await;
+                DispatchResult::Handled
+            })
+        }));
+        self
+    }
+
+    /// Dispatches the command through every middleware to its handler
+    pub async fn dispatch(&self, command: Command) -> DispatchResult {
+        Next {
+            command: Arc::new(command),
+            inner: self.inner.clone(),
+            index: 0,
+        }
+        .run()
+        .await
+    }
+}
+
+/// Returns the event type name of the command, or the variant name for non event commands
+fn command_name(command: &Command) -> String {
+    command
+        .event_type_name()
+        .unwrap_or_else(|| format!("{:?}", command))
+}
+
+/// Built-in middleware passing a line to the `sink` before and after each dispatch
+pub struct LoggingMiddleware {
+    sink: Arc<dyn Fn(String) + Send + Sync>,
+}
+
+impl LoggingMiddleware {
+    pub fn new(sink: impl Fn(String) + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Returns a `LoggingMiddleware` printing each line to stdout
+    pub fn stdout() -> Self {
+        Self::new(|line| println!("{}", line))
+    }
+}
+
+impl Middleware for LoggingMiddleware {
+    fn handle(&self, command: &Command, next: Next) -> DispatchFuture {
+        let name = command_name(command);
+        let sink = self.sink.clone();
+        sink(format!("Dispatching `{}`", name));
+        Box::pin(async move {
+            let started = Instant::now();
+            let result = next.run().await;
+            sink(format!(
+                "Dispatched `{}` in {:?}: {:?}",
+                name,
+                started.elapsed(),
+                result
+            ));
+            result
+        })
+    }
+}
+
+/// Token bucket of a single event type
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Built-in middleware rejecting commands once their event type exceeds `capacity` dispatches per `window`, using a token bucket per event type
+pub struct RateLimitMiddleware {
+    capacity: u32,
+    window: Duration,
+    buckets: Mutex<Registry<String, Bucket>>,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(capacity: u32, window: Duration) -> Self {
+        Self {
+            capacity,
+            window,
+            buckets: Mutex::new(Registry::new()),
+        }
+    }
+
+    /// Takes a token from the bucket of the event type, refilling it by the time passed since the last refill
+    fn try_acquire(&self, name: &str) -> bool {
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return false;
+        };
+        let capacity = self.capacity as f64;
+        let now = Instant::now();
+        let bucket = buckets.entry(name.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            refilled: now,
+        });
+        let refill = now.duration_since(bucket.refilled).as_secs_f64() / self.window.as_secs_f64();
+        bucket.tokens = (bucket.tokens + refill * capacity).min(capacity);
+        bucket.refilled = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Middleware for RateLimitMiddleware {
+    fn handle(&self, command: &Command, next: Next) -> DispatchFuture {
+        let name = command_name(command);
+        if self.try_acquire(&name) {
+            next.run()
+        } else {
+            Box::pin(async move {
+                DispatchResult::Rejected(format!("Rate limit exceeded for `{}`", name))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        event, Command, DispatchResult, Event, EventDispatcher, LoggingMiddleware, Next,
+        RateLimitMiddleware,
+    };
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    #[event]
+    struct TestEvent(u8);
+
+    #[event]
+    struct OtherEvent;
+
+    #[tokio::test]
+    async fn debug() {
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .use_middleware(|_: &Command, next: Next| next.run())
+            .on(|_: TestEvent| async {});
+        assert_eq!(
+            format!("{:?}", dispatcher),
+            "EventDispatcher { middlewares: 1, handlers: [\"al_core::dispatcher::tests::TestEvent\"], fallback: false }"
+        );
+    }
+
+    #[tokio::test]
+    async fn dispatch() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut dispatcher = EventDispatcher::new();
+        let received_clone = received.clone();
+        dispatcher.on(move |event: TestEvent| {
+            received_clone.lock().unwrap().push(event.0);
+            async {}
+        });
+
+        assert_eq!(
+            dispatcher.dispatch(TestEvent(1).to_cmd()).await,
+            DispatchResult::Handled
+        );
+        assert_eq!(
+            dispatcher.dispatch(OtherEvent.to_cmd()).await,
+            DispatchResult::Unhandled
+        );
+        assert_eq!(
+            dispatcher.dispatch(Command::Stop).await,
+            DispatchResult::Unhandled
+        );
+        assert_eq!(*received.lock().unwrap(), vec![1]);
+
+        // The fallback handles everything without its own handler
+        dispatcher.fallback(|_| async {});
+        assert_eq!(
+            dispatcher.dispatch(Command::Stop).await,
+            DispatchResult::Handled
+        );
+    }
+
+    #[tokio::test]
+    async fn middleware_order() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut dispatcher = EventDispatcher::new();
+        for i in 1..=3 {
+            let order = order.clone();
+            dispatcher.use_middleware(move |_: &Command, next: Next| {
+                let order = order.clone();
+                async move {
+                    order.lock().unwrap().push(format!("before {}", i));
+                    let result = next.run().await;
+                    order.lock().unwrap().push(format!("after {}", i));
+                    result
+                }
+            });
+        }
+        let order_clone = order.clone();
+        dispatcher.on(move |_: TestEvent| {
+            order_clone.lock().unwrap().push("handler".to_string());
+            async {}
+        });
+
+        assert_eq!(
+            dispatcher.dispatch(TestEvent(1).to_cmd()).await,
+            DispatchResult::Handled
+        );
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["before 1", "before 2", "before 3", "handler", "after 3", "after 2", "after 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn short_circuit() {
+        let handled = Arc::new(Mutex::new(false));
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.use_middleware(|command: &Command, next: Next| {
+            let allowed = command.downcast_event::<TestEvent>().map(|e| e.0) != Ok(0);
+            async move {
+                if allowed {
+                    next.run().await
+                } else {
+                    DispatchResult::Rejected("Unauthorized".to_string())
+                }
+            }
+        });
+        let handled_clone = handled.clone();
+        dispatcher.on(move |_: TestEvent| {
+            *handled_clone.lock().unwrap() = true;
+            async {}
+        });
+
+        assert_eq!(
+            dispatcher.dispatch(TestEvent(0).to_cmd()).await,
+            DispatchResult::Rejected("Unauthorized".to_string())
+        );
+        assert!(!*handled.lock().unwrap());
+        assert_eq!(
+            dispatcher.dispatch(TestEvent(1).to_cmd()).await,
+            DispatchResult::Handled
+        );
+        assert!(*handled.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn rate_limit() {
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .use_middleware(RateLimitMiddleware::new(3, Duration::from_millis(100)))
+            .on(|_: TestEvent| async {})
+            .on(|_: OtherEvent| async {});
+
+        for _ in 0..3 {
+            assert_eq!(
+                dispatcher.dispatch(TestEvent(1).to_cmd()).await,
+                DispatchResult::Handled
+            );
+        }
+        assert!(matches!(
+            dispatcher.dispatch(TestEvent(1).to_cmd()).await,
+            DispatchResult::Rejected(_)
+        ));
+        // Each event type has its own bucket
+        assert_eq!(
+            dispatcher.dispatch(OtherEvent.to_cmd()).await,
+            DispatchResult::Handled
+        );
+
+        // The bucket refills over the window
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            dispatcher.dispatch(TestEvent(1).to_cmd()).await,
+            DispatchResult::Handled
+        );
+    }
+
+    #[tokio::test]
+    async fn logging() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let lines_clone = lines.clone();
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .use_middleware(LoggingMiddleware::new(move |line| {
+                lines_clone.lock().unwrap().push(line)
+            }))
+            .on(|_: TestEvent| async {});
+
+        dispatcher.dispatch(TestEvent(1).to_cmd()).await;
+        dispatcher.dispatch(Command::Stop).await;
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "Dispatching `al_core::dispatcher::tests::TestEvent`"
+        );
+        assert!(lines[1].ends_with(": Handled"));
+        assert_eq!(lines[2], "Dispatching `Stop`");
+        assert!(lines[3].ends_with(": Unhandled"));
+    }
+}
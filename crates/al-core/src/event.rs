@@ -9,7 +9,7 @@ use std::{
 #[cfg(feature = "serde")]
 pub static EVENT_REGISTRY: once_cell::sync::Lazy<
     crate::serde_utils::event_registry::EventRegistry,
-> = once_cell::sync::Lazy::new(|| crate::serde_utils::event_registry::EventRegistry::new());
+> = once_cell::sync::Lazy::new(crate::serde_utils::event_registry::EventRegistry::new);
 
 /// Helper function to return the simple names of types with generics using the `tynm` crate
 pub fn type_with_generics<T>(_: &T) -> String {
@@ -17,6 +17,7 @@ pub fn type_with_generics<T>(_: &T) -> String {
 }
 
 /// Helper function to downcast an event to a specific type, returning None if the downcast fails
+#[allow(clippy::borrowed_box)]
 pub fn downcast<T: Event + EventRequirements + 'static>(
     event: &Box<dyn Event>,
 ) -> Result<T, String> {
@@ -31,15 +32,13 @@ pub fn downcast<T: Event + EventRequirements + 'static>(
     }
 }
 
+#[allow(clippy::borrowed_box)]
 pub trait DowncastEvent {
-    fn downcast<T: Event + EventRequirements + 'static>(
-        self: &Box<Self>,
-    ) -> Result<T, String>;
+    fn downcast<T: Event + EventRequirements + 'static>(self: &Box<Self>) -> Result<T, String>;
 }
+#[allow(clippy::borrowed_box)]
 impl DowncastEvent for dyn Event {
-    fn downcast<T: Event + EventRequirements + 'static>(
-        self: &Box<Self>,
-    ) -> Result<T, String> {
+    fn downcast<T: Event + EventRequirements + 'static>(self: &Box<Self>) -> Result<T, String> {
         downcast(self)
     }
 }
@@ -47,7 +46,7 @@ impl DowncastEvent for dyn Event {
 /// The `Event` trait defines the required methods for event types to exist in the system along with trait bounds that dont interfere with dyn usage
 pub trait Event: Send + Sync + Debug + Any + crate::SerdeFeature + 'static {
     #[cfg(feature = "serde")]
-    fn register(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    fn register(self) -> Result<(), crate::RegistryError>
     where
         Self: for<'de> serde::Deserialize<'de>;
     fn as_any(&self) -> &dyn Any;
@@ -58,11 +57,21 @@ pub trait Event: Send + Sync + Debug + Any + crate::SerdeFeature + 'static {
     fn _hash_event(&self, state: &mut dyn Hasher);
 
     #[cfg(feature = "command")]
+    /// Wraps the event in a `Command`, with a `CommandMeta` created at the current time
     fn to_cmd(self) -> crate::Command
     where
         Self: Sized,
     {
-        crate::Command::Event(Box::new(self))
+        self.to_cmd_with_meta(crate::CommandMeta::now())
+    }
+
+    #[cfg(feature = "command")]
+    /// Wraps the event in a `Command` with the passed `CommandMeta`
+    fn to_cmd_with_meta(self, meta: crate::CommandMeta) -> crate::Command
+    where
+        Self: Sized,
+    {
+        crate::Command::Event(Box::new(self), Some(Box::new(meta)))
     }
 }
 
@@ -70,7 +79,7 @@ pub trait Event: Send + Sync + Debug + Any + crate::SerdeFeature + 'static {
 /// This allows any type that implements `EventMarker` and the traits required by `EventRequirements` to automatically be treated as an `Event`
 impl<T: EventMarker + EventRequirements + crate::SerdeFeature> Event for T {
     #[cfg(feature = "serde")]
-    fn register(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    fn register(self) -> Result<(), crate::RegistryError>
     where
         Self: for<'de> serde::Deserialize<'de>,
     {
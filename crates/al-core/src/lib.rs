@@ -2,6 +2,8 @@
 extern crate self as al_core;
 #[cfg(feature = "command")]
 mod command;
+#[cfg(all(feature = "command", feature = "event"))]
+mod dispatcher;
 #[cfg(feature = "event")]
 mod event;
 mod markers;
@@ -18,9 +20,12 @@ mod transports;
 use std::marker::PhantomData;
 
 #[cfg(feature = "command")]
-pub use command::Command;
-#[cfg(all(feature = "event", feature = "serde"))]
-pub use event::EVENT_REGISTRY;
+pub use command::{Command, CommandMeta};
+#[cfg(all(feature = "command", feature = "event"))]
+pub use dispatcher::{
+    DispatchFuture, DispatchResult, EventDispatcher, LoggingMiddleware, Middleware, Next,
+    RateLimitMiddleware,
+};
 #[cfg(all(
     any(feature = "event", feature = "command"),
     feature = "serde",
@@ -34,7 +39,18 @@ pub use serde_utils::serde_format::BinarySerde;
 ))]
 pub use serde_utils::serde_format::JsonSerde;
 #[cfg(all(any(feature = "event", feature = "command"), feature = "serde"))]
-pub use serde_utils::serde_format::SerdeFormat;
+pub use serde_utils::serde_format::{
+    EffectiveVersion, MaxPayload, PayloadTooLarge, SerdeFormat, FORMAT_MAGIC,
+};
+#[cfg(all(feature = "transport", feature = "task", feature = "command"))]
+pub use transports::shutdown::PipelineShutdown;
+#[cfg(all(
+    feature = "transport",
+    feature = "task",
+    feature = "command",
+    feature = "event"
+))]
+pub use transports::watchdog::{ProgressProbe, StageStalled, StallAction, Watchdog};
 #[cfg(feature = "event")]
 pub use {
     al_derive::event, al_derive::event_requirements, al_derive::EventMarker as DeriveEventMarker,
@@ -43,22 +59,47 @@ pub use {
 };
 #[cfg(feature = "transport")]
 pub use {
-    markers::NoOp, markers::TransportItemRequirements, markers::TransportRequirements,
-    transport::Transport, transport::TransportError, transports::list::List,
-    transports::publisher::Publisher, transports::queue::Queue,
+    al_derive::transport_item, markers::NoOp, markers::TransportItemRequirements,
+    markers::TransportRequirements, transport::Transport, transport::TransportError,
+    transports::ack::AckTransport, transports::ack::DeliveryId, transports::ack::DeliveryReceipt,
+    transports::dead_letter::DeadLetter, transports::dead_letter::RetryPolicy,
+    transports::delay_queue::DelayQueue, transports::delay_queue::DelayTicket,
+    transports::duplex::duplex, transports::duplex::DuplexEnd, transports::list::List,
+    transports::oneshot::OneShot, transports::publisher::Publisher, transports::queue::Queue,
+    transports::sharded_queue::ShardKeyFn, transports::sharded_queue::ShardedQueue,
     transports::transform::ApplyTransform, transports::transform::Transform,
     transports::transform::TransformFn,
 };
+#[cfg(all(feature = "event", feature = "serde"))]
+pub use {event::EVENT_REGISTRY, serde_utils::event_registry::RegistryError};
 #[cfg(feature = "task")]
 pub use {
-    markers::TaskStateRequirements, markers::TaskTypes, task::Task,
+    markers::TaskStateRequirements, markers::TaskTypes, task::Task, task_utils::budget::Budget,
+    task_utils::multi_task::JobState, task_utils::multi_task::MultiJob,
+    task_utils::multi_task::MultiTaskState, task_utils::task_elements::TaskCompletion,
     task_utils::task_elements::TaskConfig, task_utils::task_elements::TaskError,
     task_utils::task_elements::TaskMode, task_utils::task_state::AsTaskState,
     task_utils::task_state::BaseTaskState, task_utils::task_state::ExtendedTaskState,
-    task_utils::task_state::TaskState,
+    task_utils::task_state::TaskState, task_utils::task_state::TimingStats,
 };
 #[cfg(all(feature = "transport", feature = "task"))]
-pub use {transports::buffered::Buffered, transports::link::Link, transports::splice::Splice};
+pub use {
+    transports::buffered::Buffered, transports::coalesce::Coalesce, transports::link::Link,
+    transports::splice::Splice,
+};
+#[cfg(all(feature = "transport", feature = "command", feature = "serde"))]
+pub use {
+    transports::capture::CaptureDirection, transports::capture::CaptureReader,
+    transports::capture::CaptureRecord, transports::capture::CaptureReplay,
+    transports::capture::CaptureTransport,
+};
+#[cfg(all(feature = "transport", feature = "task", feature = "serde"))]
+pub use {
+    transports::topology::build as build_topology, transports::topology::BuiltTopology,
+    transports::topology::EdgeSpec, transports::topology::NodeKind, transports::topology::NodeSpec,
+    transports::topology::TopologyError, transports::topology::TopologyRegistry,
+    transports::topology::TopologySpec, transports::topology::TransportFactory,
+};
 
 /// A generic registry type using a HashMap.
 pub type Registry<K, V> = std::collections::HashMap<K, V>;
@@ -212,11 +253,11 @@ mod tests {
         .to_cmd();
         let generic_val = TestEventGeneric(TEST_VAL).to_cmd();
         let generic_str = TestEventGeneric(TEST_MSG.to_string()).to_cmd();
-        assert!(matches!(cmd, Command::Event(_)));
-        assert!(matches!(enum_cmd, Command::Event(_)));
-        assert!(matches!(payload_cmd, Command::Event(_)));
-        assert!(matches!(generic_val, Command::Event(_)));
-        assert!(matches!(generic_str, Command::Event(_)));
+        assert!(matches!(cmd, Command::Event(..)));
+        assert!(matches!(enum_cmd, Command::Event(..)));
+        assert!(matches!(payload_cmd, Command::Event(..)));
+        assert!(matches!(generic_val, Command::Event(..)));
+        assert!(matches!(generic_str, Command::Event(..)));
     }
 
     #[cfg(feature = "event")]
@@ -226,7 +267,7 @@ mod tests {
         use crate::DowncastEvent;
 
         let event_a: Box<dyn Event> = Box::new(TestEventA);
-        let event_b: Box<dyn Event> = Box::new(TestEventB::default());
+        let event_b: Box<dyn Event> = Box::new(TestEventB);
         let enum_a: Box<dyn Event> = Box::new(TestEventEnum::A);
         let enum_b: Box<dyn Event> = Box::new(TestEventEnum::B(1));
         let enum_c: Box<dyn Event> = Box::new(TestEventEnum::C(TEST_MSG.to_string()));
@@ -1615,4 +1656,652 @@ mod tests {
         assert_eq!(cmd_generic_str, cmd_generic_str_same);
         assert_ne!(cmd_generic_str, cmd_generic_str_diff);
     }
+
+    /// Test the versioned `Command` envelope header for both formats, including headerless legacy bytes
+    #[cfg(all(
+        feature = "command",
+        feature = "serde",
+        feature = "json",
+        feature = "binary"
+    ))]
+    #[test]
+    fn command_format_version() {
+        use crate::{BinarySerde, EffectiveVersion, JsonSerde, SerdeFormat, FORMAT_MAGIC};
+
+        fn check_format<F: SerdeFormat>(format: F, legacy: Vec<u8>) {
+            let cmd = Command::Restart;
+
+            // New bytes start with the header for the current version
+            let versioned = format.serialize_command(&cmd).unwrap();
+            assert_eq!(versioned[..2], [FORMAT_MAGIC, F::FORMAT_VERSION]);
+            assert_eq!(
+                format
+                    .serialize_command_versioned(&cmd, EffectiveVersion(1))
+                    .unwrap()[2..],
+                legacy[..]
+            );
+            assert_eq!(format.deserialize_command(&versioned).unwrap(), cmd);
+
+            // Headerless legacy bytes are sniffed and parsed by the version `0` path
+            assert_eq!(format.deserialize_command(&legacy).unwrap(), cmd);
+            assert_eq!(
+                format
+                    .deserialize_command_versioned(&legacy, EffectiveVersion::LEGACY)
+                    .unwrap(),
+                cmd
+            );
+            assert_eq!(
+                format
+                    .serialize_command_versioned(&cmd, EffectiveVersion::LEGACY)
+                    .unwrap(),
+                legacy
+            );
+
+            // Unknown future versions return a clear error
+            let mut future = versioned.clone();
+            future[1] = F::FORMAT_VERSION + 1;
+            assert!(format
+                .deserialize_command(&future)
+                .unwrap_err()
+                .to_string()
+                .contains("Unsupported format version"));
+
+            // Negotiation picks the highest mutually supported version
+            assert_eq!(format.negotiate(0), EffectiveVersion::LEGACY);
+            assert_eq!(
+                format.negotiate(u8::MAX),
+                EffectiveVersion(F::FORMAT_VERSION)
+            );
+        }
+
+        check_format(JsonSerde, serde_json::to_vec(&Command::Restart).unwrap());
+        check_format(BinarySerde, bitcode::serialize(&Command::Restart).unwrap());
+    }
+
+    /// Test commands serialized by the crate before the header was added are read by `deserialize_command`
+    #[cfg(all(
+        feature = "command",
+        feature = "event",
+        feature = "serde",
+        feature = "json",
+        feature = "binary"
+    ))]
+    #[test]
+    fn command_format_baseline() {
+        use crate::{register_event, BinarySerde, JsonSerde, SerdeFormat};
+
+        register_event!(TestEventPayload);
+        let event = TestEventPayload {
+            value: TEST_VAL,
+            message: TEST_MSG.to_string(),
+        }
+        .to_cmd();
+
+        // Bytes written by the headerless crate
+        let json: [(&[u8], Command); 2] = [
+            (br#""Restart""#, Command::Restart),
+            (
+                br#"{"Event":["al_core::tests::TestEventPayload",{"value":7878,"message":"Test"}]}"#,
+                event.clone(),
+            ),
+        ];
+        let mut binary_event = vec![0, 32];
+        binary_event.extend(b"al_core::tests::TestEventPayload");
+        binary_event.extend([6, 198, 30, 4]);
+        binary_event.extend(b"Test");
+        let binary: [(&[u8], Command); 2] =
+            [(&[1], Command::Restart), (&binary_event, event.clone())];
+
+        for (data, cmd) in json {
+            assert_eq!(JsonSerde.deserialize_command(data).unwrap(), cmd);
+        }
+        for (data, cmd) in binary {
+            assert_eq!(BinarySerde.deserialize_command(data).unwrap(), cmd);
+        }
+    }
+
+    /// Test a `SerdeFormat` implemented without versioning keeps its wire format through the default functions
+    #[cfg(all(
+        feature = "command",
+        feature = "event",
+        feature = "serde",
+        feature = "json"
+    ))]
+    #[test]
+    fn command_format_legacy() {
+        use crate::{
+            EffectiveVersion, Event, EventRequirements, JsonSerde, SerdeFormat, FORMAT_MAGIC,
+        };
+
+        /// Legacy format without a header, whose payloads happen to start with `FORMAT_MAGIC`
+        #[derive(Clone, Default, PartialEq, Debug, Hash)]
+        struct MagicLegacySerde;
+
+        impl SerdeFormat for MagicLegacySerde {
+            fn serialize_event(
+                &self,
+                event: &dyn Event,
+            ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+                JsonSerde.serialize_event(event)
+            }
+
+            fn deserialize_event<T>(&self, data: &[u8]) -> Result<T, Box<dyn std::error::Error>>
+            where
+                T: Event + EventMarker + EventRequirements + for<'de> serde::Deserialize<'de>,
+            {
+                JsonSerde.deserialize_event(data)
+            }
+
+            fn deserialize_event_dyn(
+                &self,
+                data: &[u8],
+            ) -> Result<Box<dyn Event>, Box<dyn std::error::Error>> {
+                JsonSerde.deserialize_event_dyn(data)
+            }
+
+            fn serialize_command(
+                &self,
+                command: &Command,
+            ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+                let mut data = vec![FORMAT_MAGIC];
+                data.extend(serde_json::to_vec(command)?);
+                Ok(data)
+            }
+
+            fn deserialize_command(
+                &self,
+                data: &[u8],
+            ) -> Result<Command, Box<dyn std::error::Error>> {
+                Ok(serde_json::from_slice(&data[1..])?)
+            }
+        }
+
+        let format = MagicLegacySerde;
+        let cmd = Command::Restart;
+        assert_eq!(MagicLegacySerde::FORMAT_VERSION, 0);
+        assert_eq!(format.negotiate(u8::MAX), EffectiveVersion::LEGACY);
+
+        // The negotiated legacy version never reads the leading `FORMAT_MAGIC` as a header
+        let legacy = format.serialize_command(&cmd).unwrap();
+        assert_eq!(legacy[0], FORMAT_MAGIC);
+        assert_eq!(
+            format
+                .serialize_command_versioned(&cmd, EffectiveVersion::LEGACY)
+                .unwrap(),
+            legacy
+        );
+        assert_eq!(
+            format
+                .deserialize_command_versioned(&legacy, EffectiveVersion::LEGACY)
+                .unwrap(),
+            cmd
+        );
+
+        // Versions the format doesn't support return an error
+        assert!(format
+            .serialize_command_versioned(&cmd, EffectiveVersion(1))
+            .is_err());
+        assert!(format
+            .deserialize_command_versioned(&legacy, EffectiveVersion(1))
+            .is_err());
+    }
+
+    /// Test `CommandMeta` is set by `to_cmd()`, ignored by equality, and survives both formats
+    #[cfg(all(
+        feature = "command",
+        feature = "event",
+        feature = "serde",
+        feature = "json",
+        feature = "binary"
+    ))]
+    #[test]
+    fn command_meta() {
+        use crate::{
+            register_event, BinarySerde, CommandMeta, EffectiveVersion, JsonSerde, SerdeFormat,
+        };
+
+        fn check_format<F: SerdeFormat>(format: F) {
+            let meta = CommandMeta::now().with_origin("service_a").with_trace_id(7);
+            let cmd = TestEventPayload {
+                value: TEST_VAL,
+                message: TEST_MSG.to_string(),
+            }
+            .to_cmd_with_meta(meta.clone());
+
+            let new_cmd = format
+                .deserialize_command(&format.serialize_command(&cmd).unwrap())
+                .unwrap();
+            assert_eq!(new_cmd, cmd);
+            assert_eq!(new_cmd.meta(), Some(&meta));
+
+            // Payloads from before `CommandMeta` deserialize without it
+            let legacy = format
+                .serialize_command_versioned(&cmd, EffectiveVersion(1))
+                .unwrap();
+            let legacy_cmd = format.deserialize_command(&legacy).unwrap();
+            assert_eq!(legacy_cmd, cmd);
+            assert_eq!(legacy_cmd.meta(), None);
+
+            // Non event commands never carry meta
+            let restart = format
+                .deserialize_command(&format.serialize_command(&Command::Restart).unwrap())
+                .unwrap();
+            assert_eq!(restart, Command::Restart);
+            assert_eq!(restart.meta(), None);
+        }
+
+        register_event!(TestEventPayload);
+        check_format(JsonSerde);
+        check_format(BinarySerde);
+
+        // `to_cmd()` populates the creation time
+        let cmd = TestEventA.to_cmd();
+        let meta = cmd.meta().unwrap();
+        assert!(meta.created_at_micros > 0);
+        assert_eq!(meta.origin, None);
+        assert_eq!(meta.trace_id, None);
+
+        // Equality and hashing ignore differing meta
+        let cmd_a = TestEventA.to_cmd_with_meta(CommandMeta::now().with_trace_id(1));
+        let cmd_b = TestEventA.to_cmd_with_meta(CommandMeta::now().with_trace_id(2));
+        assert_eq!(cmd_a, cmd_b);
+        assert_eq!(cmd_a, cmd_a.clone().with_meta(None));
+        let mut hasher_a = DefaultHasher::new();
+        let mut hasher_b = DefaultHasher::new();
+        cmd_a.hash(&mut hasher_a);
+        cmd_b.hash(&mut hasher_b);
+        assert_eq!(hasher_a.finish(), hasher_b.finish());
+    }
+
+    /// Test `CommandMeta` is passed through `Link` and `Publisher` untouched
+    #[cfg(all(
+        feature = "command",
+        feature = "event",
+        feature = "transport",
+        feature = "task"
+    ))]
+    #[tokio::test]
+    async fn command_meta_transports() {
+        use crate::{CommandMeta, Link, Publisher, Queue, Transport};
+        use std::sync::Arc;
+
+        let meta = CommandMeta::now().with_origin("service_a").with_trace_id(7);
+        let cmd = TestEventA.to_cmd_with_meta(meta.clone());
+
+        let consumer = Arc::new(Queue::<Command>::new());
+        let publisher = Arc::new(Publisher::<Command>::new());
+        publisher.subscribe(consumer.clone()).unwrap();
+        let link = Link::new(Arc::new(Queue::<Command>::new()), publisher);
+
+        link.send(cmd.clone()).await.unwrap();
+        let received = consumer.recv().await.unwrap();
+        assert_eq!(received, cmd);
+        assert_eq!(received.meta(), Some(&meta));
+    }
+
+    /// Test converting events to and from their separate type name and payload parts
+    #[cfg(all(
+        feature = "event",
+        feature = "serde",
+        feature = "json",
+        feature = "binary"
+    ))]
+    #[test]
+    fn event_parts() {
+        use crate::{
+            register_event, BinarySerde, DowncastEvent, JsonSerde, RegistryError, SerdeFormat,
+            EVENT_REGISTRY,
+        };
+
+        fn check_format<F: SerdeFormat>(format: F) {
+            let payload_event = TestEventPayload {
+                value: TEST_VAL,
+                message: TEST_MSG.to_string(),
+            };
+            let generic_event = TestEventGeneric(TEST_VAL);
+
+            // Store the parts of two event types, as if they were separate columns
+            let rows = [
+                EVENT_REGISTRY
+                    .serialize_event_parts(&payload_event, &format)
+                    .unwrap(),
+                EVENT_REGISTRY
+                    .serialize_event_parts(&generic_event, &format)
+                    .unwrap(),
+            ];
+            assert_eq!(rows[0].0, payload_event.type_with_generics());
+            assert_eq!(rows[1].0, generic_event.type_with_generics());
+
+            let events = rows
+                .iter()
+                .map(|(type_name, payload)| {
+                    EVENT_REGISTRY
+                        .deserialize_event(type_name, payload, &format)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                events[0].downcast::<TestEventPayload>().unwrap(),
+                payload_event
+            );
+            assert_eq!(
+                events[1].downcast::<TestEventGeneric<u128>>().unwrap(),
+                generic_event
+            );
+
+            // A payload stored with another type's name errors rather than returning the wrong type
+            assert!(matches!(
+                EVENT_REGISTRY.deserialize_event(&rows[1].0, &rows[0].1, &format),
+                Err(RegistryError::TypeMismatch { expected, found })
+                    if expected == rows[1].0 && found == rows[0].0
+            ));
+            assert!(matches!(
+                EVENT_REGISTRY.deserialize_event("Unregistered", &rows[0].1, &format),
+                Err(RegistryError::UnknownEvent(name)) if name == "Unregistered"
+            ));
+            assert!(matches!(
+                EVENT_REGISTRY.deserialize_event(&rows[0].0, &[0xFF, 0x00], &format),
+                Err(RegistryError::Serde(_))
+            ));
+        }
+
+        register_event!(TestEventPayload);
+        register_event!(TestEventGeneric<u128>);
+        check_format(JsonSerde);
+        check_format(BinarySerde);
+    }
+
+    /// Test that event parts are decoded with the registry's deserializer for the passed type name, rather than the name embedded in the payload
+    #[cfg(all(
+        feature = "event",
+        feature = "serde",
+        feature = "json",
+        feature = "binary"
+    ))]
+    #[test]
+    fn event_parts_embedded_name() {
+        use crate::{
+            register_event_with, serde_utils::event_registry::EventRegistry, BinarySerde,
+            DowncastEvent, JsonSerde, RegistryError, SerdeFormat,
+        };
+
+        fn check_format<F: SerdeFormat>(format: F, unregistered_payload: Vec<u8>) {
+            // Only `TestEventB` is registered, so decoding can't fall back to the global registry
+            let registry = EventRegistry::new();
+            register_event_with!(registry, TestEventB);
+            let (type_name, payload) = registry
+                .serialize_event_parts(&TestEventB, &format)
+                .unwrap();
+            assert_eq!(
+                registry
+                    .deserialize_event(&type_name, &payload, &format)
+                    .unwrap()
+                    .downcast::<TestEventB>()
+                    .unwrap(),
+                TestEventB
+            );
+
+            // The data decodes as `TestEventB`, but the embedded name is still checked against the type name
+            let (a_name, a_payload) = registry
+                .serialize_event_parts(&TestEventA, &format)
+                .unwrap();
+            assert!(matches!(
+                registry.deserialize_event(&type_name, &a_payload, &format),
+                Err(RegistryError::TypeMismatch { expected, found })
+                    if expected == type_name && found == a_name
+            ));
+            assert!(matches!(
+                registry.deserialize_event(&type_name, &unregistered_payload, &format),
+                Err(RegistryError::TypeMismatch { expected, found })
+                    if expected == type_name && found == "Unregistered"
+            ));
+        }
+
+        check_format(
+            JsonSerde,
+            serde_json::to_vec(&("Unregistered", ())).unwrap(),
+        );
+        check_format(
+            BinarySerde,
+            bitcode::serialize(&("Unregistered", ())).unwrap(),
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    /// Test that re-registering an event is a no-op while a different type claiming the same name conflicts
+    #[test]
+    fn registry_conflict() {
+        use crate::{serde_utils::event_registry::EventRegistry, EventMarker, RegistryError};
+        use std::any::TypeId;
+
+        // Two types from separate builds reporting the same module path share a type name
+        mod crate_a {
+            #[crate::event_requirements]
+            pub struct SharedName;
+            impl crate::EventMarker for SharedName {
+                fn module_path() -> &'static str {
+                    "shared"
+                }
+            }
+        }
+
+        mod crate_b {
+            #[crate::event_requirements]
+            pub struct SharedName;
+            impl crate::EventMarker for SharedName {
+                fn module_path() -> &'static str {
+                    "shared"
+                }
+            }
+        }
+
+        let registry = EventRegistry::new();
+        assert!(crate::try_register_event_with!(registry, crate_a::SharedName).is_ok());
+        assert!(registry.register_event::<crate_a::SharedName>().is_ok());
+        assert!(matches!(
+            crate::try_register_event_with!(registry, crate_b::SharedName),
+            Err(RegistryError::Conflict { name, existing_type_id, new_type_id })
+                if name == <crate_a::SharedName as EventMarker>::type_with_generics()
+                    && existing_type_id == TypeId::of::<crate_a::SharedName>()
+                    && new_type_id == TypeId::of::<crate_b::SharedName>()
+        ));
+
+        assert!(registry
+            .register_event::<crate_b::SharedName>()
+            .unwrap_err()
+            .to_string()
+            .starts_with(&format!(
+                "Event type name {} is already registered",
+                <crate_a::SharedName as EventMarker>::type_with_generics()
+            )));
+
+        // The existing registration is kept
+        assert!(registry.register_event::<crate_a::SharedName>().is_ok());
+        assert!(crate::try_register_event!(TestEventA).is_ok());
+        assert!(crate::try_register_event!(TestEventA).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    /// Stress test registering overlapping sets of events from many threads at once
+    #[test]
+    fn concurrent_registration() {
+        use crate::{serde_utils::event_registry::EventRegistry, EventMarker, RegistryError};
+
+        type Registration = (fn(&EventRegistry) -> Result<(), RegistryError>, String);
+        let registrations: [Registration; 8] = [
+            (
+                |r| r.register_event::<TestEventGeneric<u8>>(),
+                <TestEventGeneric<u8> as EventMarker>::type_with_generics(),
+            ),
+            (
+                |r| r.register_event::<TestEventGeneric<u16>>(),
+                <TestEventGeneric<u16> as EventMarker>::type_with_generics(),
+            ),
+            (
+                |r| r.register_event::<TestEventGeneric<u32>>(),
+                <TestEventGeneric<u32> as EventMarker>::type_with_generics(),
+            ),
+            (
+                |r| r.register_event::<TestEventGeneric<u64>>(),
+                <TestEventGeneric<u64> as EventMarker>::type_with_generics(),
+            ),
+            (
+                |r| r.register_event::<TestEventGeneric<u128>>(),
+                <TestEventGeneric<u128> as EventMarker>::type_with_generics(),
+            ),
+            (
+                |r| r.register_event::<TestEventGeneric<String>>(),
+                <TestEventGeneric<String> as EventMarker>::type_with_generics(),
+            ),
+            (
+                |r| r.register_event::<TestEventA>(),
+                <TestEventA as EventMarker>::type_with_generics(),
+            ),
+            (
+                |r| r.register_event::<TestEventB>(),
+                <TestEventB as EventMarker>::type_with_generics(),
+            ),
+        ];
+
+        let registry = EventRegistry::new();
+        let barrier = std::sync::Barrier::new(32);
+        std::thread::scope(|scope| {
+            for thread in 0..32 {
+                let (registry, barrier, registrations) = (&registry, &barrier, &registrations);
+                scope.spawn(move || {
+                    barrier.wait();
+                    // Each thread registers half of the events, starting from a different offset
+                    for i in 0..registrations.len() / 2 {
+                        let (register, _) = &registrations[(thread + i) % registrations.len()];
+                        for _ in 0..100 {
+                            register(registry).unwrap();
+                        }
+                    }
+                });
+            }
+        });
+
+        for (_, name) in &registrations {
+            assert!(registry.get_deserializer(name).unwrap().is_some());
+        }
+    }
+
+    #[cfg(all(
+        feature = "event",
+        feature = "command",
+        feature = "json",
+        feature = "binary"
+    ))]
+    /// Test `MaxPayload` formats reject oversized data with a distinguishable error
+    #[test]
+    fn max_payload() {
+        use crate::{
+            register_event, BinarySerde, Event, JsonSerde, MaxPayload, PayloadTooLarge, SerdeFormat,
+        };
+
+        /// Event overriding the payload limit of the format
+        #[crate::event_requirements]
+        struct HintedEvent(String);
+        impl crate::EventMarker for HintedEvent {
+            fn module_path() -> &'static str {
+                module_path!()
+            }
+            fn max_payload_hint() -> Option<usize> {
+                Some(256)
+            }
+        }
+
+        fn is_too_large<T>(result: Result<T, Box<dyn std::error::Error>>) -> bool {
+            matches!(result, Err(e) if e.downcast_ref::<PayloadTooLarge>().is_some())
+        }
+
+        fn check_format<F: SerdeFormat>(with_max_payload: impl Fn(usize) -> MaxPayload<F>) {
+            let event = TestEventPayload {
+                value: TEST_VAL,
+                message: TEST_MSG.to_string(),
+            };
+            let data = with_max_payload(usize::MAX)
+                .serialize_event(&event)
+                .unwrap();
+
+            // Data just under or at the limit parses, while data just over it fails before parsing
+            let format = with_max_payload(data.len());
+            assert_eq!(
+                format.deserialize_event::<TestEventPayload>(&data).unwrap(),
+                event
+            );
+            assert!(format.deserialize_event_dyn(&data).is_ok());
+            let format = with_max_payload(data.len() - 1);
+            assert!(is_too_large(
+                format.deserialize_event::<TestEventPayload>(&data)
+            ));
+            assert!(is_too_large(format.deserialize_event_dyn(&data)));
+
+            // Malformed data under the limit returns the format's own error
+            let malformed = [0xFF; 4];
+            let result = format.deserialize_event::<TestEventPayload>(&malformed);
+            assert!(result.is_err() && !is_too_large(result));
+
+            let command = event.to_cmd();
+            let data = format.serialize_command(&command).unwrap();
+            assert_eq!(
+                with_max_payload(data.len())
+                    .deserialize_command(&data)
+                    .unwrap(),
+                command
+            );
+            assert!(is_too_large(
+                with_max_payload(data.len() - 1).deserialize_command(&data)
+            ));
+            let result = with_max_payload(data.len()).deserialize_command(&malformed);
+            assert!(result.is_err() && !is_too_large(result));
+
+            // The event type's hint tightens the limit of the format, checked once its type name is read for `Box<dyn Event>` and `Command` data
+            let format = with_max_payload(usize::MAX);
+            let data = format
+                .serialize_event(&HintedEvent("x".repeat(256)))
+                .unwrap();
+            assert!(is_too_large(format.deserialize_event::<HintedEvent>(&data)));
+            assert!(is_too_large(format.deserialize_event_dyn(&data)));
+            let data = format
+                .serialize_command(&HintedEvent("x".repeat(256)).to_cmd())
+                .unwrap();
+            assert!(is_too_large(format.deserialize_command(&data)));
+            assert!(is_too_large(format.deserialize_command_versioned(
+                &data,
+                format.negotiate(F::FORMAT_VERSION)
+            )));
+
+            // A hint larger than the limit of the format never raises it
+            let data = format
+                .serialize_event(&HintedEvent("x".to_string()))
+                .unwrap();
+            assert!(format.deserialize_event::<HintedEvent>(&data).is_ok());
+            assert!(format.deserialize_event_dyn(&data).is_ok());
+            let format = with_max_payload(data.len() - 1);
+            assert!(is_too_large(format.deserialize_event::<HintedEvent>(&data)));
+            assert!(is_too_large(format.deserialize_event_dyn(&data)));
+            let command = HintedEvent("x".to_string()).to_cmd();
+            let data = with_max_payload(usize::MAX)
+                .serialize_command(&command)
+                .unwrap();
+            assert_eq!(
+                with_max_payload(data.len())
+                    .deserialize_command(&data)
+                    .unwrap(),
+                command
+            );
+            assert!(is_too_large(
+                with_max_payload(data.len() - 1).deserialize_command(&data)
+            ));
+        }
+
+        register_event!(TestEventPayload);
+        register_event!(HintedEvent);
+        check_format(JsonSerde::with_max_payload);
+        check_format(BinarySerde::with_max_payload);
+
+        // The default limit is unlimited
+        assert_eq!(MaxPayload::<JsonSerde>::default().max_payload(), usize::MAX);
+    }
 }
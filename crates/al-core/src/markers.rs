@@ -52,12 +52,32 @@ impl<T: 'static + Send + Sync + Clone + Default + PartialEq + Any + Debug + Hash
 /// `EventMarker` trait acts as a marker for `Event` systems and should be derived for each event type
 /// It requires impl of `sealed::Marker` to ensure all required traits are impl'd
 /// _type_name is derived from the module_path and type name, eg. `my_crate::MyEvent`. Generics are included in simple name form through the `tynm` crate. This is used for event registration and lookup.
+///
+/// Events are boxed into a `Command`, so they must be `'static`. Owned data and `'static` borrows such as `Cow<'static, str>` can be used:
+/// ```
+/// #[al_core::event]
+/// struct OwnedEvent(String, std::borrow::Cow<'static, str>);
+/// ```
+/// While the `#[event]` attribute and the `EventMarker` derive reject lifetime parameters:
+/// ```compile_fail
+/// #[al_core::event]
+/// struct BorrowedEvent<'a>(&'a str);
+/// ```
+/// ```compile_fail
+/// #[derive(Clone, Default, PartialEq, Hash, Debug, al_core::DeriveEventMarker)]
+/// struct BorrowedEvent<'a>(&'a str);
+/// ```
 pub trait EventMarker: sealed::EventMarker {
     fn module_path() -> &'static str;
     /// Helper function to return the simple names of generic events
     fn type_with_generics() -> String {
         format!("{}::{}", Self::module_path(), tynm::type_name::<Self>())
     }
+    /// Maximum payload size in bytes when deserializing this event type with a `MaxPayload` format, tightening the format's limit.
+    /// A hint larger than the format's limit has no effect, and `None` uses the limit of the format
+    fn max_payload_hint() -> Option<usize> {
+        None
+    }
 }
 #[cfg(feature = "event")]
 impl<T: EventMarker> sealed::EventMarker for T {}
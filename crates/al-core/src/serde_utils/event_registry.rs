@@ -1,11 +1,12 @@
 use crate::SharedRegistry;
 use std::{
-    collections::HashMap,
+    any::TypeId,
+    collections::{hash_map::Entry, HashMap},
     sync::{Arc, RwLock},
 };
 
 /// Type alias for the event deserializer function.
-type EventDeserializer = Arc<
+pub(crate) type EventDeserializer = Arc<
     dyn for<'de> Fn(
             &mut dyn erased_serde::Deserializer<'de>,
         ) -> Result<Box<dyn crate::Event>, erased_serde::Error>
@@ -13,11 +14,64 @@ type EventDeserializer = Arc<
         + Sync,
 >;
 
+/// A registered event deserializer, along with the `TypeId` and `EventMarker::max_payload_hint()` of the event type it was registered for
+#[derive(Clone)]
+pub(crate) struct RegisteredEvent {
+    pub(crate) type_id: TypeId,
+    pub(crate) deserializer: EventDeserializer,
+    pub(crate) max_payload_hint: Option<usize>,
+}
+
+/// Error type for converting events to and from their `(type name, payload)` parts
+#[derive(Debug, Clone)]
+pub enum RegistryError {
+    /// No event type is registered with the type name
+    UnknownEvent(String),
+    /// The payload holds an event of a different type than its type name
+    TypeMismatch {
+        expected: String,
+        found: String,
+    },
+    /// The payload failed to serialize or deserialize with the `SerdeFormat`
+    Serde(String),
+    LockPoisoned(String),
+    /// A different event type is already registered with the type name
+    Conflict {
+        name: String,
+        existing_type_id: TypeId,
+        new_type_id: TypeId,
+    },
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::UnknownEvent(name) => write!(f, "No event type registered as {}", name),
+            RegistryError::TypeMismatch { expected, found } => {
+                write!(f, "Expected event type {}, found {}", expected, found)
+            }
+            RegistryError::Serde(e) => write!(f, "Event serde error: {}", e),
+            RegistryError::LockPoisoned(e) => write!(f, "Event registry lock poisoned: {}", e),
+            RegistryError::Conflict {
+                name,
+                existing_type_id,
+                new_type_id,
+            } => write!(
+                f,
+                "Event type name {} is already registered for {:?}, not {:?}",
+                name, existing_type_id, new_type_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
 /// The EventRegistry is a registry for event deserializers.
 /// It allows registering event types and retrieving their deserializers.
-/// The key for each deserializer is the events type_name()
+/// The key for each deserializer is the events type_name(), stored with the `TypeId` and `max_payload_hint()` of the event it was registered for
 pub struct EventRegistry {
-    deserializers: SharedRegistry<String, EventDeserializer>,
+    deserializers: SharedRegistry<String, RegisteredEvent>,
 }
 
 impl EventRegistry {
@@ -28,25 +82,59 @@ impl EventRegistry {
     }
 
     /// Registers an event type with its deserializer function using erased_serde.
+    /// Registering the same event type again is a no-op, while a different event type with the same type name returns `RegistryError::Conflict`
     pub fn register_event<
         E: crate::Event + crate::EventMarker + for<'de> serde::de::Deserialize<'de> + 'static,
     >(
         &self,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), RegistryError> {
         let deserializer: EventDeserializer =
             Arc::new(move |de: &mut dyn erased_serde::Deserializer<'_>| {
                 let event: E = erased_serde::deserialize(de)?;
                 Ok(Box::new(event))
             });
 
-        self.deserializers
+        let new_type_id = TypeId::of::<E>();
+        match self
+            .deserializers
             .write()
-            .map_err(|e| format!("Event serde registry write lock poisoned: {e}"))?
-            .insert(
-                <E as crate::EventMarker>::type_with_generics(),
-                deserializer,
-            );
-        Ok(())
+            .map_err(|e| {
+                RegistryError::LockPoisoned(format!(
+                    "Event serde registry write lock poisoned: {e}"
+                ))
+            })?
+            .entry(<E as crate::EventMarker>::type_with_generics())
+        {
+            Entry::Occupied(entry) if entry.get().type_id == new_type_id => Ok(()),
+            Entry::Occupied(entry) => Err(RegistryError::Conflict {
+                name: entry.key().clone(),
+                existing_type_id: entry.get().type_id,
+                new_type_id,
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(RegisteredEvent {
+                    type_id: new_type_id,
+                    deserializer,
+                    max_payload_hint: <E as crate::EventMarker>::max_payload_hint(),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the registered deserializer and `max_payload_hint()` for the given event type name, or None if not registered
+    pub(crate) fn get_registered(
+        &self,
+        type_name: &str,
+    ) -> Result<Option<RegisteredEvent>, RegistryError> {
+        Ok(self
+            .deserializers
+            .read()
+            .map_err(|e| {
+                RegistryError::LockPoisoned(format!("Event serde registry read lock poisoned: {e}"))
+            })?
+            .get(type_name)
+            .cloned())
     }
 
     /// Returns a deserializer function for the given event type name if registered, None if not registered, or an error if the lock is poisoned.
@@ -59,11 +147,51 @@ impl EventRegistry {
             .read()
             .map_err(|e| format!("Event serde registry read lock poisoned: {e}"))?
             .get(type_name.as_ref())
-            .cloned())
+            .map(|registered| registered.deserializer.clone()))
+    }
+
+    /// Serializes the event into its type name and payload, such as for storing them in separate columns.
+    /// The parts are converted back into a `Box<dyn Event>` by `registry.deserialize_event()`
+    pub fn serialize_event_parts(
+        &self,
+        event: &dyn crate::Event,
+        format: &impl crate::SerdeFormat,
+    ) -> Result<(String, Vec<u8>), RegistryError> {
+        let payload = format
+            .serialize_event(event)
+            .map_err(|e| RegistryError::Serde(e.to_string()))?;
+        Ok((event.type_with_generics(), payload))
+    }
+
+    /// Deserializes the payload from `registry.serialize_event_parts()` into a `Box<dyn Event>` with the deserializer registered in this registry for `type_name`.
+    /// Errors if the type name isn't registered, or if the type name embedded in the payload doesn't match it
+    pub fn deserialize_event(
+        &self,
+        type_name: &str,
+        payload: &[u8],
+        format: &impl crate::SerdeFormat,
+    ) -> Result<Box<dyn crate::Event>, RegistryError> {
+        let registered = self
+            .get_registered(type_name)?
+            .ok_or_else(|| RegistryError::UnknownEvent(type_name.to_string()))?;
+
+        let (event, found) =
+            crate::serde_utils::event_visitors::with_deserializer(registered, || {
+                format
+                    .deserialize_event_dyn(payload)
+                    .map_err(|e| RegistryError::Serde(e.to_string()))
+            });
+        match found {
+            Some(found) if found != type_name => Err(RegistryError::TypeMismatch {
+                expected: type_name.to_string(),
+                found,
+            }),
+            _ => event,
+        }
     }
 }
 
-/// Macro to register an event type with the global event registry.
+/// Macro to register an event type with the global event registry, panicking if it fails.
 #[macro_export]
 macro_rules! register_event {
     ($event:ty) => {{
@@ -77,7 +205,15 @@ macro_rules! register_event {
     }};
 }
 
-/// Macro to register an event type with any registry.
+/// Macro to register an event type with the global event registry, returning the `Result<(), RegistryError>`.
+#[macro_export]
+macro_rules! try_register_event {
+    ($event:ty) => {
+        $crate::EVENT_REGISTRY.register_event::<$event>()
+    };
+}
+
+/// Macro to register an event type with any registry, panicking if it fails.
 #[macro_export]
 macro_rules! register_event_with {
     ($registry:expr, $event:ty) => {{
@@ -91,3 +227,11 @@ macro_rules! register_event_with {
         }
     }};
 }
+
+/// Macro to register an event type with any registry, returning the `Result<(), RegistryError>`.
+#[macro_export]
+macro_rules! try_register_event_with {
+    ($registry:expr, $event:ty) => {
+        $registry.register_event::<$event>()
+    };
+}
@@ -1,4 +1,120 @@
+use crate::{serde_utils::event_registry::RegisteredEvent, PayloadTooLarge};
 use serde::de::{DeserializeSeed, Visitor};
+use std::cell::RefCell;
+
+/// Deserializer overriding the registry lookup for the next `Box<dyn Event>` deserialized on this thread, replaced by the type name embedded in the payload once used
+enum EventOverride {
+    Pending(RegisteredEvent),
+    Used(String),
+}
+
+/// Length of the data a `MaxPayload` format is deserializing and the limit of the format, along with the first event type limit it exceeded
+struct PayloadLimit {
+    len: usize,
+    max_payload: usize,
+    exceeded: Option<PayloadTooLarge>,
+}
+
+thread_local! {
+    static EVENT_OVERRIDE: RefCell<Option<EventOverride>> = const { RefCell::new(None) };
+    static PAYLOAD_LIMIT: RefCell<Option<PayloadLimit>> = const { RefCell::new(None) };
+}
+
+/// Restores the outer payload limit when `with_payload_limit()` returns or panics
+struct LimitGuard(Option<PayloadLimit>);
+
+impl Drop for LimitGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        PAYLOAD_LIMIT.with(|slot| *slot.borrow_mut() = previous);
+    }
+}
+
+/// Runs `f` with each `Box<dyn Event>` it deserializes checking `len` against the `max_payload_hint()` of its event type once its type name is read, where a hint can only tighten `max_payload`.
+/// Returns the result of `f` along with the `PayloadTooLarge` error for the first event type limit that was exceeded
+pub(crate) fn with_payload_limit<R>(
+    len: usize,
+    max_payload: usize,
+    f: impl FnOnce() -> R,
+) -> (R, Option<PayloadTooLarge>) {
+    let limit = PayloadLimit {
+        len,
+        max_payload,
+        exceeded: None,
+    };
+    let _guard = LimitGuard(PAYLOAD_LIMIT.with(|slot| slot.borrow_mut().replace(limit)));
+    let result = f();
+    let exceeded = PAYLOAD_LIMIT.with(|slot| {
+        slot.borrow_mut()
+            .as_mut()
+            .and_then(|limit| limit.exceeded.take())
+    });
+    (result, exceeded)
+}
+
+/// Checks the length of the data a `MaxPayload` format is deserializing, if any, against the limit of an event type
+fn check_payload_limit(max_payload_hint: Option<usize>) -> Result<(), PayloadTooLarge> {
+    PAYLOAD_LIMIT.with(|slot| match slot.borrow_mut().as_mut() {
+        Some(limit) => {
+            let max_payload =
+                max_payload_hint.map_or(limit.max_payload, |hint| hint.min(limit.max_payload));
+            match limit.len > max_payload {
+                true => {
+                    let exceeded = PayloadTooLarge {
+                        len: limit.len,
+                        max_payload,
+                    };
+                    limit.exceeded.get_or_insert(exceeded.clone());
+                    Err(exceeded)
+                }
+                false => Ok(()),
+            }
+        }
+        None => Ok(()),
+    })
+}
+
+/// Clears the override when `with_deserializer()` returns or panics, so it can't apply to a later deserialization
+struct OverrideGuard;
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        EVENT_OVERRIDE.with(|slot| slot.borrow_mut().take());
+    }
+}
+
+/// Runs `f` with the outermost `Box<dyn Event>` it deserializes using `deserializer` rather than the deserializer registered for its embedded type name.
+/// Returns the result of `f` along with the embedded type name, if `f` reached the event data. Nested events still use the registry
+pub(crate) fn with_deserializer<R>(
+    registered: RegisteredEvent,
+    f: impl FnOnce() -> R,
+) -> (R, Option<String>) {
+    EVENT_OVERRIDE.with(|slot| *slot.borrow_mut() = Some(EventOverride::Pending(registered)));
+    let _guard = OverrideGuard;
+    let result = f();
+    let found = EVENT_OVERRIDE.with(|slot| match slot.borrow_mut().take() {
+        Some(EventOverride::Used(found)) => Some(found),
+        _ => None,
+    });
+    (result, found)
+}
+
+/// Takes the pending override deserializer, recording the embedded type name it replaced
+fn take_override(type_name: &str) -> Option<RegisteredEvent> {
+    EVENT_OVERRIDE.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        match slot.take() {
+            Some(EventOverride::Pending(registered)) => {
+                *slot = Some(EventOverride::Used(type_name.to_string()));
+                Some(registered)
+            }
+            other => {
+                *slot = other;
+                None
+            }
+        }
+    })
+}
 
 /// Visitor for deserializing `Box<dyn Event>` from a tuple sequence where the first element is the event type name and the second element is the event data.
 pub(crate) struct EventVisitor<'a> {
@@ -21,18 +137,19 @@ impl<'de, 'a> Visitor<'de> for EventVisitor<'a> {
         let type_name = seq
             .next_element::<String>()?
             .ok_or_else(|| serde::de::Error::custom("Expected event type name as first element"))?;
-        // Pass the type name and the registry to the EventSeed to deserialize the event data
-        Ok(seq
-            .next_element_seed(EventSeed {
-                type_name: &type_name,
-                registry: self.registry,
-            })?
-            .ok_or_else(|| serde::de::Error::custom("Expected event data as second element"))?)
+        // Pass the type name, the registry, and any override deserializer to the EventSeed to deserialize the event data
+        seq.next_element_seed(EventSeed {
+            override_registered: take_override(&type_name),
+            type_name: &type_name,
+            registry: self.registry,
+        })?
+        .ok_or_else(|| serde::de::Error::custom("Expected event data as second element"))
     }
 }
 
-/// Seed for deserializing a specific event type using its type name and the event registry
+/// Seed for deserializing a specific event type using its type name and the event registry, unless an override deserializer is set
 struct EventSeed<'a> {
+    override_registered: Option<RegisteredEvent>,
     type_name: &'a str,
     registry: &'a crate::serde_utils::event_registry::EventRegistry,
 }
@@ -45,16 +162,23 @@ impl<'de, 'a> DeserializeSeed<'de> for EventSeed<'a> {
         deserializer: D,
     ) -> Result<Self::Value, D::Error> {
         // Get the deserializer for the given type name from the registry
-        let deser = self
-            .registry
-            .get_deserializer(self.type_name)
-            .map_err(|e| serde::de::Error::custom(format!("Registry error: {}", e)))?
-            .ok_or_else(|| {
-                serde::de::Error::custom(format!(
-                    "Error getting deserializer for Event type: {}",
-                    self.type_name
-                ))
-            })?;
+        let registered = match self.override_registered {
+            Some(registered) => registered,
+            None => self
+                .registry
+                .get_registered(self.type_name)
+                .map_err(|e| serde::de::Error::custom(format!("Registry error: {:?}", e)))?
+                .ok_or_else(|| {
+                    serde::de::Error::custom(format!(
+                        "Error getting deserializer for Event type: {}",
+                        self.type_name
+                    ))
+                })?,
+        };
+        // Check the data against the limit of the event type before deserializing it
+        check_payload_limit(registered.max_payload_hint)
+            .map_err(|e| serde::de::Error::custom(e.to_string()))?;
+        let deser = registered.deserializer;
         // Erase the deserializer and pass it to the registry deserializser function
         deser(&mut <dyn erased_serde::Deserializer>::erase(deserializer))
             .map_err(|e| serde::de::Error::custom(e.to_string()))
@@ -3,4 +3,4 @@ pub mod event_registry;
 #[cfg(feature = "event")]
 pub mod event_visitors;
 #[cfg(any(feature = "event", feature = "command"))]
-pub mod serde_format;
\ No newline at end of file
+pub mod serde_format;
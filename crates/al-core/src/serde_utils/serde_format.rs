@@ -2,15 +2,80 @@
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct SerdeWrapper<T>(String, T);
 impl<T> SerdeWrapper<T> {
+    #[allow(clippy::wrong_self_convention)]
     pub fn as_result<E>(self) -> Result<T, E> {
         Ok(self.1)
     }
 }
 
+/// Magic byte prefixing versioned `Command` envelopes, distinguishing them from headerless legacy envelopes.
+pub const FORMAT_MAGIC: u8 = 0xA7;
+
+/// The `Command` envelope version agreed on with a peer, where version `0` is the headerless legacy envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EffectiveVersion(pub u8);
+
+impl EffectiveVersion {
+    pub const LEGACY: Self = Self(0);
+
+    /// Returns true if the version is the headerless legacy envelope
+    pub fn is_legacy(&self) -> bool {
+        *self == Self::LEGACY
+    }
+}
+
+/// The first envelope version carrying the `CommandMeta` of events, as a `(Command, Option<CommandMeta>)` payload.
+#[cfg(feature = "command")]
+pub const COMMAND_META_VERSION: EffectiveVersion = EffectiveVersion(2);
+
+/// Prefixes the serialized payload with the `[FORMAT_MAGIC, version]` header, leaving legacy payloads headerless.
+pub fn write_header(version: EffectiveVersion, payload: Vec<u8>) -> Vec<u8> {
+    if version.is_legacy() {
+        return payload;
+    }
+    let mut data = Vec::with_capacity(payload.len() + 2);
+    data.push(FORMAT_MAGIC);
+    data.push(version.0);
+    data.extend(payload);
+    data
+}
+
+/// Splits the header from the data, returning the envelope version and payload.
+/// Data not starting with `FORMAT_MAGIC` is the headerless legacy envelope, while a version above `max_version` returns an error.
+/// Sniffing is safe for `JsonSerde`, as valid JSON never starts with `0xA7`, and for `BinarySerde`, whose legacy envelopes start with the `Command` variant index
+pub fn read_header(
+    data: &[u8],
+    max_version: u8,
+) -> Result<(EffectiveVersion, &[u8]), Box<dyn std::error::Error>> {
+    match data {
+        [FORMAT_MAGIC, version, payload @ ..] if *version <= max_version => {
+            Ok((EffectiveVersion(*version), payload))
+        }
+        [FORMAT_MAGIC, version, ..] => Err(unsupported_version(*version, max_version)),
+        [FORMAT_MAGIC] => Err("Missing format version after the format magic byte".into()),
+        _ => Ok((EffectiveVersion::LEGACY, data)),
+    }
+}
+
+fn unsupported_version(version: u8, max_version: u8) -> Box<dyn std::error::Error> {
+    format!(
+        "Unsupported format version `{version}`, the highest supported version is `{max_version}`"
+    )
+    .into()
+}
+
 /// A trait for serializing and deserializing events and commands using specified formats.
 pub trait SerdeFormat:
     Send + Sync + Clone + Default + PartialEq + std::any::Any + std::fmt::Debug + std::hash::Hash
 {
+    /// The highest `Command` envelope version this format writes and reads, defaulting to the headerless legacy version.
+    const FORMAT_VERSION: u8 = 0;
+
+    /// Returns the highest envelope version supported by both this format and the peer.
+    fn negotiate(&self, peer_version: u8) -> EffectiveVersion {
+        EffectiveVersion(Self::FORMAT_VERSION.min(peer_version))
+    }
+
     #[cfg(feature = "event")]
     /// Serialize the passed event into a vector of bytes.
     fn serialize_event(
@@ -22,7 +87,10 @@ pub trait SerdeFormat:
     /// Deserialize an event as type T from the passed byte slice.
     fn deserialize_event<T>(&self, data: &[u8]) -> Result<T, Box<dyn std::error::Error>>
     where
-        T: crate::Event + crate::EventRequirements + for<'de> serde::Deserialize<'de>;
+        T: crate::Event
+            + crate::EventMarker
+            + crate::EventRequirements
+            + for<'de> serde::Deserialize<'de>;
 
     #[cfg(feature = "event")]
     /// Deserialize an event as a `Box<dyn Event>` from the passed byte slice.
@@ -32,18 +100,46 @@ pub trait SerdeFormat:
     ) -> Result<Box<dyn crate::Event>, Box<dyn std::error::Error>>;
 
     #[cfg(feature = "command")]
-    /// Serialize the passed command into a vector of bytes.
+    /// Serialize the passed command into a vector of bytes, with the header for `FORMAT_VERSION`.
     fn serialize_command(
         &self,
         command: &crate::Command,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
 
     #[cfg(feature = "command")]
-    /// Deserialize a command from the passed byte slice.
-    fn deserialize_command<'a>(
+    /// Serialize the passed command into a vector of bytes, with the header for the passed `EffectiveVersion`.
+    /// The default only writes the headerless legacy version through `serialize_command`
+    fn serialize_command_versioned(
         &self,
-        data: &'a [u8],
+        command: &crate::Command,
+        version: EffectiveVersion,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match version.is_legacy() {
+            true => self.serialize_command(command),
+            false => Err(unsupported_version(version.0, Self::FORMAT_VERSION)),
+        }
+    }
+
+    #[cfg(feature = "command")]
+    /// Deserialize a command from the passed byte slice, with the header for `FORMAT_VERSION` or below.
+    fn deserialize_command(
+        &self,
+        data: &[u8],
     ) -> Result<crate::Command, Box<dyn std::error::Error>>;
+
+    #[cfg(feature = "command")]
+    /// Deserialize a command from the passed byte slice, written by a peer using the passed `EffectiveVersion`.
+    /// The default only reads the headerless legacy version through `deserialize_command`
+    fn deserialize_command_versioned(
+        &self,
+        data: &[u8],
+        version: EffectiveVersion,
+    ) -> Result<crate::Command, Box<dyn std::error::Error>> {
+        match version.is_legacy() {
+            true => self.deserialize_command(data),
+            false => Err(unsupported_version(version.0, Self::FORMAT_VERSION)),
+        }
+    }
 }
 
 /// A JSON-based implementation of the SerdeFormat trait using serde_json.
@@ -53,6 +149,8 @@ pub struct JsonSerde;
 
 #[cfg(feature = "json")]
 impl SerdeFormat for JsonSerde {
+    const FORMAT_VERSION: u8 = 2;
+
     #[cfg(feature = "event")]
     fn serialize_event(
         &self,
@@ -64,7 +162,11 @@ impl SerdeFormat for JsonSerde {
     #[cfg(feature = "event")]
     fn deserialize_event<T>(&self, data: &[u8]) -> Result<T, Box<dyn std::error::Error>>
     where
-        T: crate::Event + crate::EventRequirements + for<'de> serde::Deserialize<'de> + 'static,
+        T: crate::Event
+            + crate::EventMarker
+            + crate::EventRequirements
+            + for<'de> serde::Deserialize<'de>
+            + 'static,
     {
         serde_json::from_slice::<SerdeWrapper<T>>(data)
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?
@@ -79,20 +181,54 @@ impl SerdeFormat for JsonSerde {
         serde_json::from_slice(data).map_err(|e| e.into())
     }
 
+    #[cfg(feature = "command")]
+    fn serialize_command_versioned(
+        &self,
+        command: &crate::Command,
+        version: EffectiveVersion,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let payload = if version >= COMMAND_META_VERSION {
+            serde_json::to_vec(&(command, command.meta()))?
+        } else {
+            serde_json::to_vec(command)?
+        };
+        Ok(write_header(version, payload))
+    }
+
     #[cfg(feature = "command")]
     fn serialize_command(
         &self,
         command: &crate::Command,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        serde_json::to_vec(command).map_err(|e| e.into())
+        self.serialize_command_versioned(command, EffectiveVersion(Self::FORMAT_VERSION))
     }
 
     #[cfg(feature = "command")]
-    fn deserialize_command<'a>(
+    fn deserialize_command(
         &self,
-        data: &'a [u8],
+        data: &[u8],
     ) -> Result<crate::Command, Box<dyn std::error::Error>> {
-        serde_json::from_slice(data).map_err(|e| e.into())
+        self.deserialize_command_versioned(data, EffectiveVersion(Self::FORMAT_VERSION))
+    }
+
+    #[cfg(feature = "command")]
+    fn deserialize_command_versioned(
+        &self,
+        data: &[u8],
+        version: EffectiveVersion,
+    ) -> Result<crate::Command, Box<dyn std::error::Error>> {
+        // Versions before `COMMAND_META_VERSION` share the legacy payload shape without meta
+        let (version, payload) = match version.is_legacy() {
+            true => (version, data),
+            false => read_header(data, version.0.min(Self::FORMAT_VERSION))?,
+        };
+        if version >= COMMAND_META_VERSION {
+            let (command, meta): (crate::Command, Option<crate::CommandMeta>) =
+                serde_json::from_slice(payload)?;
+            Ok(command.with_meta(meta))
+        } else {
+            serde_json::from_slice(payload).map_err(|e| e.into())
+        }
     }
 }
 
@@ -103,6 +239,8 @@ pub struct BinarySerde;
 
 #[cfg(feature = "binary")]
 impl SerdeFormat for BinarySerde {
+    const FORMAT_VERSION: u8 = 2;
+
     #[cfg(feature = "event")]
     fn serialize_event(
         &self,
@@ -114,7 +252,10 @@ impl SerdeFormat for BinarySerde {
     #[cfg(feature = "event")]
     fn deserialize_event<T>(&self, data: &[u8]) -> Result<T, Box<dyn std::error::Error>>
     where
-        T: crate::Event + crate::EventRequirements + for<'de> serde::Deserialize<'de>,
+        T: crate::Event
+            + crate::EventMarker
+            + crate::EventRequirements
+            + for<'de> serde::Deserialize<'de>,
     {
         bitcode::deserialize::<SerdeWrapper<T>>(data)
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?
@@ -129,19 +270,236 @@ impl SerdeFormat for BinarySerde {
         bitcode::deserialize(data).map_err(|e| e.into())
     }
 
+    #[cfg(feature = "command")]
+    fn serialize_command_versioned(
+        &self,
+        command: &crate::Command,
+        version: EffectiveVersion,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let payload = if version >= COMMAND_META_VERSION {
+            bitcode::serialize(&(command, command.meta()))?
+        } else {
+            bitcode::serialize(command)?
+        };
+        Ok(write_header(version, payload))
+    }
+
     #[cfg(feature = "command")]
     fn serialize_command(
         &self,
         command: &crate::Command,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        bitcode::serialize(command).map_err(|e| e.into())
+        self.serialize_command_versioned(command, EffectiveVersion(Self::FORMAT_VERSION))
+    }
+
+    #[cfg(feature = "command")]
+    fn deserialize_command(
+        &self,
+        data: &[u8],
+    ) -> Result<crate::Command, Box<dyn std::error::Error>> {
+        self.deserialize_command_versioned(data, EffectiveVersion(Self::FORMAT_VERSION))
     }
 
     #[cfg(feature = "command")]
-    fn deserialize_command<'a>(
+    fn deserialize_command_versioned(
         &self,
-        data: &'a [u8],
+        data: &[u8],
+        version: EffectiveVersion,
     ) -> Result<crate::Command, Box<dyn std::error::Error>> {
-        bitcode::deserialize(data).map_err(|e| e.into())
+        // Versions before `COMMAND_META_VERSION` share the legacy payload shape without meta
+        let (version, payload) = match version.is_legacy() {
+            true => (version, data),
+            false => read_header(data, version.0.min(Self::FORMAT_VERSION))?,
+        };
+        if version >= COMMAND_META_VERSION {
+            let (command, meta): (crate::Command, Option<crate::CommandMeta>) =
+                bitcode::deserialize(payload)?;
+            Ok(command.with_meta(meta))
+        } else {
+            bitcode::deserialize(payload).map_err(|e| e.into())
+        }
+    }
+}
+
+/// Error returned when the data passed to a `MaxPayload` format is larger than its limit, before any parsing is attempted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadTooLarge {
+    pub len: usize,
+    pub max_payload: usize,
+}
+
+impl std::fmt::Display for PayloadTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Payload of {} bytes exceeds the maximum payload of {} bytes",
+            self.len, self.max_payload
+        )
+    }
+}
+
+impl std::error::Error for PayloadTooLarge {}
+
+/// Wraps a `SerdeFormat`, rejecting data larger than `max_payload` bytes with a `PayloadTooLarge` error before deserializing it.
+/// An event type can tighten the limit with its `EventMarker::max_payload_hint()`. Data that may hold any registered event type is checked against `max_payload` before parsing, then against the tighter hint of each event type once its type name is read
+#[derive(Clone, PartialEq, Debug, Hash)]
+pub struct MaxPayload<F> {
+    format: F,
+    max_payload: usize,
+}
+
+/// Defaults to an unlimited payload size
+impl<F: Default> Default for MaxPayload<F> {
+    fn default() -> Self {
+        Self::new(F::default(), usize::MAX)
+    }
+}
+
+impl<F> MaxPayload<F> {
+    pub fn new(format: F, max_payload: usize) -> Self {
+        Self {
+            format,
+            max_payload,
+        }
+    }
+
+    pub fn format(&self) -> &F {
+        &self.format
+    }
+
+    pub fn max_payload(&self) -> usize {
+        self.max_payload
+    }
+
+    /// Returns a `PayloadTooLarge` error if the data is larger than `max_payload`
+    fn check(data: &[u8], max_payload: usize) -> Result<(), Box<dyn std::error::Error>> {
+        match data.len() > max_payload {
+            true => Err(Box::new(PayloadTooLarge {
+                len: data.len(),
+                max_payload,
+            })),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<F: SerdeFormat> MaxPayload<F> {
+    /// Deserializes data that may hold any registered event type with `f`, checking it against `max_payload` before parsing and against the hint of each event type once its type name is read
+    fn deserialize_dyn<R>(
+        &self,
+        data: &[u8],
+        f: impl FnOnce(&F, &[u8]) -> Result<R, Box<dyn std::error::Error>>,
+    ) -> Result<R, Box<dyn std::error::Error>> {
+        #[cfg(feature = "event")]
+        {
+            Self::check(data, self.max_payload)?;
+            let (result, exceeded) = crate::serde_utils::event_visitors::with_payload_limit(
+                data.len(),
+                self.max_payload,
+                || f(&self.format, data),
+            );
+            match exceeded {
+                Some(exceeded) => Err(Box::new(exceeded)),
+                None => result,
+            }
+        }
+        #[cfg(not(feature = "event"))]
+        {
+            Self::check(data, self.max_payload)?;
+            f(&self.format, data)
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl JsonSerde {
+    /// Creates a `JsonSerde` rejecting data larger than `max_payload` bytes.
+    /// `serde_json` also limits the nesting depth of the data it parses
+    pub fn with_max_payload(max_payload: usize) -> MaxPayload<Self> {
+        MaxPayload::new(Self, max_payload)
+    }
+}
+
+#[cfg(feature = "binary")]
+impl BinarySerde {
+    /// Creates a `BinarySerde` rejecting data larger than `max_payload` bytes
+    pub fn with_max_payload(max_payload: usize) -> MaxPayload<Self> {
+        MaxPayload::new(Self, max_payload)
+    }
+}
+
+impl<F: SerdeFormat> SerdeFormat for MaxPayload<F> {
+    const FORMAT_VERSION: u8 = F::FORMAT_VERSION;
+
+    fn negotiate(&self, peer_version: u8) -> EffectiveVersion {
+        self.format.negotiate(peer_version)
+    }
+
+    #[cfg(feature = "event")]
+    fn serialize_event(
+        &self,
+        event: &dyn crate::Event,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.format.serialize_event(event)
+    }
+
+    #[cfg(feature = "event")]
+    fn deserialize_event<T>(&self, data: &[u8]) -> Result<T, Box<dyn std::error::Error>>
+    where
+        T: crate::Event
+            + crate::EventMarker
+            + crate::EventRequirements
+            + for<'de> serde::Deserialize<'de>,
+    {
+        Self::check(
+            data,
+            <T as crate::EventMarker>::max_payload_hint()
+                .map_or(self.max_payload, |hint| hint.min(self.max_payload)),
+        )?;
+        self.format.deserialize_event(data)
+    }
+
+    #[cfg(feature = "event")]
+    fn deserialize_event_dyn(
+        &self,
+        data: &[u8],
+    ) -> Result<Box<dyn crate::Event>, Box<dyn std::error::Error>> {
+        self.deserialize_dyn(data, |format, data| format.deserialize_event_dyn(data))
+    }
+
+    #[cfg(feature = "command")]
+    fn serialize_command(
+        &self,
+        command: &crate::Command,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.format.serialize_command(command)
+    }
+
+    #[cfg(feature = "command")]
+    fn serialize_command_versioned(
+        &self,
+        command: &crate::Command,
+        version: EffectiveVersion,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.format.serialize_command_versioned(command, version)
+    }
+
+    #[cfg(feature = "command")]
+    fn deserialize_command(
+        &self,
+        data: &[u8],
+    ) -> Result<crate::Command, Box<dyn std::error::Error>> {
+        self.deserialize_dyn(data, |format, data| format.deserialize_command(data))
+    }
+
+    #[cfg(feature = "command")]
+    fn deserialize_command_versioned(
+        &self,
+        data: &[u8],
+        version: EffectiveVersion,
+    ) -> Result<crate::Command, Box<dyn std::error::Error>> {
+        self.deserialize_dyn(data, |format, data| {
+            format.deserialize_command_versioned(data, version)
+        })
     }
 }
@@ -1,18 +1,48 @@
-use crate::{TaskConfig, TaskError, TaskMode, TaskState, TaskTypes};
+use crate::{
+    MultiJob, MultiTaskState, TaskCompletion, TaskConfig, TaskError, TaskMode, TaskState,
+    TaskStateRequirements, TaskTypes,
+};
 use al_derive::with_bounds;
 use std::sync::Arc;
 use std::{future::Future, marker::PhantomData};
-use tokio::sync::RwLock;
+use tokio::runtime::Handle;
+use tokio::sync::{watch, Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tokio::time::Instant;
 
+/// Lifecycle of the spawned thread of a `Task`, watched by `task.wait_started()` and `task.wait_stopped()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskPhase {
+    Pending,
+    Running,
+    Stopped,
+}
+
+/// Marks the `Task` as running, then as stopped once dropped, whether the loop completed, panicked, or was aborted
+struct TaskPhaseGuard(Arc<watch::Sender<TaskPhase>>);
+
+impl TaskPhaseGuard {
+    fn running(phase: Arc<watch::Sender<TaskPhase>>) -> Self {
+        phase.send_replace(TaskPhase::Running);
+        Self(phase)
+    }
+}
+
+impl Drop for TaskPhaseGuard {
+    fn drop(&mut self) {
+        self.0.send_replace(TaskPhase::Stopped);
+    }
+}
+
 /// `Task` handles the interactions and state of the background thread it spawned
 #[derive(Debug)]
 pub struct Task<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> {
     handle: Option<JoinHandle<()>>,
     panicked: Arc<RwLock<bool>>,
     cancelled: Arc<RwLock<bool>>,
+    phase: Arc<watch::Sender<TaskPhase>>,
     state: Arc<RwLock<S>>,
+    runtime: Handle,
     _phantom: std::marker::PhantomData<(T, E)>,
 }
 
@@ -26,6 +56,7 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Drop for Task<T, E, S> {
 /// Implement `Task`, generating the actual functions with the `with_common_bounds` macro
 impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
     /// Constant to allow `Task::NO_CONDITION` rather than specifying `None::<...>`
+    #[allow(clippy::type_complexity)]
     pub const NO_CONDITION: Option<
         fn(&Arc<RwLock<S>>) -> std::pin::Pin<Box<dyn Future<Output = bool> + Send + Sync>>,
     > = None;
@@ -38,25 +69,89 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
     /// Creates a `Task` with the default `TaskConfig`
     #[with_bounds(F)]
     pub fn infinite(f: F, state: S) -> Self {
-        Self::_infinite(f, TaskMode::Infinite, state)
+        Self::_infinite(f, TaskMode::Infinite, state, Handle::current())
+    }
+
+    /// Creates a `Task` with the default `TaskConfig`, spawned on the `runtime` rather than the ambient runtime
+    #[with_bounds(F)]
+    pub fn infinite_on(runtime: Handle, f: F, state: S) -> Self {
+        Self::_infinite(f, TaskMode::Infinite, state, runtime)
     }
 
     /// Creates a `Task` that runs a fixed number of times, with the default `TaskConfig`
     #[with_bounds(F)]
     pub fn fixed(iterations: usize, f: F, state: S) -> Self {
-        Self::_fixed(f, TaskMode::Fixed(iterations), state)
+        Self::_fixed(f, TaskMode::Fixed(iterations), state, Handle::current())
+    }
+
+    /// Creates a `Task` that runs a fixed number of times, with the default `TaskConfig`, spawned on the `runtime` rather than the ambient runtime
+    #[with_bounds(F)]
+    pub fn fixed_on(runtime: Handle, iterations: usize, f: F, state: S) -> Self {
+        Self::_fixed(f, TaskMode::Fixed(iterations), state, runtime)
     }
 
     /// Creates a `Task` that runs for a specific duration, with the default `TaskConfig`
     #[with_bounds(F)]
     pub fn for_duration(duration: std::time::Duration, f: F, state: S) -> Self {
-        Self::_duration(f, TaskMode::Duration(duration), state, Instant::now())
+        Self::_duration(
+            f,
+            TaskMode::Duration(duration),
+            state,
+            Instant::now(),
+            Handle::current(),
+        )
+    }
+
+    /// Creates a `Task` that runs for a specific duration measured from `start_time`, with the default `TaskConfig`.
+    /// Along with `tokio::time::pause()`, allows tests to control when the duration started.
+    /// Placing the start 25 seconds in the past leaves only 5 seconds of a 30 second `Task` to run, which the paused clock skips through instantly:
+    /// ```
+    /// use al_core::{BaseTaskState, Task};
+    /// use std::time::Duration;
+    /// use tokio::time::Instant;
+    ///
+    /// #[tokio::main(flavor = "current_thread", start_paused = true)]
+    /// async fn main() {
+    ///     let start = Instant::now();
+    ///     tokio::time::advance(Duration::from_secs(25)).await;
+    ///     let mut task = Task::for_duration_since(
+    ///         start,
+    ///         Duration::from_secs(30),
+    ///         |_, _| async move { Ok::<_, ()>(Instant::now()) },
+    ///         BaseTaskState::default(),
+    ///     );
+    ///     let last = task.wait_for_complete().await.unwrap().unwrap();
+    ///     assert!(last - start >= Duration::from_secs(25));
+    ///     assert!(last - start <= Duration::from_secs(30));
+    /// }
+    /// ```
+    #[cfg(feature = "virtual-time")]
+    #[with_bounds(F)]
+    pub fn for_duration_since(
+        start_time: Instant,
+        duration: std::time::Duration,
+        f: F,
+        state: S,
+    ) -> Self {
+        Self::_duration(
+            f,
+            TaskMode::Duration(duration),
+            state,
+            start_time,
+            Handle::current(),
+        )
     }
 
     /// Creates a `Task` that runs until a condition is met, with the default `TaskConfig`
     #[with_bounds(F, C)]
     pub fn until_condition(f: F, state: S, condition: C) -> Self {
-        Self::_conditional(f, TaskMode::Conditional, state, condition)
+        Self::_conditional(
+            f,
+            TaskMode::Conditional,
+            state,
+            condition,
+            Handle::current(),
+        )
     }
 
     /// Creates a `Task` with a specific `TaskConfig`
@@ -68,9 +163,10 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
         condition: Option<C>,
     ) -> Result<Self, TaskError> {
         let config = config.into();
+        let runtime = config.runtime_handle()?;
         match config.mode() {
-            TaskMode::Infinite => Ok(Task::_infinite(f, config, state)),
-            TaskMode::Fixed(_) => Ok(Task::_fixed(f, config, state)),
+            TaskMode::Infinite => Ok(Task::_infinite(f, config, state, runtime)),
+            TaskMode::Fixed(_) => Ok(Task::_fixed(f, config, state, runtime)),
             TaskMode::Conditional => Ok(Task::_conditional(
                 f,
                 config,
@@ -80,52 +176,229 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
                         "Missing condition function for `TaskMode::Conditional`".to_string(),
                     )
                 })?,
+                runtime,
             )),
-            TaskMode::Duration(_) => Ok(Task::_duration(f, config, state, Instant::now())),
+            TaskMode::Duration(_) => Ok(Task::_duration(f, config, state, Instant::now(), runtime)),
+        }
+    }
+
+    /// Creates a `Task` that runs `init` once before the loop, passing its resource to every iteration, with the default `TaskConfig`
+    #[with_bounds(I, FR)]
+    pub fn infinite_with_init(init: I, f: F, state: S) -> Self {
+        Self::_with_init(
+            init,
+            f,
+            TaskMode::Infinite,
+            state,
+            Self::NO_CONDITION,
+            Handle::current(),
+        )
+    }
+
+    /// Creates a `Task` that runs `init` once before the loop, passing its resource to every iteration, with a specific `TaskConfig`
+    #[with_bounds(I, FR, C)]
+    pub fn with_init(
+        init: I,
+        f: F,
+        config: impl Into<TaskConfig>,
+        state: S,
+        condition: Option<C>,
+    ) -> Result<Self, TaskError> {
+        let config = config.into();
+        if *config.mode() == TaskMode::Conditional && condition.is_none() {
+            return Err(TaskError::NoCondition(
+                "Missing condition function for `TaskMode::Conditional`".to_string(),
+            ));
+        }
+        let runtime = config.runtime_handle()?;
+        Ok(Self::_with_init(init, f, config, state, condition, runtime))
+    }
+
+    /// Starts a `Task` that awaits `init` before the loop, checking cancelation along with all `TaskMode` completions
+    #[with_bounds(I, FR, C)]
+    fn _with_init(
+        init: I,
+        mut f: F,
+        config: impl Into<TaskConfig>,
+        state: S,
+        condition: Option<C>,
+        runtime: Handle,
+    ) -> Self {
+        Self::_spawn_loop(
+            init,
+            move |iteration, state, resource| {
+                let result = f(iteration, state, resource);
+                async move { Some(result.await) }
+            },
+            config,
+            state,
+            condition,
+            runtime,
+        )
+    }
+
+    /// Starts a `Task` that awaits `init` before a loop of `step`, checking cancelation along with all `TaskMode` completions.
+    /// A `step` returning `None` ran nothing worth recording, leaving the last result unchanged while still counting the iteration
+    #[with_bounds(I, C)]
+    fn _spawn_loop<St, FutSt>(
+        init: I,
+        mut step: St,
+        config: impl Into<TaskConfig>,
+        state: S,
+        mut condition: Option<C>,
+        runtime: Handle,
+    ) -> Self
+    where
+        St: FnMut(usize, &Arc<RwLock<S>>, &mut R) -> FutSt + Send + Sync + 'static,
+        FutSt: Future<Output = Option<Result<T, E>>> + Send + Sync + 'static,
+    {
+        let config = config.into();
+
+        let cancelled = Arc::new(RwLock::new(false));
+        let cancelled_clone = cancelled.clone();
+
+        let phase = Arc::new(watch::channel(TaskPhase::Pending).0);
+        let phase_clone = phase.clone();
+
+        let state = Arc::new(RwLock::new(state));
+        let state_clone = state.clone();
+
+        let handle = runtime.spawn(async move {
+            config.on_task_start();
+            let _phase = TaskPhaseGuard::running(phase_clone);
+            let mut resource = init(&state_clone).await;
+            let start_time = Instant::now();
+            let mut iteration = 0usize;
+            let mut interval = tokio::time::interval(config.interval());
+
+            loop {
+                // Check if cancelled
+                if *cancelled_clone.read().await {
+                    break;
+                }
+
+                // Check iterations and duration completion
+                if config.check_iterations(iteration).await
+                    || config.check_duration(start_time).await
+                {
+                    break;
+                }
+
+                // Check completion conditions
+                if let Some(condition) = &mut condition {
+                    if condition(&state_clone).await {
+                        break;
+                    }
+                }
+
+                // Execute the step, timing it if enabled
+                let start = config.is_collecting_timing().then(Instant::now);
+                let result = step(iteration, &state_clone, &mut resource).await;
+                let elapsed = start.map(|start| start.elapsed());
+
+                // Update the state
+                match result {
+                    // Check if last result causes a stop
+                    Some(result) if config.stop_on_error() && result.is_err() => {
+                        Task::set_state(
+                            &mut *state_clone.write().await,
+                            iteration,
+                            result,
+                            elapsed,
+                        )
+                        .await;
+                        break;
+                    }
+                    Some(result) => {
+                        Task::set_state(&mut *state_clone.write().await, iteration, result, elapsed)
+                            .await
+                    }
+                    None => {
+                        let mut state = state_clone.write().await;
+                        state.set_iteration(iteration + 1);
+                        if let Some(elapsed) = elapsed {
+                            state.record_timing(elapsed);
+                        }
+                    }
+                }
+
+                // Check interval bounds
+                if iteration == usize::MAX {
+                    iteration = 0;
+                }
+
+                iteration += 1;
+                interval.tick().await;
+            }
+
+            // Call `Task` complete function and mark state as not running
+            let mut state = state_clone.write().await;
+            config.on_task_complete();
+            state.set_is_running(false);
+        });
+
+        Self {
+            handle: Some(handle),
+            runtime,
+            panicked: Arc::new(RwLock::new(false)),
+            cancelled,
+            phase,
+            state,
+            _phantom: PhantomData::<(T, E)>,
         }
     }
 
     /// Starts a `Task` with a infinite structure, only checking cancelation
     #[with_bounds(F)]
-    fn _infinite(mut f: F, config: impl Into<TaskConfig>, state: S) -> Self {
+    fn _infinite(mut f: F, config: impl Into<TaskConfig>, state: S, runtime: Handle) -> Self {
         let config = config.into();
 
         let cancelled = Arc::new(RwLock::new(false));
         let cancelled_clone = cancelled.clone();
 
+        let phase = Arc::new(watch::channel(TaskPhase::Pending).0);
+        let phase_clone = phase.clone();
+
         let state = Arc::new(RwLock::new(state));
         let state_clone = state.clone();
 
-        let handle = tokio::spawn(async move {
+        let handle = runtime.spawn(async move {
             config.on_task_start();
+            let _phase = TaskPhaseGuard::running(phase_clone);
             let mut iteration = 0usize;
             let mut interval = tokio::time::interval(config.interval());
 
             loop {
                 // Check if cancelled
-                if let true = *cancelled_clone.read().await {
+                if *cancelled_clone.read().await {
                     break;
                 }
 
-                // Execute the closure
+                // Execute the closure, timing it if enabled
+                let start = config.is_collecting_timing().then(Instant::now);
                 let result = f(iteration, &state_clone).await;
+                let elapsed = start.map(|start| start.elapsed());
 
                 // Update the state
                 {
                     // Check if last result causes a stop
-                    if config.stop_on_error() {
-                        if let Err(_) = result {
-                            Task::set_state(&mut *state_clone.write().await, iteration, result)
-                                .await;
-                            break;
-                        }
+                    if config.stop_on_error() && result.is_err() {
+                        Task::set_state(
+                            &mut *state_clone.write().await,
+                            iteration,
+                            result,
+                            elapsed,
+                        )
+                        .await;
+                        break;
                     }
 
-                    Task::set_state(&mut *state_clone.write().await, iteration, result).await;
+                    Task::set_state(&mut *state_clone.write().await, iteration, result, elapsed)
+                        .await;
                 }
 
                 // Check interval bounds
-                if iteration >= usize::max_value() {
+                if iteration == usize::MAX {
                     iteration = 0;
                 }
 
@@ -141,8 +414,10 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
 
         Self {
             handle: Some(handle),
+            runtime,
             panicked: Arc::new(RwLock::new(false)),
             cancelled,
+            phase,
             state,
             _phantom: PhantomData::<(T, E)>,
         }
@@ -150,23 +425,27 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
 
     /// Starts a `Task` with a fixed structure, checking cancelation along with iterations
     #[with_bounds(F)]
-    fn _fixed(mut f: F, config: impl Into<TaskConfig>, state: S) -> Self {
+    fn _fixed(mut f: F, config: impl Into<TaskConfig>, state: S, runtime: Handle) -> Self {
         let config = config.into();
 
         let cancelled = Arc::new(RwLock::new(false));
         let cancelled_clone = cancelled.clone();
 
+        let phase = Arc::new(watch::channel(TaskPhase::Pending).0);
+        let phase_clone = phase.clone();
+
         let state = Arc::new(RwLock::new(state));
         let state_clone = state.clone();
 
-        let handle = tokio::spawn(async move {
+        let handle = runtime.spawn(async move {
             config.on_task_start();
+            let _phase = TaskPhaseGuard::running(phase_clone);
             let mut iteration = 0usize;
             let mut interval = tokio::time::interval(config.interval());
 
             loop {
                 // Check if cancelled
-                if let true = *cancelled_clone.read().await {
+                if *cancelled_clone.read().await {
                     break;
                 }
 
@@ -175,24 +454,30 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
                     break;
                 }
 
-                // Execute the closure
+                // Execute the closure, timing it if enabled
+                let start = config.is_collecting_timing().then(Instant::now);
                 let result = f(iteration, &state_clone).await;
+                let elapsed = start.map(|start| start.elapsed());
 
                 // Update the state
                 {
                     // Check if last result causes a stop
-                    if config.stop_on_error() {
-                        if let Err(_) = result {
-                            Task::set_state(&mut *state_clone.write().await, iteration, result)
-                                .await;
-                            break;
-                        }
+                    if config.stop_on_error() && result.is_err() {
+                        Task::set_state(
+                            &mut *state_clone.write().await,
+                            iteration,
+                            result,
+                            elapsed,
+                        )
+                        .await;
+                        break;
                     }
-                    Task::set_state(&mut *state_clone.write().await, iteration, result).await;
+                    Task::set_state(&mut *state_clone.write().await, iteration, result, elapsed)
+                        .await;
                 }
 
                 // Check interval bounds
-                if iteration >= usize::max_value() {
+                if iteration == usize::MAX {
                     iteration = 0;
                 }
 
@@ -208,8 +493,10 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
 
         Self {
             handle: Some(handle),
+            runtime,
             panicked: Arc::new(RwLock::new(false)),
             cancelled,
+            phase,
             state,
             _phantom: PhantomData::<(T, E)>,
         }
@@ -217,23 +504,33 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
 
     /// Starts a `Task` with a conditional structure, checking cancelation along with conditions
     #[with_bounds(F, C)]
-    fn _conditional(mut f: F, config: impl Into<TaskConfig>, state: S, mut condition: C) -> Self {
+    fn _conditional(
+        mut f: F,
+        config: impl Into<TaskConfig>,
+        state: S,
+        mut condition: C,
+        runtime: Handle,
+    ) -> Self {
         let config = config.into();
 
         let cancelled = Arc::new(RwLock::new(false));
         let cancelled_clone = cancelled.clone();
 
+        let phase = Arc::new(watch::channel(TaskPhase::Pending).0);
+        let phase_clone = phase.clone();
+
         let state = Arc::new(RwLock::new(state));
         let state_clone = state.clone();
 
-        let handle = tokio::spawn(async move {
+        let handle = runtime.spawn(async move {
             config.on_task_start();
+            let _phase = TaskPhaseGuard::running(phase_clone);
             let mut iteration = 0usize;
             let mut interval = tokio::time::interval(config.interval());
 
             loop {
                 // Check if cancelled
-                if let true = *cancelled_clone.read().await {
+                if *cancelled_clone.read().await {
                     break;
                 }
 
@@ -242,24 +539,30 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
                     break;
                 }
 
-                // Execute the closure
+                // Execute the closure, timing it if enabled
+                let start = config.is_collecting_timing().then(Instant::now);
                 let result = f(iteration, &state_clone).await;
+                let elapsed = start.map(|start| start.elapsed());
 
                 // Update the state
                 {
                     // Check if last result causes a stop
-                    if config.stop_on_error() {
-                        if let Err(_) = result {
-                            Task::set_state(&mut *state_clone.write().await, iteration, result)
-                                .await;
-                            break;
-                        }
+                    if config.stop_on_error() && result.is_err() {
+                        Task::set_state(
+                            &mut *state_clone.write().await,
+                            iteration,
+                            result,
+                            elapsed,
+                        )
+                        .await;
+                        break;
                     }
-                    Task::set_state(&mut *state_clone.write().await, iteration, result).await;
+                    Task::set_state(&mut *state_clone.write().await, iteration, result, elapsed)
+                        .await;
                 }
 
                 // Check interval bounds
-                if iteration >= usize::max_value() {
+                if iteration == usize::MAX {
                     iteration = 0;
                 }
 
@@ -275,8 +578,10 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
 
         Self {
             handle: Some(handle),
+            runtime,
             panicked: Arc::new(RwLock::new(false)),
             cancelled,
+            phase,
             state,
             _phantom: PhantomData::<(T, E)>,
         }
@@ -284,23 +589,33 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
 
     /// Starts a `Task` with a duration structure, checking cancelation along with elapsed time
     #[with_bounds(F)]
-    fn _duration(mut f: F, config: impl Into<TaskConfig>, state: S, start_time: Instant) -> Self {
+    fn _duration(
+        mut f: F,
+        config: impl Into<TaskConfig>,
+        state: S,
+        start_time: Instant,
+        runtime: Handle,
+    ) -> Self {
         let config = config.into();
 
         let cancelled = Arc::new(RwLock::new(false));
         let cancelled_clone = cancelled.clone();
 
+        let phase = Arc::new(watch::channel(TaskPhase::Pending).0);
+        let phase_clone = phase.clone();
+
         let state = Arc::new(RwLock::new(state));
         let state_clone = state.clone();
 
-        let handle = tokio::spawn(async move {
+        let handle = runtime.spawn(async move {
             config.on_task_start();
+            let _phase = TaskPhaseGuard::running(phase_clone);
             let mut iteration = 0usize;
             let mut interval = tokio::time::interval(config.interval());
 
             loop {
                 // Check if cancelled
-                if let true = *cancelled_clone.read().await {
+                if *cancelled_clone.read().await {
                     break;
                 }
 
@@ -309,24 +624,30 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
                     break;
                 }
 
-                // Execute the closure
+                // Execute the closure, timing it if enabled
+                let start = config.is_collecting_timing().then(Instant::now);
                 let result = f(iteration, &state_clone).await;
+                let elapsed = start.map(|start| start.elapsed());
 
                 // Update the state
                 {
                     // Check if last result causes a stop
-                    if config.stop_on_error() {
-                        if let Err(_) = result {
-                            Task::set_state(&mut *state_clone.write().await, iteration, result)
-                                .await;
-                            break;
-                        }
+                    if config.stop_on_error() && result.is_err() {
+                        Task::set_state(
+                            &mut *state_clone.write().await,
+                            iteration,
+                            result,
+                            elapsed,
+                        )
+                        .await;
+                        break;
                     }
-                    Task::set_state(&mut *state_clone.write().await, iteration, result).await;
+                    Task::set_state(&mut *state_clone.write().await, iteration, result, elapsed)
+                        .await;
                 }
 
                 // Check interval bounds
-                if iteration >= usize::max_value() {
+                if iteration == usize::MAX {
                     iteration = 0;
                 }
 
@@ -342,17 +663,27 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
 
         Self {
             handle: Some(handle),
+            runtime,
             panicked: Arc::new(RwLock::new(false)),
             cancelled,
+            phase,
             state,
             _phantom: PhantomData::<(T, E)>,
         }
     }
 
-    /// Sets the current iteration and result for the `Task`
-    async fn set_state(state: &mut S, iteration: usize, result: Result<T, E>) {
+    /// Sets the current iteration, result, and iteration duration if timed for the `Task`
+    async fn set_state(
+        state: &mut S,
+        iteration: usize,
+        result: Result<T, E>,
+        elapsed: Option<std::time::Duration>,
+    ) {
         state.set_iteration(iteration + 1);
         state.set_last_result(result);
+        if let Some(elapsed) = elapsed {
+            state.record_timing(elapsed);
+        }
     }
 
     /// Gets the `Task` current state
@@ -405,6 +736,24 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
         }
     }
 
+    /// Waits until the `Task` loop has started, resolving immediately if it already started or stopped
+    pub async fn wait_started(&self) {
+        let _ = self
+            .phase
+            .subscribe()
+            .wait_for(|phase| *phase != TaskPhase::Pending)
+            .await;
+    }
+
+    /// Waits until the `Task` loop has stopped, by completing, cancelation, or being aborted, resolving immediately if it already stopped
+    pub async fn wait_stopped(&self) {
+        let _ = self
+            .phase
+            .subscribe()
+            .wait_for(|phase| *phase == TaskPhase::Stopped)
+            .await;
+    }
+
     ///Wait for the result of the `Task` finishing naturally
     pub async fn wait_for_complete(&mut self) -> Option<Result<T, E>> {
         let panicked = match self.handle.take() {
@@ -425,6 +774,15 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
         self.last_result().await
     }
 
+    /// Wait for the `Task` to finish naturally, returning whether it completed, ran no iterations, or panicked
+    pub async fn wait_for_completion(&mut self) -> TaskCompletion<T, E> {
+        match self.wait_for_complete().await {
+            Some(result) => TaskCompletion::Completed(result),
+            None if *self.panicked.read().await => TaskCompletion::Panicked,
+            None => TaskCompletion::NoIterations,
+        }
+    }
+
     /// Cancel the `Task` and wait for the result
     pub async fn stop_and_wait(&mut self) -> Option<Result<T, E>> {
         self.cancel().await;
@@ -444,15 +802,48 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
     pub fn abort(&mut self) {
         if let Some(handle) = self.handle.take() {
             handle.abort();
+            self.phase.send_replace(TaskPhase::Stopped);
+
+            // Update the state without blocking
+            let state = self.state.clone();
+            let cancelled = self.cancelled.clone();
+            self.runtime.spawn(async move {
+                state.write().await.set_is_running(false);
+                *cancelled.write().await = true;
+            });
+        }
+    }
+
+    /// Returns a handle that stops the `Task` immediately, allowing it to be aborted through a shared reference
+    pub fn abort_handle(&self) -> Option<tokio::task::AbortHandle> {
+        self.handle.as_ref().map(JoinHandle::abort_handle)
+    }
+
+    /// Returns the handle of the runtime the `Task` was spawned on
+    pub fn runtime(&self) -> &Handle {
+        &self.runtime
+    }
+
+    /// Aborts the `Task` and returns the owned state, erroring if the state is still shared elsewhere
+    pub async fn into_state(mut self) -> Result<S, TaskError> {
+        // Abort and wait for the spawned thread so its reference to the state is dropped
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+            let _ = handle.await;
         }
+        self.phase.send_replace(TaskPhase::Stopped);
+        self.state.write().await.set_is_running(false);
 
-        // Update the state without blocking
         let state = self.state.clone();
-        let cancelled = self.cancelled.clone();
-        tokio::spawn(async move {
-            state.write().await.set_is_running(false);
-            *cancelled.write().await = true;
-        });
+        drop(self);
+        Arc::try_unwrap(state)
+            .map(RwLock::into_inner)
+            .map_err(|state| {
+                TaskError::Custom(format!(
+                    "`Task` state is still shared by {} other references",
+                    Arc::strong_count(&state) - 1
+                ))
+            })
     }
 
     /// Returns the number of iteration ran by reading it from the `TaskState``
@@ -471,13 +862,96 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskState<T, E>> Task<T, E, S> {
     }
 }
 
+/// Implement multi `Task` functions, running several named jobs within a single loop
+impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> Task<T, E, MultiTaskState<T, E, S>> {
+    /// Creates a `Task` that runs each `MultiJob` in sequence per tick, with a specific `TaskConfig`.
+    /// The `TaskConfig` mode and cancelation apply to the loop as a whole while each job tracks its own `JobState`.
+    /// The `Task` last result is that of the last job ran, with the errors of jobs disabled on error only kept in their `JobState`
+    pub fn multi<C, FutC>(
+        jobs: Vec<MultiJob<T, E, S>>,
+        config: impl Into<TaskConfig>,
+        mut state: MultiTaskState<T, E, S>,
+        condition: Option<C>,
+    ) -> Result<Self, TaskError>
+    where
+        C: FnMut(&Arc<RwLock<MultiTaskState<T, E, S>>>) -> FutC + Send + Sync + 'static,
+        FutC: Future<Output = bool> + Send + Sync + 'static,
+    {
+        let config = config.into();
+        let condition = match (config.mode(), condition) {
+            (TaskMode::Conditional, None) => {
+                return Err(TaskError::NoCondition(
+                    "Missing condition function for `TaskMode::Conditional`".to_string(),
+                ))
+            }
+            (TaskMode::Conditional, condition) => condition,
+            _ => None,
+        };
+
+        // Register each job in the state, rejecting duplicate names
+        for job in &jobs {
+            if !state.add_job(job.name()) {
+                return Err(TaskError::Custom(format!(
+                    "Duplicate job name `{}` for multi `Task`",
+                    job.name()
+                )));
+            }
+        }
+
+        let runtime = config.runtime_handle()?;
+        let stop_on_error = config.stop_on_error();
+        let jobs = Arc::new(Mutex::new(jobs));
+
+        Ok(Self::_spawn_loop(
+            |_| async {},
+            move |iteration, state, _: &mut ()| {
+                let jobs = jobs.clone();
+                let state = state.clone();
+                async move {
+                    // Execute each enabled job that runs on this tick, keeping the last result of a job not disabled by it
+                    let mut result = None;
+                    for job in jobs.lock().await.iter_mut() {
+                        let enabled = state
+                            .read()
+                            .await
+                            .job(job.name())
+                            .is_some_and(|job_state| job_state.is_enabled());
+                        if !enabled || !job.runs_on(iteration) {
+                            continue;
+                        }
+
+                        let job_result = job.call(iteration, &state).await;
+
+                        // Update the job state, checking if the result disables the job or causes a stop
+                        let mut state = state.write().await;
+                        state.set_job_result(job.name(), job_result.clone());
+                        match job_result {
+                            Err(_) if job.is_disabled_on_error() => state.disable_job(job.name()),
+                            Err(_) if stop_on_error => return Some(job_result),
+                            _ => result = Some(job_result),
+                        }
+                    }
+                    result
+                }
+            },
+            config,
+            state,
+            condition,
+            runtime,
+        ))
+    }
+}
+
 #[cfg(all(test, feature = "test-tasks"))]
 mod tests {
-    use crate::{AsTaskState, BaseTaskState, Task, TaskMode};
-    use std::time::Duration;
+    use crate::{
+        AsTaskState, BaseTaskState, Budget, MultiJob, MultiTaskState, Task, TaskCompletion,
+        TaskConfig, TaskError, TaskMode, TaskState,
+    };
+    use std::{sync::Arc, time::Duration};
     use tokio::time::{sleep, Instant};
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn infinite_task() {
         let duration = 5;
         let mut task = Task::infinite(
@@ -494,7 +968,7 @@ mod tests {
             })))
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn fixed_task() {
         assert!(Task::fixed(
             5,
@@ -503,7 +977,7 @@ mod tests {
                 let state = state.clone();
                 async move {
                     // Get the ith element of the Vec stored in the `TaskState`
-                    let x = state.read().await.into_inner()[i];
+                    let x = state.read().await.as_inner()[i];
                     assert!((i + 1) == x);
                     // Set the task last_result as the ith element of the Vec
                     Ok::<_, ()>(x)
@@ -517,7 +991,7 @@ mod tests {
         .is_some_and(|res| res.is_ok_and(|i| i == 5)));
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn conditional_task() {
         let target_iteration = 5;
         assert!(Task::until_condition(
@@ -528,14 +1002,14 @@ mod tests {
                     let mut state = state.write().await;
                     // Get the target iteration from the `TaskState`
                     let iters = {
-                        let inner = state.into_inner();
-                        assert!(inner.0 == false);
+                        let inner = state.as_inner();
+                        assert!(!inner.0);
                         inner.1
                     };
                     if i >= iters {
                         state.set_inner((true, iters));
                     }
-                    println!("{}, {}", state.into_inner().0, i);
+                    println!("{}, {}", state.as_inner().0, i);
                     Ok::<_, ()>(i)
                 }
             },
@@ -544,7 +1018,7 @@ mod tests {
             // Condition checking the first element of the tuple in the `TaskState`
             |state| {
                 let state = state.clone();
-                async move { state.read().await.into_inner().0 }
+                async move { state.read().await.as_inner().0 }
             }
         )
         .wait_for_complete()
@@ -552,7 +1026,7 @@ mod tests {
         .is_some_and(|res| res.is_ok_and(|i| i == target_iteration)));
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn duration_task() {
         // Spawn a series of tasks with increasing durations
         let mut tasks = Vec::new();
@@ -594,7 +1068,34 @@ mod tests {
         println!("Average /{}: {} millis", len, avg / len)
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
+    async fn duration_task_since() {
+        // A start time injected in the past only leaves the remaining duration to run
+        let start_time = Instant::now();
+        tokio::time::advance(Duration::from_secs(9)).await;
+        let mut task = Task::for_duration_since(
+            start_time,
+            Duration::from_secs(10),
+            |_, _| async move { Ok::<_, ()>(Instant::now()) },
+            BaseTaskState::default(),
+        );
+        let last = task.wait_for_complete().await.unwrap().unwrap();
+        assert!(last - start_time < Duration::from_secs(10));
+        assert!(last - start_time >= Duration::from_secs(9));
+        assert!(task.state().await.get_iterations() <= 11);
+
+        // A start time a full duration in the past completes without running
+        let start_time = Instant::now() - Duration::from_secs(10);
+        let mut task = Task::for_duration_since(
+            start_time,
+            Duration::from_secs(10),
+            |_, _| async move { Ok::<_, ()>(Instant::now()) },
+            BaseTaskState::default(),
+        );
+        assert_eq!(task.wait_for_complete().await, None);
+    }
+
+    #[tokio::test(start_paused = true)]
     async fn tasks_with_config() {
         // Infinite
         let duration = 5;
@@ -617,7 +1118,7 @@ mod tests {
             |i, state| {
                 let state = state.clone();
                 async move {
-                    let x = state.read().await.into_inner()[i];
+                    let x = state.read().await.as_inner()[i];
                     assert!((i + 1) == x);
                     Ok::<_, ()>(x)
                 }
@@ -639,7 +1140,7 @@ mod tests {
                 let state = state.clone();
                 async move {
                     let mut state = state.write().await;
-                    assert!(*state.into_inner() == false);
+                    assert!(!*state.as_inner());
                     if i >= target_iteration {
                         state.set_inner(true);
                     }
@@ -684,4 +1185,463 @@ mod tests {
                 .is_some_and(|res| res.is_ok_and(|i| { (expected_end - i).as_millis() <= 500 })));
         }
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn runtime_handle() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("dedicated-runtime")
+            .enable_all()
+            .build()
+            .unwrap();
+        let thread_name = |_, _: &_| async move {
+            Ok::<_, ()>(std::thread::current().name().map(str::to_string))
+        };
+
+        // Each `Task` runs on the designated runtime rather than the test runtime
+        let mut task = Task::with_config(
+            thread_name,
+            TaskConfig::from(TaskMode::Fixed(1)).runtime(runtime.handle().clone()),
+            BaseTaskState::new(),
+            Task::NO_CONDITION,
+        )
+        .unwrap();
+        let expected = Some(Ok(Some("dedicated-runtime".to_string())));
+        assert_eq!(task.wait_for_complete().await, expected);
+        let mut task = Task::fixed_on(
+            runtime.handle().clone(),
+            1,
+            thread_name,
+            BaseTaskState::new(),
+        );
+        assert_eq!(task.wait_for_complete().await, expected);
+
+        // Without a runtime, the ambient one is used
+        let mut task = Task::fixed(1, thread_name, BaseTaskState::new());
+        assert_ne!(task.wait_for_complete().await, expected);
+
+        runtime.shutdown_background();
+    }
+
+    #[test]
+    fn no_runtime() {
+        assert!(matches!(
+            Task::with_config(
+                |i, _| async move { Ok::<_, ()>(i) },
+                TaskMode::Infinite,
+                BaseTaskState::new(),
+                Task::NO_CONDITION,
+            ),
+            Err(TaskError::NoRuntime(_))
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timing_stats() {
+        let sleep_duration = Duration::from_millis(20);
+        let mut task = Task::with_config(
+            move |i, _| async move {
+                sleep(sleep_duration).await;
+                Ok::<_, ()>(i)
+            },
+            TaskConfig::from(TaskMode::Fixed(5)).collect_timing(true),
+            BaseTaskState::new(),
+            Task::NO_CONDITION,
+        )
+        .unwrap();
+        task.wait_for_complete().await;
+
+        let state = task.state().await;
+        let timing = state.get_timing().unwrap();
+        assert_eq!(timing.count(), state.get_iterations());
+        assert!(timing.min() >= sleep_duration);
+        assert!(timing.max() < sleep_duration * 10);
+        assert!(timing.min() <= timing.mean() && timing.mean() <= timing.max());
+        // Sleeps of 20+ milliseconds land in the `[16, 32)` or higher buckets
+        assert_eq!(timing.histogram()[..5].iter().sum::<usize>(), 0);
+        assert_eq!(timing.histogram().iter().sum::<usize>(), 5);
+
+        // Timing is not collected by default
+        let mut task = Task::fixed(
+            2,
+            |i, _| async move { Ok::<_, ()>(i) },
+            BaseTaskState::new(),
+        );
+        task.wait_for_complete().await;
+        assert!(task.state().await.get_timing().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn task_with_init() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let init_calls = Arc::new(AtomicUsize::new(0));
+        let init_calls_clone = init_calls.clone();
+        let mut task = Task::with_init(
+            move |_| async move {
+                init_calls_clone.fetch_add(1, Ordering::Relaxed);
+                // The resource is a buffer reused across iterations
+                Vec::<usize>::with_capacity(5)
+            },
+            |i, _, buffer: &mut Vec<usize>| {
+                buffer.push(i);
+                let result = (buffer.len(), buffer.capacity());
+                async move { Ok::<_, ()>(result) }
+            },
+            TaskConfig::new(
+                Duration::from_millis(10),
+                false,
+                TaskMode::Fixed(5),
+                None,
+                None,
+            ),
+            BaseTaskState::new(),
+            Task::NO_CONDITION,
+        )
+        .unwrap();
+
+        // The resource saw every iteration while `init` only ran once
+        assert_eq!(task.wait_for_complete().await, Some(Ok((5, 5))));
+        assert_eq!(init_calls.load(Ordering::Relaxed), 1);
+
+        // `TaskMode::Conditional` still requires a condition
+        assert!(Task::with_init(
+            |_| async {},
+            |i, _, _: &mut ()| async move { Ok::<_, ()>(i) },
+            TaskMode::Conditional,
+            BaseTaskState::new(),
+            Task::NO_CONDITION,
+        )
+        .is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_started_stopped() {
+        // Waiters are created before the single threaded test runtime polls the spawned `Task` loop
+        let task = Arc::new(Task::infinite(
+            |i, _| async move {
+                sleep(Duration::from_millis(1)).await;
+                Ok::<_, ()>(i)
+            },
+            BaseTaskState::default(),
+        ));
+        let waiters = (0..2)
+            .map(|_| {
+                let task = task.clone();
+                tokio::spawn(async move { task.wait_started().await })
+            })
+            .collect::<Vec<_>>();
+        for waiter in waiters {
+            tokio::time::timeout(Duration::from_secs(1), waiter)
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        assert!(task.is_running().await);
+        // Already reached states resolve immediately
+        task.wait_started().await;
+
+        // Stopped by cancelation
+        let mut task = Arc::into_inner(task).unwrap();
+        task.cancel().await;
+        tokio::time::timeout(Duration::from_secs(1), task.wait_stopped())
+            .await
+            .unwrap();
+
+        // Stopped by completing naturally
+        let task = Task::fixed(
+            3,
+            |i, _| async move { Ok::<_, ()>(i) },
+            BaseTaskState::default(),
+        );
+        tokio::time::timeout(Duration::from_secs(1), task.wait_stopped())
+            .await
+            .unwrap();
+        task.wait_started().await;
+        assert_eq!(task.last_result().await, Some(Ok(2)));
+
+        // Stopped by being aborted mid iteration
+        let mut task = Task::infinite(
+            |i, _| async move {
+                sleep(Duration::from_secs(60)).await;
+                Ok::<_, ()>(i)
+            },
+            BaseTaskState::default(),
+        );
+        task.wait_started().await;
+        task.abort();
+        tokio::time::timeout(Duration::from_secs(1), task.wait_stopped())
+            .await
+            .unwrap();
+        assert!(task.abort_handle().is_none());
+
+        // Stopped through the abort handle of a shared `Task`
+        let task = Arc::new(Task::infinite(
+            |i, _| async move {
+                sleep(Duration::from_secs(60)).await;
+                Ok::<_, ()>(i)
+            },
+            BaseTaskState::default(),
+        ));
+        task.wait_started().await;
+        task.abort_handle().unwrap().abort();
+        tokio::time::timeout(Duration::from_secs(1), task.wait_stopped())
+            .await
+            .unwrap();
+        assert!(!task.is_running().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn no_iterations() {
+        // Fixed(0)
+        let mut task = Task::fixed(
+            0,
+            |i, _| async move { Ok::<_, ()>(i) },
+            BaseTaskState::default(),
+        );
+        assert_eq!(
+            task.wait_for_completion().await,
+            TaskCompletion::NoIterations
+        );
+        assert_eq!(task.state().await.get_iterations(), 0);
+        assert!(!task.is_panic().await);
+
+        // Duration(ZERO)
+        let mut task = Task::for_duration(
+            Duration::ZERO,
+            |i, _| async move { Ok::<_, ()>(i) },
+            BaseTaskState::default(),
+        );
+        assert_eq!(
+            task.wait_for_completion().await,
+            TaskCompletion::NoIterations
+        );
+        assert_eq!(task.state().await.get_iterations(), 0);
+        assert!(!task.is_panic().await);
+
+        // Conditional with an already met condition
+        let cond_state = true.as_task_state();
+        let mut task = Task::with_config(
+            |i, _| async move { Ok::<_, ()>(i) },
+            TaskMode::Conditional,
+            cond_state.clone(),
+            Task::some_condition(&cond_state, |state| {
+                let state = state.clone();
+                async move { state.read().await.inner_clone() }
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            task.wait_for_completion().await,
+            TaskCompletion::NoIterations
+        );
+        assert_eq!(task.state().await.get_iterations(), 0);
+        assert!(!task.is_panic().await);
+
+        // Running iterations or panicking are still told apart
+        let mut task = Task::fixed(
+            2,
+            |i, _| async move { Ok::<_, ()>(i) },
+            BaseTaskState::default(),
+        );
+        assert_eq!(
+            task.wait_for_completion().await,
+            TaskCompletion::Completed(Ok(1))
+        );
+        let mut task = Task::fixed(
+            2,
+            |i, _| async move {
+                if i == 1 {
+                    panic!("Panicked on iteration {i}");
+                }
+                Ok::<_, ()>(i)
+            },
+            BaseTaskState::default(),
+        );
+        assert_eq!(task.wait_for_completion().await, TaskCompletion::Panicked);
+        assert!(task.is_panic().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn budget() {
+        // A `Task` closure yields on every third tick
+        let mut task = Task::fixed(
+            1,
+            |_, _| async move {
+                let mut budget = Budget::new(3);
+                let mut yielded = vec![];
+                for _ in 0..6 {
+                    yielded.push(budget.tick().await);
+                }
+                Ok::<_, ()>(yielded)
+            },
+            BaseTaskState::default(),
+        );
+        assert_eq!(
+            task.wait_for_complete().await,
+            Some(Ok(vec![false, false, true, false, false, true]))
+        );
+
+        // Once refilled or unlimited, ticks don't yield
+        let mut budget = Budget::new(2);
+        assert!(!budget.tick().await);
+        assert_eq!(budget.remaining(), 1);
+        budget.reset();
+        assert!(!budget.tick().await);
+        assert!(budget.tick().await);
+        assert_eq!(budget.remaining(), 2);
+        let mut budget = Budget::unlimited();
+        assert_eq!(budget.limit(), 0);
+        for _ in 0..10 {
+            assert!(!budget.tick().await);
+        }
+        assert_eq!(Budget::default().limit(), Budget::DEFAULT_LIMIT);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn into_state() {
+        let buffer = vec![1usize, 2, 3];
+        let buffer_ptr = buffer.as_ptr();
+        let mut task = Task::fixed(
+            buffer.len(),
+            |i, state| {
+                let state = state.clone();
+                async move { Ok::<_, ()>(state.read().await.as_inner()[i]) }
+            },
+            buffer.as_task_state(),
+        );
+        task.wait_for_complete().await;
+
+        // Move the buffer out of the completed `Task`, checking it is the same allocation
+        let (base, buffer) = task.into_state().await.unwrap().into_parts();
+        assert_eq!(base.get_iterations(), 3);
+        assert_eq!(base.get_last_result(), Some(Ok(3)));
+        assert_eq!(buffer, vec![1, 2, 3]);
+        assert_eq!(buffer.as_ptr(), buffer_ptr);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn multi_task() {
+        // Three jobs running every 1, 2 and 3 ticks for 6 ticks
+        let mut task = Task::multi(
+            vec![
+                MultiJob::new("heartbeat", |i, _| async move { Ok::<_, ()>(i) }),
+                MultiJob::new("scrape", |i, _| async move { Ok::<_, ()>(i * 10) }).every_n_ticks(2),
+                MultiJob::new("evict", |i, _| async move { Ok::<_, ()>(i * 100) }).every_n_ticks(3),
+            ],
+            TaskConfig::new(
+                Duration::from_millis(10),
+                false,
+                TaskMode::Fixed(6),
+                None,
+                None,
+            ),
+            MultiTaskState::<usize, (), ()>::default(),
+            Task::NO_CONDITION,
+        )
+        .unwrap();
+        task.wait_for_complete().await;
+
+        let state = task.state().await;
+        for (name, invocations, last_result) in
+            [("heartbeat", 6, 5), ("scrape", 3, 40), ("evict", 2, 300)]
+        {
+            let job = state.job(name).unwrap();
+            assert_eq!(job.get_invocations(), invocations);
+            assert_eq!(job.get_last_result(), Some(Ok(last_result)));
+        }
+        assert_eq!(state.get_iterations(), 6);
+
+        // Ticks where no job is due are still counted as iterations
+        let mut task = Task::multi(
+            vec![MultiJob::new("evict", |i, _| async move { Ok::<_, ()>(i) }).every_n_ticks(4)],
+            TaskConfig::new(
+                Duration::from_millis(10),
+                false,
+                TaskMode::Fixed(6),
+                None,
+                None,
+            ),
+            MultiTaskState::<usize, (), ()>::default(),
+            Task::NO_CONDITION,
+        )
+        .unwrap();
+        task.wait_for_complete().await;
+        let state = task.state().await;
+        assert_eq!(state.job("evict").unwrap().get_invocations(), 2);
+        assert_eq!(state.get_last_result(), Some(Ok(4)));
+        assert_eq!(state.get_iterations(), 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn multi_task_errors() {
+        // A job disabled on error should not stop the other jobs
+        let mut task = Task::multi(
+            vec![
+                MultiJob::new("ok", |i, _| async move { Ok::<_, usize>(i) }),
+                MultiJob::new("fails", |i, _| async move { Err::<usize, _>(i) })
+                    .disable_on_error(true),
+            ],
+            TaskConfig::new(
+                Duration::from_millis(10),
+                true,
+                TaskMode::Fixed(4),
+                None,
+                None,
+            ),
+            MultiTaskState::<usize, usize, ()>::default(),
+            Task::NO_CONDITION,
+        )
+        .unwrap();
+        task.wait_for_complete().await;
+
+        let state = task.state().await;
+        assert_eq!(state.job("ok").unwrap().get_invocations(), 4);
+        let failed = state.job("fails").unwrap();
+        assert_eq!(failed.get_invocations(), 1);
+        assert!(!failed.is_enabled());
+        // The disabling error is only kept by its job
+        assert_eq!(failed.get_last_result(), Some(Err(0)));
+        assert_eq!(state.get_last_result(), Some(Ok(3)));
+        assert_eq!(state.get_iterations(), 4);
+
+        // A job not disabled on error stops the whole `Task` when `stop_on_error` is set
+        let mut task = Task::multi(
+            vec![
+                MultiJob::new("fails", |i, _| async move { Err::<usize, _>(i) }),
+                MultiJob::new("ok", |i, _| async move { Ok::<_, usize>(i) }),
+            ],
+            TaskConfig::new(
+                Duration::from_millis(10),
+                true,
+                TaskMode::Fixed(4),
+                None,
+                None,
+            ),
+            MultiTaskState::<usize, usize, ()>::default(),
+            Task::NO_CONDITION,
+        )
+        .unwrap();
+        assert_eq!(task.wait_for_complete().await, Some(Err(0)));
+        assert_eq!(task.state().await.job("ok").unwrap().get_invocations(), 0);
+
+        // Duplicate job names and missing conditions are rejected
+        assert!(Task::multi(
+            vec![
+                MultiJob::new("a", |i, _| async move { Ok::<_, ()>(i) }),
+                MultiJob::new("a", |i, _| async move { Ok::<_, ()>(i) }),
+            ],
+            TaskMode::Fixed(1),
+            MultiTaskState::<usize, (), ()>::default(),
+            Task::NO_CONDITION,
+        )
+        .is_err());
+        assert!(Task::multi(
+            vec![MultiJob::new("a", |i, _| async move { Ok::<_, ()>(i) })],
+            TaskMode::Conditional,
+            MultiTaskState::<usize, (), ()>::default(),
+            Task::NO_CONDITION,
+        )
+        .is_err());
+    }
 }
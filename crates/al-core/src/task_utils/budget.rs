@@ -0,0 +1,62 @@
+/// `Budget` lets a tight loop within a `Task` closure cooperatively yield to the runtime after every `limit` ticks.
+/// Without yielding, a loop whose awaits are always ready never gives other tasks on the same worker a chance to run
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Budget {
+    limit: usize,
+    remaining: usize,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_LIMIT)
+    }
+}
+
+impl Budget {
+    /// Default number of ticks between yields, matching the 128 unit coop budget tokio gives each task before its own resources return `Pending`.
+    /// Loops over tokio resources already yield at that rate, so a `Budget` at the default keeps a loop over always ready transports to the same fairness
+    pub const DEFAULT_LIMIT: usize = 128;
+
+    /// Creates a `Budget` yielding after every `limit` ticks, where a `limit` of 0 never yields
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            remaining: limit,
+        }
+    }
+
+    /// Creates a `Budget` that never yields
+    pub fn unlimited() -> Self {
+        Self::new(0)
+    }
+
+    /// Returns the number of ticks between yields
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the number of ticks left before the next yield
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Spends one tick of the `Budget`, yielding to the runtime and refilling it once it runs out.
+    /// Returns true if it yielded
+    pub async fn tick(&mut self) -> bool {
+        if self.limit == 0 {
+            return false;
+        }
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            return false;
+        }
+        self.remaining = self.limit;
+        tokio::task::yield_now().await;
+        true
+    }
+
+    /// Refills the `Budget` without yielding, such as after the loop awaited something that wasn't ready
+    pub fn reset(&mut self) {
+        self.remaining = self.limit;
+    }
+}
@@ -1,2 +1,4 @@
+pub mod budget;
+pub mod multi_task;
 pub mod task_elements;
 pub mod task_state;
@@ -0,0 +1,234 @@
+use crate::{BaseTaskState, Registry, TaskState, TaskStateRequirements, TaskTypes, TimingStats};
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+
+/// Boxed closure type for a single job run by a multi `Task`
+pub type MultiTaskFn<T, E, S> = Box<
+    dyn FnMut(
+            usize,
+            &Arc<RwLock<MultiTaskState<T, E, S>>>,
+        ) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + Sync>>
+        + Send
+        + Sync,
+>;
+
+/// `MultiJob` is a single named closure run within the shared loop of a multi `Task`
+pub struct MultiJob<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> {
+    name: String,
+    every_n_ticks: usize,
+    disable_on_error: bool,
+    f: MultiTaskFn<T, E, S>,
+}
+
+impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> MultiJob<T, E, S> {
+    /// Creates a `MultiJob` that runs on every tick of the `Task`
+    pub fn new<F, Fut>(name: impl AsRef<str>, mut f: F) -> Self
+    where
+        F: FnMut(usize, &Arc<RwLock<MultiTaskState<T, E, S>>>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.as_ref().to_string(),
+            every_n_ticks: 1,
+            disable_on_error: false,
+            f: Box::new(move |tick, state| Box::pin(f(tick, state))),
+        }
+    }
+
+    /// Sets the job to only run on ticks that are a multiple of `n`, a value of `0` is treated as `1`
+    pub fn every_n_ticks(mut self, n: usize) -> Self {
+        self.every_n_ticks = n.max(1);
+        self
+    }
+
+    /// Sets the job to be disabled, rather than stopping the `Task`, when it returns an error
+    pub fn disable_on_error(mut self, disable_on_error: bool) -> Self {
+        self.disable_on_error = disable_on_error;
+        self
+    }
+
+    /// Returns the name of the job
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if the job should run on the passed tick
+    pub(crate) fn runs_on(&self, tick: usize) -> bool {
+        tick.is_multiple_of(self.every_n_ticks)
+    }
+
+    pub(crate) fn is_disabled_on_error(&self) -> bool {
+        self.disable_on_error
+    }
+
+    /// Calls the inner closure of the job
+    pub(crate) fn call(
+        &mut self,
+        tick: usize,
+        state: &Arc<RwLock<MultiTaskState<T, E, S>>>,
+    ) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + Sync>> {
+        (self.f)(tick, state)
+    }
+}
+
+/// Impl Debug for `MultiJob` manually as `Fn()` doesn't support `Debug`
+impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> std::fmt::Debug for MultiJob<T, E, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiJob")
+            .field("name", &self.name)
+            .field("every_n_ticks", &self.every_n_ticks)
+            .field("disable_on_error", &self.disable_on_error)
+            .field("f", &"MultiTaskFn")
+            .finish()
+    }
+}
+
+/// `JobState` contains the values a multi `Task` tracks for each of its jobs
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct JobState<T: TaskTypes, E: TaskTypes> {
+    invocations: usize,
+    last_result: Option<Result<T, E>>,
+    enabled: bool,
+}
+
+impl<T: TaskTypes, E: TaskTypes> Default for JobState<T, E> {
+    fn default() -> Self {
+        Self {
+            invocations: 0,
+            last_result: None,
+            enabled: true,
+        }
+    }
+}
+
+impl<T: TaskTypes, E: TaskTypes> JobState<T, E> {
+    /// Returns the number of times the job has been ran
+    pub fn get_invocations(&self) -> usize {
+        self.invocations
+    }
+
+    /// Returns a clone of the last result of the job
+    pub fn get_last_result(&self) -> Option<Result<T, E>> {
+        self.last_result.clone()
+    }
+
+    /// Returns false if the job was disabled after returning an error
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// `MultiTaskState` holds the `BaseTaskState` of a multi `Task`, the `JobState` of each job keyed by name, and extended state passed by the user
+#[derive(Debug, Clone)]
+pub struct MultiTaskState<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements = ()> {
+    base: BaseTaskState<T, E>,
+    jobs: Registry<String, JobState<T, E>>,
+    extended: S,
+}
+
+impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements + Default> Default
+    for MultiTaskState<T, E, S>
+{
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> MultiTaskState<T, E, S> {
+    pub fn new(extended: S) -> Self {
+        Self {
+            base: BaseTaskState::new(),
+            jobs: Registry::new(),
+            extended,
+        }
+    }
+
+    /// Returns the `JobState` of the named job, if it exists
+    pub fn job(&self, name: impl AsRef<str>) -> Option<&JobState<T, E>> {
+        self.jobs.get(name.as_ref())
+    }
+
+    /// Returns the names of all jobs tracked by the state
+    pub fn job_names(&self) -> impl Iterator<Item = &String> {
+        self.jobs.keys()
+    }
+
+    /// Sets `extended` to the passed `S`
+    pub fn set_inner(&mut self, inner: S) {
+        self.extended = inner
+    }
+
+    /// Returns the extended state
+    pub fn as_inner(&self) -> &S {
+        &self.extended
+    }
+
+    /// Consumes the state, returning the `BaseTaskState`, the `JobState` of each job, and the owned extended state
+    #[allow(clippy::type_complexity)]
+    pub fn into_parts(self) -> (BaseTaskState<T, E>, Registry<String, JobState<T, E>>, S) {
+        (self.base, self.jobs, self.extended)
+    }
+
+    /// Returns a clone of the extended state
+    pub fn inner_clone(&self) -> S {
+        self.extended.clone()
+    }
+
+    /// Adds an empty `JobState` for the named job, returning false if the name is already taken
+    pub(crate) fn add_job(&mut self, name: &str) -> bool {
+        if self.jobs.contains_key(name) {
+            return false;
+        }
+        self.jobs.insert(name.to_string(), JobState::default());
+        true
+    }
+
+    /// Records a result for the named job, incrementing its invocations
+    pub(crate) fn set_job_result(&mut self, name: &str, result: Result<T, E>) {
+        let job = self.jobs.entry(name.to_string()).or_default();
+        job.invocations += 1;
+        job.last_result = Some(result);
+    }
+
+    /// Marks the named job as disabled
+    pub(crate) fn disable_job(&mut self, name: &str) {
+        self.jobs.entry(name.to_string()).or_default().enabled = false;
+    }
+}
+
+/// Impl `TaskState` for `MultiTaskState` by delegating to `BaseTaskState`
+impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> TaskState<T, E>
+    for MultiTaskState<T, E, S>
+{
+    fn get_iterations(&self) -> usize {
+        self.base.get_iterations()
+    }
+
+    fn set_iteration(&mut self, iterations: usize) {
+        self.base.set_iteration(iterations)
+    }
+
+    fn get_last_result(&self) -> Option<Result<T, E>> {
+        self.base.get_last_result()
+    }
+
+    fn set_last_result(&mut self, result: Result<T, E>) {
+        self.base.set_last_result(result)
+    }
+
+    fn get_is_running(&self) -> bool {
+        self.base.get_is_running()
+    }
+
+    fn set_is_running(&mut self, is_running: bool) {
+        self.base.set_is_running(is_running)
+    }
+
+    fn get_timing(&self) -> Option<TimingStats> {
+        self.base.get_timing()
+    }
+
+    fn record_timing(&mut self, duration: Duration) {
+        self.base.record_timing(duration)
+    }
+}
@@ -5,6 +5,19 @@ use std::{sync::Arc, time::Duration};
 pub enum TaskError {
     Custom(String),
     NoCondition(String),
+    /// No `TaskConfig::runtime` was set and there is no ambient tokio runtime to spawn on
+    NoRuntime(String),
+}
+
+/// `TaskCompletion` is how a `Task` finished, separating a `Task` that never ran an iteration from one that panicked
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskCompletion<T, E> {
+    /// The `Task` ran at least one iteration, holding the last result
+    Completed(Result<T, E>),
+    /// The `Task` stopped before its first iteration, such as `TaskMode::Fixed(0)`, `TaskMode::Duration(Duration::ZERO)`, or an already met condition
+    NoIterations,
+    /// The `Task` loop panicked
+    Panicked,
 }
 
 /// `TaskMode` defines parameters for if a `Task` should be stopped automatically
@@ -29,6 +42,8 @@ pub struct TaskConfig {
     mode: TaskMode,
     on_task_start: Option<Arc<dyn Fn() + Send + Sync>>,
     on_task_complete: Option<Arc<dyn Fn() + Send + Sync>>,
+    collect_timing: bool,
+    runtime: Option<tokio::runtime::Handle>,
 }
 
 impl TaskConfig {
@@ -46,6 +61,33 @@ impl TaskConfig {
             mode,
             on_task_start,
             on_task_complete,
+            collect_timing: false,
+            runtime: None,
+        }
+    }
+
+    /// Sets if the `Task` records the execution duration of each iteration into its `TaskState`
+    pub fn collect_timing(mut self, collect_timing: bool) -> Self {
+        self.collect_timing = collect_timing;
+        self
+    }
+
+    pub fn is_collecting_timing(&self) -> bool {
+        self.collect_timing
+    }
+
+    /// Sets the tokio runtime the `Task` is spawned on, rather than the ambient runtime it is created in
+    pub fn runtime(mut self, runtime: tokio::runtime::Handle) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Returns the set runtime, falling back to the ambient runtime or a `TaskError::NoRuntime` if there is none
+    pub fn runtime_handle(&self) -> Result<tokio::runtime::Handle, TaskError> {
+        match &self.runtime {
+            Some(runtime) => Ok(runtime.clone()),
+            None => tokio::runtime::Handle::try_current()
+                .map_err(|e| TaskError::NoRuntime(e.to_string())),
         }
     }
 
@@ -107,6 +149,8 @@ impl Default for TaskConfig {
             mode: TaskMode::default(),
             on_task_start: None,
             on_task_complete: None,
+            collect_timing: false,
+            runtime: None,
         }
     }
 }
@@ -118,6 +162,8 @@ impl PartialEq for TaskConfig {
             && self.mode == other.mode
             && (self.on_task_start.is_some() == other.on_task_start.is_some())
             && (self.on_task_complete.is_some() == other.on_task_complete.is_some())
+            && self.collect_timing == other.collect_timing
+            && (self.runtime.is_some() == other.runtime.is_some())
     }
 }
 
@@ -143,6 +189,15 @@ impl std::fmt::Debug for TaskConfig {
                     &"None"
                 },
             )
+            .field("collect_timing", &self.collect_timing)
+            .field(
+                "runtime",
+                if self.runtime.is_some() {
+                    &"<Handle>"
+                } else {
+                    &"None"
+                },
+            )
             .finish()
     }
 }
@@ -154,6 +209,8 @@ impl std::hash::Hash for TaskConfig {
         self.mode.hash(state);
         self.on_task_start.is_some().hash(state);
         self.on_task_complete.is_some().hash(state);
+        self.collect_timing.hash(state);
+        self.runtime.is_some().hash(state);
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::{TaskStateRequirements, TaskTypes};
+use std::time::Duration;
 
 /// `TaskState` contains all required functions hooks and should hold all values a `Task` tracks between iterations
 pub trait TaskState<T: TaskTypes = (), E: TaskTypes = ()>: TaskStateRequirements {
@@ -10,6 +11,79 @@ pub trait TaskState<T: TaskTypes = (), E: TaskTypes = ()>: TaskStateRequirements
 
     fn get_is_running(&self) -> bool;
     fn set_is_running(&mut self, is_running: bool);
+
+    /// Returns the iteration timing stats, if the state collects them
+    fn get_timing(&self) -> Option<TimingStats> {
+        None
+    }
+    /// Records the execution duration of an iteration, only called when `TaskConfig::collect_timing` is set
+    fn record_timing(&mut self, _duration: Duration) {}
+}
+
+/// `TimingStats` holds aggregate execution durations of `Task` iterations
+#[derive(Debug, Clone, Default, PartialEq, Hash)]
+pub struct TimingStats {
+    count: usize,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+    histogram: [usize; TimingStats::BUCKETS],
+}
+
+impl TimingStats {
+    /// Number of histogram buckets, see `histogram()`
+    pub const BUCKETS: usize = 16;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an iteration duration to the stats
+    pub fn record(&mut self, duration: Duration) {
+        if self.count == 0 || duration < self.min {
+            self.min = duration;
+        }
+        self.max = self.max.max(duration);
+        self.count += 1;
+        self.total += duration;
+        self.histogram[Self::bucket(duration)] += 1;
+    }
+
+    /// Returns the histogram bucket for a duration, bucket `0` being under 1 millisecond and bucket `i` being `[2^(i-1), 2^i)` milliseconds, with the last bucket holding all longer durations
+    pub fn bucket(duration: Duration) -> usize {
+        match duration.as_millis() {
+            0 => 0,
+            millis => ((u128::BITS - millis.leading_zeros()) as usize).min(Self::BUCKETS - 1),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            count => self.total.div_f64(count as f64),
+        }
+    }
+
+    pub fn min(&self) -> Duration {
+        self.min
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the count of iterations within each power of two milliseconds bucket, see `bucket()`
+    pub fn histogram(&self) -> &[usize; Self::BUCKETS] {
+        &self.histogram
+    }
 }
 
 /// `BaseTaskState` contains all values a `Task` tracks between iterations
@@ -18,6 +92,7 @@ pub struct BaseTaskState<T: TaskTypes = (), E: TaskTypes = ()> {
     iterations: usize,
     last_result: Option<Result<T, E>>,
     is_running: bool,
+    timing: Option<TimingStats>,
 }
 
 impl<T: TaskTypes, E: TaskTypes> Default for BaseTaskState<T, E> {
@@ -26,6 +101,7 @@ impl<T: TaskTypes, E: TaskTypes> Default for BaseTaskState<T, E> {
             iterations: 0,
             last_result: None,
             is_running: false,
+            timing: None,
         }
     }
 }
@@ -61,10 +137,21 @@ impl<T: TaskTypes, E: TaskTypes> TaskState<T, E> for BaseTaskState<T, E> {
     fn set_is_running(&mut self, is_running: bool) {
         self.is_running = is_running;
     }
+
+    fn get_timing(&self) -> Option<TimingStats> {
+        self.timing.clone()
+    }
+
+    fn record_timing(&mut self, duration: Duration) {
+        self.timing
+            .get_or_insert_with(TimingStats::new)
+            .record(duration);
+    }
 }
 
 /// `WithTaskState` allows any type with `'static + Send + Sync + Clone` to use `as_task_state()` and `with_task_state(mode)`
 pub trait AsTaskState<T: TaskTypes, E: TaskTypes>: TaskStateRequirements {
+    #[allow(clippy::wrong_self_convention)]
     fn as_task_state(self) -> ExtendedTaskState<T, E, Self>;
 }
 
@@ -115,10 +202,21 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> ExtendedTaskState<T,
     }
 
     /// Returns the extended state
-    pub fn into_inner(&self) -> &S {
+    pub fn as_inner(&self) -> &S {
         &self.extended
     }
 
+    /// Returns the extended state
+    #[deprecated(note = "`into_inner` does not consume, use `as_inner` or `into_parts` instead")]
+    pub fn into_inner(&self) -> &S {
+        self.as_inner()
+    }
+
+    /// Consumes the state, returning the `BaseTaskState` and the owned extended state
+    pub fn into_parts(self) -> (BaseTaskState<T, E>, S) {
+        (self.base, self.extended)
+    }
+
     /// Returns a clone of the extended state
     pub fn inner_clone(&self) -> S {
         self.extended.clone()
@@ -152,4 +250,12 @@ impl<T: TaskTypes, E: TaskTypes, S: TaskStateRequirements> TaskState<T, E>
     fn set_is_running(&mut self, is_running: bool) {
         self.base.set_is_running(is_running)
     }
+
+    fn get_timing(&self) -> Option<TimingStats> {
+        self.base.get_timing()
+    }
+
+    fn record_timing(&mut self, duration: Duration) {
+        self.base.record_timing(duration)
+    }
 }
@@ -34,6 +34,48 @@ pub trait Transport<T: TransportItemRequirements>: TransportRequirements {
     fn try_recv(
         &self,
     ) -> Pin<Box<dyn Future<Output = Result<Option<T>, TransportError>> + Send + Sync + '_>>;
+
+    /// Synchronously wait until at least `min` items are received or the transport is closed, then receive all currently avaliable data.
+    /// Once closed, the items received so far are returned, or `TransportError::Closed` if there were none
+    fn recv_at_least_blocking(&self, min: usize) -> Result<Vec<T>, TransportError> {
+        let mut data = Vec::with_capacity(min);
+        while data.len() < min {
+            match self.recv_blocking() {
+                Ok(item) => data.push(item),
+                Err(TransportError::Closed) if !data.is_empty() => return Ok(data),
+                Err(e) => return Err(e),
+            }
+        }
+        match self.recv_avaliable_blocking() {
+            Ok(rest) => data.extend(rest),
+            Err(TransportError::Closed) if !data.is_empty() => {}
+            Err(e) => return Err(e),
+        }
+        Ok(data)
+    }
+    /// Asynchronously wait until at least `min` items are received or the transport is closed, then receive all currently avaliable data.
+    /// Once closed, the items received so far are returned, or `TransportError::Closed` if there were none
+    fn recv_at_least(
+        &self,
+        min: usize,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<T>, TransportError>> + Send + Sync + '_>> {
+        Box::pin(async move {
+            let mut data = Vec::with_capacity(min);
+            while data.len() < min {
+                match self.recv().await {
+                    Ok(item) => data.push(item),
+                    Err(TransportError::Closed) if !data.is_empty() => return Ok(data),
+                    Err(e) => return Err(e),
+                }
+            }
+            match self.recv_avaliable().await {
+                Ok(rest) => data.extend(rest),
+                Err(TransportError::Closed) if !data.is_empty() => {}
+                Err(e) => return Err(e),
+            }
+            Ok(data)
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +85,12 @@ pub enum TransportError {
     Transport(String),
     UnSupported(String),
     NoData,
+    Closed,
+    /// A `DeadLetter` failed to divert data to its sink, holding the final send error along with the sink's error
+    DivertFailed {
+        error: Box<TransportError>,
+        sink_error: Box<TransportError>,
+    },
 }
 
 impl<T> From<PoisonError<T>> for TransportError {
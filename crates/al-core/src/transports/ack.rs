@@ -0,0 +1,717 @@
+use crate::{SliceDebug, Transport, TransportError, TransportItemRequirements};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// Id of a delivery into an `AckTransport`, used to `.ack()` or `.nack()` it
+pub type DeliveryId = u64;
+
+/// Outcome counts shared between a `DeliveryReceipt` and every `AckTransport` it was delivered to
+#[derive(Debug, Default)]
+struct ReceiptCounts {
+    pending: usize,
+    acked: usize,
+    dead_lettered: usize,
+    redeliveries: usize,
+}
+
+#[derive(Debug, Default)]
+struct ReceiptState {
+    counts: Mutex<ReceiptCounts>,
+    notifier: tokio::sync::Notify,
+}
+
+impl ReceiptState {
+    fn update(&self, f: impl FnOnce(&mut ReceiptCounts)) -> Result<(), TransportError> {
+        f(&mut *self.counts.lock()?);
+        self.notifier.notify_waiters();
+        Ok(())
+    }
+}
+
+/// A single delivery, queued while `deadline` is `None` and in flight with a consumer otherwise
+struct Delivery<T> {
+    data: T,
+    attempts: usize,
+    deadline: Option<Instant>,
+    receipt: Option<Arc<ReceiptState>>,
+}
+
+struct AckState<T> {
+    next_id: DeliveryId,
+    ready: VecDeque<DeliveryId>,
+    deliveries: HashMap<DeliveryId, Delivery<T>>,
+}
+
+/// AckTransport wrapper requiring each received item to be acknowledged, redelivering nacked or timed out items up to a retry limit before routing them to a dead letter transport
+pub struct AckTransport<T> {
+    state: Mutex<AckState<T>>,
+    max_retries: usize,
+    ack_timeout: Duration,
+    dead_letter: Option<Arc<dyn Transport<T>>>,
+    notifier: tokio::sync::Notify,
+    condvar: Condvar,
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for AckTransport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.state.lock() {
+            Ok(state) => {
+                let ready = state
+                    .ready
+                    .iter()
+                    .filter_map(|id| state.deliveries.get(id).map(|delivery| &delivery.data))
+                    .collect::<Vec<&T>>();
+                f.debug_struct("AckTransport")
+                    .field("ready", &SliceDebug::new(&ready))
+                    .field("in_flight", &(state.deliveries.len() - ready.len()))
+                    .finish()
+            }
+            Err(e) => f
+                .debug_struct("AckTransport")
+                .field("state", &format!("<LockPoisoned>: {}", e))
+                .finish(),
+        }
+    }
+}
+
+impl<T: TransportItemRequirements> From<AckTransport<T>> for Arc<dyn Transport<T>> {
+    fn from(transport: AckTransport<T>) -> Self {
+        Arc::new(transport)
+    }
+}
+
+impl<T: TransportItemRequirements> AckTransport<T> {
+    /// Creates an `AckTransport` delivering each item at most `1 + max_retries` times, treating deliveries not acked within `ack_timeout` as nacked
+    pub fn new(max_retries: usize, ack_timeout: Duration) -> Self {
+        Self {
+            state: Mutex::new(AckState {
+                next_id: 0,
+                ready: VecDeque::new(),
+                deliveries: HashMap::new(),
+            }),
+            max_retries,
+            ack_timeout,
+            dead_letter: None,
+            notifier: tokio::sync::Notify::new(),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Sets the transport items are sent to once their retries are exhausted, without one they are dropped
+    pub fn with_dead_letter(mut self, dead_letter: Arc<dyn Transport<T>>) -> Self {
+        self.dead_letter = Some(dead_letter);
+        self
+    }
+
+    /// Queues an item, linking its outcome to the `receipt` if provided
+    fn enqueue(
+        &self,
+        data: T,
+        receipt: Option<Arc<ReceiptState>>,
+    ) -> Result<DeliveryId, TransportError> {
+        let mut state = self.state.lock()?;
+        let id = state.next_id;
+        state.next_id += 1;
+        state.deliveries.insert(
+            id,
+            Delivery {
+                data,
+                attempts: 0,
+                deadline: None,
+                receipt,
+            },
+        );
+        state.ready.push_back(id);
+        self.condvar.notify_all();
+        self.notifier.notify_waiters();
+        Ok(id)
+    }
+
+    /// Queues an item as part of a tracked send, called by `Publisher::send_tracked`
+    pub(crate) fn enqueue_tracked(
+        &self,
+        data: T,
+        receipt: &DeliveryReceipt<T>,
+    ) -> Result<DeliveryId, TransportError> {
+        receipt.state.update(|counts| counts.pending += 1)?;
+        self.enqueue(data, Some(receipt.state.clone()))
+    }
+
+    /// Requeues a delivery if it has retries left, otherwise removes it and collects its data into `dead_lettered`.
+    /// The dead lettered data is sent by `send_dead_lettered()` once the state lock is released
+    fn retry(
+        &self,
+        state: &mut AckState<T>,
+        id: DeliveryId,
+        dead_lettered: &mut Vec<T>,
+    ) -> Result<(), TransportError> {
+        let Some(delivery) = state.deliveries.get_mut(&id) else {
+            return Ok(());
+        };
+        if delivery.attempts <= self.max_retries {
+            delivery.deadline = None;
+            let receipt = delivery.receipt.clone();
+            state.ready.push_back(id);
+            self.condvar.notify_all();
+            self.notifier.notify_waiters();
+            if let Some(receipt) = receipt {
+                receipt.update(|counts| counts.redeliveries += 1)?;
+            }
+            return Ok(());
+        }
+
+        if let Some(delivery) = state.deliveries.remove(&id) {
+            dead_lettered.push(delivery.data);
+            if let Some(receipt) = &delivery.receipt {
+                receipt.update(|counts| {
+                    counts.pending -= 1;
+                    counts.dead_lettered += 1;
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends data out of retries to the dead letter transport, dropping it without one.
+    /// Called without the state lock held, so a slow or reentrant dead letter transport doesn't stall the `AckTransport`
+    fn send_dead_lettered(&self, dead_lettered: Vec<T>) -> Result<(), TransportError> {
+        match &self.dead_letter {
+            Some(dead_letter) if !dead_lettered.is_empty() => {
+                dead_letter.send_batch_blocking(dead_lettered)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Retries every in flight delivery whose ack timeout has passed, returning the next deadline still in flight
+    pub fn redeliver_expired(&self) -> Result<Option<Instant>, TransportError> {
+        let mut dead_lettered = Vec::new();
+        let next_deadline =
+            self.redeliver_expired_locked(&mut *self.state.lock()?, &mut dead_lettered);
+        self.send_dead_lettered(dead_lettered)?;
+        next_deadline
+    }
+
+    /// Retries every expired delivery even if one of them fails, returning the first error once all of them were retried
+    fn redeliver_expired_locked(
+        &self,
+        state: &mut AckState<T>,
+        dead_lettered: &mut Vec<T>,
+    ) -> Result<Option<Instant>, TransportError> {
+        let now = Instant::now();
+        let expired = state
+            .deliveries
+            .iter()
+            .filter(|(_, delivery)| delivery.deadline.is_some_and(|deadline| deadline <= now))
+            .map(|(id, _)| *id)
+            .collect::<Vec<DeliveryId>>();
+        let mut first_err = None;
+        for id in expired {
+            if let Err(e) = self.retry(state, id, dead_lettered) {
+                first_err.get_or_insert(e);
+            }
+        }
+        if let Some(e) = first_err {
+            return Err(e);
+        }
+        Ok(state
+            .deliveries
+            .values()
+            .filter_map(|delivery| delivery.deadline)
+            .min())
+    }
+
+    /// Takes the next ready delivery, marking it in flight until acked, nacked or timed out
+    fn take_ready(&self, state: &mut AckState<T>) -> Option<(DeliveryId, T)> {
+        let id = state.ready.pop_front()?;
+        let delivery = state.deliveries.get_mut(&id)?;
+        delivery.attempts += 1;
+        delivery.deadline = Some(Instant::now() + self.ack_timeout);
+        Some((id, delivery.data.clone()))
+    }
+
+    fn is_in_flight(state: &AckState<T>, id: DeliveryId) -> bool {
+        state
+            .deliveries
+            .get(&id)
+            .is_some_and(|delivery| delivery.deadline.is_some())
+    }
+
+    /// Marks a delivery as processed, returning false if it isn't in flight
+    pub fn ack(&self, id: DeliveryId) -> Result<bool, TransportError> {
+        let mut state = self.state.lock()?;
+        if !Self::is_in_flight(&state, id) {
+            return Ok(false);
+        }
+        if let Some(Delivery {
+            receipt: Some(receipt),
+            ..
+        }) = state.deliveries.remove(&id)
+        {
+            receipt.update(|counts| {
+                counts.pending -= 1;
+                counts.acked += 1;
+            })?;
+        }
+        Ok(true)
+    }
+
+    /// Marks a delivery as failed so it is redelivered or dead lettered, returning false if it isn't in flight
+    pub fn nack(&self, id: DeliveryId) -> Result<bool, TransportError> {
+        let mut dead_lettered = Vec::new();
+        let retried = {
+            let mut state = self.state.lock()?;
+            if !Self::is_in_flight(&state, id) {
+                return Ok(false);
+            }
+            self.retry(&mut state, id, &mut dead_lettered)
+        };
+        self.send_dead_lettered(dead_lettered)?;
+        retried.map(|_| true)
+    }
+
+    /// Try receives the next delivery without blocking, the item must then be acked or nacked by its `DeliveryId`
+    pub fn try_recv_tracked(&self) -> Result<Option<(DeliveryId, T)>, TransportError> {
+        let mut dead_lettered = Vec::new();
+        let delivery = {
+            let mut state = self.state.lock()?;
+            self.redeliver_expired_locked(&mut state, &mut dead_lettered)
+                .map(|_| self.take_ready(&mut state))
+        };
+        self.send_dead_lettered(dead_lettered)?;
+        delivery
+    }
+
+    /// Receives the next delivery, blocking the current thread until one is ready
+    pub fn recv_tracked_blocking(&self) -> Result<(DeliveryId, T), TransportError> {
+        let mut state = self.state.lock()?;
+
+        loop {
+            let mut dead_lettered = Vec::new();
+            let next_deadline = self.redeliver_expired_locked(&mut state, &mut dead_lettered);
+            if !dead_lettered.is_empty() {
+                drop(state);
+                self.send_dead_lettered(dead_lettered)?;
+                state = self.state.lock()?;
+            }
+            let next_deadline = next_deadline?;
+            if let Some(delivery) = self.take_ready(&mut state) {
+                return Ok(delivery);
+            }
+            state = match next_deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    self.condvar.wait_timeout(state, timeout)?.0
+                }
+                None => self.condvar.wait(state)?,
+            };
+        }
+    }
+
+    /// Receives the next delivery asynchronously, the item must then be acked or nacked by its `DeliveryId`
+    pub async fn recv_tracked(&self) -> Result<(DeliveryId, T), TransportError> {
+        loop {
+            // Register for notifications before checking the deliveries so an enqueue between the check and the wait isn't missed
+            let notified = self.notifier.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let mut dead_lettered = Vec::new();
+            let next_deadline = match self.state.lock() {
+                Ok(mut state) => self
+                    .redeliver_expired_locked(&mut state, &mut dead_lettered)
+                    .map(|next_deadline| (next_deadline, self.take_ready(&mut state))),
+                Err(e) => return Err(e.into()),
+            };
+            self.send_dead_lettered(dead_lettered)?;
+            let next_deadline = match next_deadline? {
+                (_, Some(delivery)) => return Ok(delivery),
+                (next_deadline, None) => next_deadline,
+            };
+
+            match next_deadline {
+                Some(deadline) => {
+                    tokio::select! {
+                        _ = notified => {}
+                        _ = tokio::time::sleep_until(deadline) => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+}
+
+/// Impl transport for ack transport, where the plain recv methods ack each item as it is received
+impl<T: TransportItemRequirements> Transport<T> for AckTransport<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        self.enqueue(data, None).map(|_| ())
+    }
+
+    fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        for item in data {
+            self.enqueue(item, None)?;
+        }
+        Ok(())
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        let (id, data) = self.recv_tracked_blocking()?;
+        self.ack(id)?;
+        Ok(data)
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        let mut data = Vec::new();
+        while let Some(item) = self.try_recv_blocking()? {
+            data.push(item);
+        }
+        Ok(data)
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        match self.try_recv_tracked()? {
+            Some((id, data)) => {
+                self.ack(id)?;
+                Ok(Some(data))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_batch_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async {
+            let (id, data) = self.recv_tracked().await?;
+            self.ack(id)?;
+            Ok(data)
+        })
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.recv_avaliable_blocking();
+        Box::pin(async { result })
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.try_recv_blocking();
+        Box::pin(async { result })
+    }
+}
+
+/// Receipt for a `Publisher::send_tracked`, resolving once every `AckTransport` subscriber has acked or dead lettered the item
+pub struct DeliveryReceipt<T> {
+    state: Arc<ReceiptState>,
+    transports: Vec<Arc<AckTransport<T>>>,
+}
+
+impl<T> std::fmt::Debug for DeliveryReceipt<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.state.counts.lock() {
+            Ok(counts) => f
+                .debug_struct("DeliveryReceipt")
+                .field("pending", &counts.pending)
+                .field("acked", &counts.acked)
+                .field("dead_lettered", &counts.dead_lettered)
+                .field("redeliveries", &counts.redeliveries)
+                .finish(),
+            Err(e) => f
+                .debug_struct("DeliveryReceipt")
+                .field("counts", &format!("<LockPoisoned>: {}", e))
+                .finish(),
+        }
+    }
+}
+
+impl<T: TransportItemRequirements> DeliveryReceipt<T> {
+    pub(crate) fn new(transports: Vec<Arc<AckTransport<T>>>) -> Self {
+        Self {
+            state: Arc::new(ReceiptState::default()),
+            transports,
+        }
+    }
+
+    /// Returns the number of subscribers yet to ack or dead letter the item
+    pub fn pending(&self) -> Result<usize, TransportError> {
+        Ok(self.state.counts.lock()?.pending)
+    }
+
+    /// Returns the number of subscribers that acked the item
+    pub fn acked(&self) -> Result<usize, TransportError> {
+        Ok(self.state.counts.lock()?.acked)
+    }
+
+    /// Returns the number of subscribers that exhausted their retries and dead lettered the item
+    pub fn dead_lettered(&self) -> Result<usize, TransportError> {
+        Ok(self.state.counts.lock()?.dead_lettered)
+    }
+
+    /// Returns the total number of redeliveries across all subscribers
+    pub fn redeliveries(&self) -> Result<usize, TransportError> {
+        Ok(self.state.counts.lock()?.redeliveries)
+    }
+
+    /// Waits up to `timeout` for every subscriber to finish with the item, returning true only if all of them acked it.
+    ///
+    /// Ack timeouts of the subscribers are also checked while waiting, so items held by a stalled consumer are still redelivered
+    pub async fn all_acked(&self, timeout: Duration) -> Result<bool, TransportError> {
+        let end = Instant::now() + timeout;
+        loop {
+            let notified = self.state.notifier.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let mut next_deadline = None;
+            for transport in self.transports.iter() {
+                if let Some(deadline) = transport.redeliver_expired()? {
+                    next_deadline =
+                        Some(next_deadline.map_or(deadline, |d: Instant| d.min(deadline)));
+                }
+            }
+            {
+                let counts = self.state.counts.lock()?;
+                if counts.pending == 0 {
+                    return Ok(counts.dead_lettered == 0);
+                }
+            }
+            if Instant::now() >= end {
+                return Ok(false);
+            }
+
+            let wake = next_deadline.map_or(end, |deadline| deadline.min(end));
+            tokio::select! {
+                _ = notified => {}
+                _ = tokio::time::sleep_until(wake) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AckTransport, Publisher, Queue, Transport};
+    use std::{sync::Arc, time::Duration};
+
+    #[tokio::test]
+    async fn debug() {
+        let transport = AckTransport::<u8>::new(1, Duration::from_secs(1));
+        transport.send(1).await.unwrap();
+        transport.send(2).await.unwrap();
+        assert_eq!(
+            format!("{:?}", transport),
+            "AckTransport { ready: [1, 2], in_flight: 0 }"
+        );
+        transport.try_recv_tracked().unwrap();
+        assert_eq!(
+            format!("{:?}", transport),
+            "AckTransport { ready: [2], in_flight: 1 }"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        let transport = AckTransport::<u8>::new(0, Duration::from_secs(1));
+        // Plain recv methods ack on receipt
+        transport.send(1).await.unwrap();
+        transport.send_batch(vec![2, 3]).await.unwrap();
+        assert_eq!(transport.recv().await.unwrap(), 1);
+        assert_eq!(transport.try_recv().await.unwrap().unwrap(), 2);
+        assert_eq!(transport.recv_avaliable().await.unwrap(), vec![3]);
+
+        transport.send_blocking(1).unwrap();
+        transport.send_batch_blocking(vec![2, 3]).unwrap();
+        assert_eq!(transport.recv_blocking().unwrap(), 1);
+        assert_eq!(transport.try_recv_blocking().unwrap().unwrap(), 2);
+        assert_eq!(transport.recv_avaliable_blocking().unwrap(), vec![3]);
+        assert_eq!(transport.try_recv().await.unwrap(), None);
+
+        // Tracked items are redelivered when nacked, and can't be acked twice
+        let transport = AckTransport::<u8>::new(1, Duration::from_secs(1));
+        transport.send(4).await.unwrap();
+        let (id, data) = transport.recv_tracked().await.unwrap();
+        assert_eq!(data, 4);
+        assert!(transport.nack(id).unwrap());
+        let (id, data) = transport.recv_tracked_blocking().unwrap();
+        assert_eq!(data, 4);
+        assert!(transport.ack(id).unwrap());
+        assert!(!transport.ack(id).unwrap());
+        assert!(!transport.nack(id).unwrap());
+    }
+
+    #[tokio::test]
+    async fn tracked() {
+        let publisher = Publisher::<u8>::new();
+        let acking = Arc::new(AckTransport::<u8>::new(3, Duration::from_secs(1)));
+        let nacking = Arc::new(AckTransport::<u8>::new(3, Duration::from_secs(1)));
+        publisher.subscribe_acked(acking.clone()).unwrap();
+        publisher.subscribe_acked(nacking.clone()).unwrap();
+
+        let receipt = publisher.send_tracked(42).unwrap();
+        assert_eq!(receipt.pending().unwrap(), 2);
+
+        let (id, data) = acking.recv_tracked().await.unwrap();
+        assert_eq!(data, 42);
+        acking.ack(id).unwrap();
+
+        // Nack twice then ack
+        let nacking_clone = nacking.clone();
+        let handle = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (id, _) = nacking_clone.recv_tracked().await.unwrap();
+                nacking_clone.nack(id).unwrap();
+            }
+            let (id, data) = nacking_clone.recv_tracked().await.unwrap();
+            assert_eq!(data, 42);
+            nacking_clone.ack(id).unwrap();
+        });
+
+        assert!(receipt.all_acked(Duration::from_secs(1)).await.unwrap());
+        handle.await.unwrap();
+        assert_eq!(receipt.redeliveries().unwrap(), 2);
+        assert_eq!(receipt.acked().unwrap(), 2);
+        assert_eq!(receipt.dead_lettered().unwrap(), 0);
+        assert_eq!(receipt.pending().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn dead_letter() {
+        let dead_letter = Arc::new(Queue::<u8>::new());
+        let publisher = Publisher::<u8>::new();
+        let never_acking = Arc::new(
+            AckTransport::<u8>::new(2, Duration::from_millis(10))
+                .with_dead_letter(dead_letter.clone()),
+        );
+        publisher.subscribe_acked(never_acking.clone()).unwrap();
+
+        let receipt = publisher.send_tracked(7).unwrap();
+        // Receive every delivery without ever acking
+        for _ in 0..3 {
+            let (_, data) = never_acking.recv_tracked().await.unwrap();
+            assert_eq!(data, 7);
+        }
+
+        assert!(!receipt.all_acked(Duration::from_secs(1)).await.unwrap());
+        assert_eq!(receipt.redeliveries().unwrap(), 2);
+        assert_eq!(receipt.dead_lettered().unwrap(), 1);
+        assert_eq!(dead_letter.try_recv().await.unwrap(), Some(7));
+        assert_eq!(never_acking.try_recv_tracked().unwrap(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dead_letter_unlocked() {
+        // A dead letter transport sending back into the `AckTransport` doesn't deadlock on its state
+        let publisher = Arc::new(Publisher::<u8>::new());
+        let transport = Arc::new(
+            AckTransport::<u8>::new(0, Duration::from_millis(10))
+                .with_dead_letter(publisher.clone()),
+        );
+        publisher.subscribe_acked(transport.clone()).unwrap();
+        transport.send(7).await.unwrap();
+        let (first, _) = transport.recv_tracked().await.unwrap();
+        assert!(transport.nack(first).unwrap());
+        let (second, data) = transport.recv_tracked().await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(data, 7);
+
+        // A failing dead letter transport doesn't stop the sweep, every expired delivery is still retired
+        let dead_letter = Arc::new(Queue::<u8>::new());
+        dead_letter.close().unwrap();
+        let transport = AckTransport::<u8>::new(0, Duration::from_millis(10))
+            .with_dead_letter(dead_letter.clone());
+        transport.send_batch(vec![1, 2]).await.unwrap();
+        assert!(transport.try_recv_tracked().unwrap().is_some());
+        assert!(transport.try_recv_tracked().unwrap().is_some());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(transport.redeliver_expired().is_err());
+        assert_eq!(
+            format!("{:?}", transport),
+            "AckTransport { ready: [], in_flight: 0 }"
+        );
+    }
+
+    #[tokio::test]
+    async fn threaded() {
+        let transport = Arc::new(AckTransport::<u8>::new(0, Duration::from_secs(1)));
+        let transport_clone = transport.clone();
+        let handle = std::thread::spawn(move || {
+            let (id, data) = transport_clone.recv_tracked_blocking().unwrap();
+            assert_eq!(data, 42);
+            assert!(transport_clone.ack(id).unwrap());
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        transport.send_blocking(42).unwrap();
+        handle.join().unwrap();
+
+        let transport_clone = transport.clone();
+        let tokio_handle = tokio::spawn(async move {
+            assert_eq!(transport_clone.recv().await.unwrap(), 42);
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        transport.send(42).await.unwrap();
+        tokio_handle.await.unwrap();
+    }
+}
@@ -0,0 +1,695 @@
+use crate::{Command, SerdeFormat, Transport, TransportError};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Direction marker for a captured `Command`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptureDirection {
+    Sent,
+    Received,
+}
+
+impl CaptureDirection {
+    fn as_byte(&self) -> u8 {
+        match self {
+            CaptureDirection::Sent => 0,
+            CaptureDirection::Received => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, TransportError> {
+        match byte {
+            0 => Ok(CaptureDirection::Sent),
+            1 => Ok(CaptureDirection::Received),
+            _ => Err(TransportError::Custom(format!(
+                "Invalid capture direction byte `{byte}`"
+            ))),
+        }
+    }
+}
+
+/// A single captured `Command` with its direction and the time since `UNIX_EPOCH` it was captured
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureRecord {
+    pub direction: CaptureDirection,
+    pub timestamp: Duration,
+    pub command: Command,
+}
+
+impl CaptureRecord {
+    /// Byte length of the little endian `u32` prefix holding the length of the rest of the record
+    const LEN_PREFIX: usize = 4;
+
+    /// Byte length of the direction marker and timestamp preceding the encoded `Command`
+    const HEADER_LEN: usize = 9;
+
+    /// Encodes the record as `[record length: u32 LE][direction: u8][timestamp micros: u64 LE][command: SerdeFormat]`.
+    /// The length prefix lets records be appended to a single capture file and split apart again by `decode_all()`
+    pub fn to_bytes<F: SerdeFormat>(&self, format: &F) -> Result<Vec<u8>, TransportError> {
+        let command = format
+            .serialize_command(&self.command)
+            .map_err(|e| TransportError::Custom(format!("Failed to serialize command: {e}")))?;
+        let len = u32::try_from(Self::HEADER_LEN + command.len()).map_err(|_| {
+            TransportError::Custom(format!(
+                "Capture record of {} bytes is too long for its length prefix",
+                Self::HEADER_LEN + command.len()
+            ))
+        })?;
+        let mut bytes = Vec::with_capacity(Self::LEN_PREFIX + len as usize);
+        bytes.extend(len.to_le_bytes());
+        bytes.push(self.direction.as_byte());
+        bytes.extend((self.timestamp.as_micros() as u64).to_le_bytes());
+        bytes.extend(command);
+        Ok(bytes)
+    }
+
+    /// Decodes a single record previously encoded with `to_bytes` using the same `SerdeFormat`
+    pub fn from_bytes<F: SerdeFormat>(bytes: &[u8], format: &F) -> Result<Self, TransportError> {
+        let (record, rest) = Self::split_record(bytes)?;
+        if !rest.is_empty() {
+            return Err(TransportError::Custom(format!(
+                "Capture record is followed by {} unexpected bytes",
+                rest.len()
+            )));
+        }
+        Self::decode(record, format)
+    }
+
+    /// Decodes every length-prefixed record appended into `bytes`, such as the contents of a capture file.
+    /// Each record is decoded on its own, so a bad record only fails its own entry. A truncated or invalid length prefix ends the decoding with an error, as the following records can't be located
+    pub fn decode_all<F: SerdeFormat>(
+        bytes: &[u8],
+        format: &F,
+    ) -> Vec<Result<Self, TransportError>> {
+        let mut records = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            match Self::split_record(rest) {
+                Ok((record, next)) => {
+                    records.push(Self::decode(record, format));
+                    rest = next;
+                }
+                Err(e) => {
+                    records.push(Err(e));
+                    break;
+                }
+            }
+        }
+        records
+    }
+
+    /// Splits the first length-prefixed record from `bytes`, returning it without its prefix along with the remaining bytes
+    fn split_record(bytes: &[u8]) -> Result<(&[u8], &[u8]), TransportError> {
+        let Some((prefix, rest)) = bytes.split_first_chunk::<{ Self::LEN_PREFIX }>() else {
+            return Err(TransportError::Custom(format!(
+                "Capture record of {} bytes is shorter than its {} byte length prefix",
+                bytes.len(),
+                Self::LEN_PREFIX
+            )));
+        };
+        let len = u32::from_le_bytes(*prefix) as usize;
+        if rest.len() < len {
+            return Err(TransportError::Custom(format!(
+                "Capture record of {} bytes is truncated to {} bytes",
+                len,
+                rest.len()
+            )));
+        }
+        Ok(rest.split_at(len))
+    }
+
+    /// Decodes a record without its length prefix
+    fn decode<F: SerdeFormat>(bytes: &[u8], format: &F) -> Result<Self, TransportError> {
+        if bytes.len() < Self::HEADER_LEN {
+            return Err(TransportError::Custom(format!(
+                "Capture record of {} bytes is shorter than the {} byte header",
+                bytes.len(),
+                Self::HEADER_LEN
+            )));
+        }
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&bytes[1..Self::HEADER_LEN]);
+        Ok(Self {
+            direction: CaptureDirection::from_byte(bytes[0])?,
+            timestamp: Duration::from_micros(u64::from_le_bytes(timestamp)),
+            command: format
+                .deserialize_command(&bytes[Self::HEADER_LEN..])
+                .map_err(|e| {
+                    TransportError::Custom(format!("Failed to deserialize command: {e}"))
+                })?,
+        })
+    }
+}
+
+/* ********************
+  CaptureTransport
+******************** */
+/// `CaptureTransport<F>` wraps a `Transport<Command>`, passing all data through while appending each sent and received `Command` to a sink `Transport<Vec<u8>>`.
+/// Capture is best-effort, failures never fail the wrapped call but are counted and accessible through `capture_errors()`
+pub struct CaptureTransport<F: SerdeFormat> {
+    inner: Arc<dyn Transport<Command>>,
+    sink: Arc<dyn Transport<Vec<u8>>>,
+    format: F,
+    capture_errors: AtomicUsize,
+}
+
+impl<F: SerdeFormat> std::fmt::Debug for CaptureTransport<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CaptureTransport")
+            .field("inner", &self.inner)
+            .field("sink", &self.sink)
+            .field("format", &self.format)
+            .field("capture_errors", &self.capture_errors())
+            .finish()
+    }
+}
+
+impl<F: SerdeFormat> From<CaptureTransport<F>> for Arc<dyn Transport<Command>> {
+    fn from(value: CaptureTransport<F>) -> Self {
+        Arc::new(value)
+    }
+}
+
+impl<F: SerdeFormat> CaptureTransport<F> {
+    pub fn new(
+        inner: Arc<dyn Transport<Command>>,
+        sink: Arc<dyn Transport<Vec<u8>>>,
+        format: F,
+    ) -> Self {
+        Self {
+            inner,
+            sink,
+            format,
+            capture_errors: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the wrapped `Transport<Command>`
+    pub fn inner(&self) -> &Arc<dyn Transport<Command>> {
+        &self.inner
+    }
+
+    /// Returns the sink `Transport<Vec<u8>>` captured records are sent to
+    pub fn sink(&self) -> &Arc<dyn Transport<Vec<u8>>> {
+        &self.sink
+    }
+
+    /// Returns the number of records that failed to be encoded or sent to the sink
+    pub fn capture_errors(&self) -> usize {
+        self.capture_errors.load(Ordering::Relaxed)
+    }
+
+    /// Encodes each `Command` as a `CaptureRecord`, counting and skipping any that fail
+    fn encode<'a>(
+        &self,
+        direction: CaptureDirection,
+        commands: impl IntoIterator<Item = &'a Command>,
+    ) -> Vec<Vec<u8>> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        commands
+            .into_iter()
+            .filter_map(|command| {
+                CaptureRecord {
+                    direction,
+                    timestamp,
+                    command: command.clone(),
+                }
+                .to_bytes(&self.format)
+                .map_err(|_| self.capture_errors.fetch_add(1, Ordering::Relaxed))
+                .ok()
+            })
+            .collect()
+    }
+
+    /// Counts the passed number of records as failed if the sink returned an error
+    fn count_sink_result(&self, result: Result<(), TransportError>, records: usize) {
+        if result.is_err() {
+            self.capture_errors.fetch_add(records, Ordering::Relaxed);
+        }
+    }
+
+    fn capture_blocking(&self, records: Vec<Vec<u8>>) {
+        let len = records.len();
+        match len {
+            0 => (),
+            1 => self.count_sink_result(
+                self.sink
+                    .send_blocking(records.into_iter().next().unwrap_or_default()),
+                len,
+            ),
+            _ => self.count_sink_result(self.sink.send_batch_blocking(records), len),
+        }
+    }
+
+    async fn capture(&self, records: Vec<Vec<u8>>) {
+        let len = records.len();
+        match len {
+            0 => (),
+            1 => self.count_sink_result(
+                self.sink
+                    .send(records.into_iter().next().unwrap_or_default())
+                    .await,
+                len,
+            ),
+            _ => self.count_sink_result(self.sink.send_batch(records).await, len),
+        }
+    }
+}
+
+/// Passes all calls through to the inner `Transport<Command>`, capturing sent data after a successful send and received data after a successful receive
+impl<F: SerdeFormat> Transport<Command> for CaptureTransport<F> {
+    fn send_blocking(&self, data: Command) -> Result<(), TransportError> {
+        let records = self.encode(CaptureDirection::Sent, [&data]);
+        self.inner.send_blocking(data)?;
+        self.capture_blocking(records);
+        Ok(())
+    }
+
+    fn send_batch_blocking(&self, data: Vec<Command>) -> Result<(), TransportError> {
+        let records = self.encode(CaptureDirection::Sent, &data);
+        self.inner.send_batch_blocking(data)?;
+        self.capture_blocking(records);
+        Ok(())
+    }
+
+    fn recv_blocking(&self) -> Result<Command, TransportError> {
+        let data = self.inner.recv_blocking()?;
+        self.capture_blocking(self.encode(CaptureDirection::Received, [&data]));
+        Ok(data)
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<Command>, TransportError> {
+        let data = self.inner.recv_avaliable_blocking()?;
+        self.capture_blocking(self.encode(CaptureDirection::Received, &data));
+        Ok(data)
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<Command>, TransportError> {
+        let data = self.inner.try_recv_blocking()?;
+        self.capture_blocking(self.encode(CaptureDirection::Received, &data));
+        Ok(data)
+    }
+
+    fn send(
+        &self,
+        data: Command,
+    ) -> Pin<Box<dyn Future<Output = Result<(), TransportError>> + Send + Sync + '_>> {
+        Box::pin(async move {
+            let records = self.encode(CaptureDirection::Sent, [&data]);
+            self.inner.send(data).await?;
+            self.capture(records).await;
+            Ok(())
+        })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<Command>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), TransportError>> + Send + Sync + '_>> {
+        Box::pin(async move {
+            let records = self.encode(CaptureDirection::Sent, &data);
+            self.inner.send_batch(data).await?;
+            self.capture(records).await;
+            Ok(())
+        })
+    }
+
+    fn recv(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = Result<Command, TransportError>> + Send + Sync + '_>> {
+        Box::pin(async move {
+            let data = self.inner.recv().await?;
+            self.capture(self.encode(CaptureDirection::Received, [&data]))
+                .await;
+            Ok(data)
+        })
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Command>, TransportError>> + Send + Sync + '_>>
+    {
+        Box::pin(async move {
+            let data = self.inner.recv_avaliable().await?;
+            self.capture(self.encode(CaptureDirection::Received, &data))
+                .await;
+            Ok(data)
+        })
+    }
+
+    fn try_recv(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<Command>, TransportError>> + Send + Sync + '_>>
+    {
+        Box::pin(async move {
+            let data = self.inner.try_recv().await?;
+            self.capture(self.encode(CaptureDirection::Received, &data))
+                .await;
+            Ok(data)
+        })
+    }
+}
+
+/* ********************
+  CaptureReader
+******************** */
+/// `CaptureReader<F>` decodes the records captured by a `CaptureTransport<F>` for offline reproduction.
+/// Each `Vec<u8>` received from the source may hold one or more appended records, such as the contents of a capture file
+#[derive(Debug)]
+pub struct CaptureReader<F: SerdeFormat> {
+    source: Arc<dyn Transport<Vec<u8>>>,
+    format: F,
+}
+
+/// Outcome of a `CaptureReader` replay, with the number of replayed `Command`s and the error of each record skipped as it failed to decode
+#[derive(Debug, Default)]
+pub struct CaptureReplay {
+    pub replayed: usize,
+    pub errors: Vec<TransportError>,
+}
+
+impl<F: SerdeFormat> CaptureReader<F> {
+    /// Creates a `CaptureReader` reading records from `source`, which should use the same `SerdeFormat` as the capture
+    pub fn new(source: Arc<dyn Transport<Vec<u8>>>, format: F) -> Self {
+        Self { source, format }
+    }
+
+    /// Receives all currently avaliable data from the source, decoding each record on its own so a bad record doesn't fail the others
+    pub fn read_blocking(
+        &self,
+    ) -> Result<Vec<Result<CaptureRecord, TransportError>>, TransportError> {
+        Ok(self.decode(self.source.recv_avaliable_blocking()?))
+    }
+
+    /// Asynchronously receives all currently avaliable data from the source, see `read_blocking()`
+    pub async fn read(&self) -> Result<Vec<Result<CaptureRecord, TransportError>>, TransportError> {
+        Ok(self.decode(self.source.recv_avaliable().await?))
+    }
+
+    /// Replays all currently avaliable records into `target` in their captured order, skipping and reporting records that fail to decode.
+    /// If `direction` is `Some`, only records with a matching direction are replayed
+    pub async fn replay(
+        &self,
+        target: &Arc<dyn Transport<Command>>,
+        direction: Option<CaptureDirection>,
+    ) -> Result<CaptureReplay, TransportError> {
+        let (commands, errors) = Self::filter(self.read().await?, direction);
+        let replayed = commands.len();
+        target.send_batch(commands).await?;
+        Ok(CaptureReplay { replayed, errors })
+    }
+
+    /// Synchronously replays all currently avaliable records into `target`, see `replay()`
+    pub fn replay_blocking(
+        &self,
+        target: &Arc<dyn Transport<Command>>,
+        direction: Option<CaptureDirection>,
+    ) -> Result<CaptureReplay, TransportError> {
+        let (commands, errors) = Self::filter(self.read_blocking()?, direction);
+        let replayed = commands.len();
+        target.send_batch_blocking(commands)?;
+        Ok(CaptureReplay { replayed, errors })
+    }
+
+    fn decode(&self, data: Vec<Vec<u8>>) -> Vec<Result<CaptureRecord, TransportError>> {
+        data.iter()
+            .flat_map(|bytes| CaptureRecord::decode_all(bytes, &self.format))
+            .collect()
+    }
+
+    fn filter(
+        records: Vec<Result<CaptureRecord, TransportError>>,
+        direction: Option<CaptureDirection>,
+    ) -> (Vec<Command>, Vec<TransportError>) {
+        let mut commands = Vec::new();
+        let mut errors = Vec::new();
+        for record in records {
+            match record {
+                Ok(record) if direction.is_none_or(|direction| record.direction == direction) => {
+                    commands.push(record.command)
+                }
+                Ok(_) => (),
+                Err(e) => errors.push(e),
+            }
+        }
+        (commands, errors)
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use crate::{
+        CaptureDirection, CaptureReader, CaptureTransport, Command, JsonSerde, Queue, Transport,
+        TransportError,
+    };
+    use std::sync::Arc;
+
+    /// Sink that always fails, used to check capture errors don't fail the main path
+    #[derive(Debug)]
+    struct FailingSink;
+    impl Transport<Vec<u8>> for FailingSink {
+        fn send_blocking(&self, _: Vec<u8>) -> Result<(), TransportError> {
+            Err(TransportError::Custom("FailingSink".to_string()))
+        }
+        fn send_batch_blocking(&self, _: Vec<Vec<u8>>) -> Result<(), TransportError> {
+            Err(TransportError::Custom("FailingSink".to_string()))
+        }
+        fn recv_blocking(&self) -> Result<Vec<u8>, TransportError> {
+            Err(TransportError::NoData)
+        }
+        fn recv_avaliable_blocking(&self) -> Result<Vec<Vec<u8>>, TransportError> {
+            Err(TransportError::NoData)
+        }
+        fn try_recv_blocking(&self) -> Result<Option<Vec<u8>>, TransportError> {
+            Err(TransportError::NoData)
+        }
+        fn send(
+            &self,
+            data: Vec<u8>,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), TransportError>> + Send + Sync + '_>,
+        > {
+            Box::pin(async move { self.send_blocking(data) })
+        }
+        fn send_batch(
+            &self,
+            data: Vec<Vec<u8>>,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), TransportError>> + Send + Sync + '_>,
+        > {
+            Box::pin(async move { self.send_batch_blocking(data) })
+        }
+        fn recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<Vec<u8>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async move { self.recv_blocking() })
+        }
+        fn recv_avaliable(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<Vec<Vec<u8>>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async move { self.recv_avaliable_blocking() })
+        }
+        fn try_recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<Option<Vec<u8>>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async move { self.try_recv_blocking() })
+        }
+    }
+
+    #[tokio::test]
+    async fn debug() {
+        assert_eq!(
+            format!(
+                "{:?}",
+                CaptureTransport::new(
+                    Queue::<Command>::new().into(),
+                    Queue::<Vec<u8>>::new().into(),
+                    JsonSerde
+                )
+            ),
+            "CaptureTransport { inner: Queue { queue: [] }, sink: Queue { queue: [] }, format: JsonSerde, capture_errors: 0 }"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        let sink: Arc<dyn Transport<Vec<u8>>> = Queue::new().into();
+        let capture = CaptureTransport::new(Queue::new().into(), sink.clone(), JsonSerde);
+        capture.send(Command::Pulse).await.unwrap();
+        capture
+            .send_batch(vec![Command::Restart, Command::Stop])
+            .await
+            .unwrap();
+        assert_eq!(capture.recv().await.unwrap(), Command::Pulse);
+        assert_eq!(capture.try_recv().await.unwrap(), Some(Command::Restart));
+        assert_eq!(capture.recv_avaliable().await.unwrap(), vec![Command::Stop]);
+
+        capture.send_blocking(Command::Pulse).unwrap();
+        capture
+            .send_batch_blocking(vec![Command::Restart, Command::Stop])
+            .unwrap();
+        assert_eq!(capture.recv_blocking().unwrap(), Command::Pulse);
+        assert_eq!(capture.try_recv_blocking().unwrap(), Some(Command::Restart));
+        assert_eq!(
+            capture.recv_avaliable_blocking().unwrap(),
+            vec![Command::Stop]
+        );
+
+        // Every sent and received `Command` is captured
+        assert_eq!(capture.capture_errors(), 0);
+        let records = CaptureReader::new(sink, JsonSerde)
+            .read()
+            .await
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 12);
+        assert!(records[..3]
+            .iter()
+            .all(|record| record.direction == CaptureDirection::Sent));
+        assert!(records[3..6]
+            .iter()
+            .all(|record| record.direction == CaptureDirection::Received));
+    }
+
+    #[tokio::test]
+    async fn replay() {
+        let sink: Arc<dyn Transport<Vec<u8>>> = Queue::new().into();
+        let capture = CaptureTransport::new(Queue::new().into(), sink.clone(), JsonSerde);
+        let commands = vec![
+            Command::Pulse,
+            Command::Stop,
+            Command::Restart,
+            Command::Pulse,
+        ];
+        for command in commands.clone() {
+            capture.send(command).await.unwrap();
+        }
+        assert_eq!(capture.recv_avaliable().await.unwrap(), commands);
+
+        // Replay only the sent `Command`s into a fresh `Queue`
+        let queue: Arc<dyn Transport<Command>> = Queue::new().into();
+        let replay = CaptureReader::new(sink, JsonSerde)
+            .replay(&queue, Some(CaptureDirection::Sent))
+            .await
+            .unwrap();
+        assert_eq!(replay.replayed, commands.len());
+        assert!(replay.errors.is_empty());
+        assert_eq!(queue.recv_avaliable().await.unwrap(), commands);
+    }
+
+    #[tokio::test]
+    async fn capture_file() {
+        use crate::CaptureRecord;
+
+        // Records appended into one buffer, as a capture file holds them, are split apart again
+        let sink = Arc::new(Queue::<Vec<u8>>::new());
+        let capture = CaptureTransport::new(Queue::new().into(), sink.clone(), JsonSerde);
+        let commands = vec![Command::Pulse, Command::Stop, Command::Restart];
+        capture.send_batch(commands.clone()).await.unwrap();
+        let mut file = sink.recv_avaliable().await.unwrap().concat();
+        let records = CaptureRecord::decode_all(&file, &JsonSerde);
+        assert_eq!(
+            records
+                .into_iter()
+                .map(|record| record.unwrap().command)
+                .collect::<Vec<_>>(),
+            commands
+        );
+
+        // A bad record is reported on its own, while the records around it still replay
+        let pulse = CaptureRecord {
+            direction: CaptureDirection::Sent,
+            timestamp: std::time::Duration::ZERO,
+            command: Command::Pulse,
+        }
+        .to_bytes(&JsonSerde)
+        .unwrap();
+        let mut bad_direction = pulse.clone();
+        bad_direction[4] = 7;
+        file.extend(bad_direction);
+        file.extend(&pulse);
+        // A truncated record at the end, as left by an interrupted append, stops the decoding
+        file.extend(&pulse[..pulse.len() - 1]);
+        let source = Arc::new(Queue::<Vec<u8>>::new());
+        source.send(file).await.unwrap();
+        let queue: Arc<dyn Transport<Command>> = Queue::new().into();
+        let replay = CaptureReader::new(source, JsonSerde)
+            .replay(&queue, None)
+            .await
+            .unwrap();
+        assert_eq!(replay.replayed, 4);
+        assert_eq!(replay.errors.len(), 2);
+        assert_eq!(
+            queue.recv_avaliable().await.unwrap(),
+            vec![
+                Command::Pulse,
+                Command::Stop,
+                Command::Restart,
+                Command::Pulse
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn capture_errors() {
+        let capture = CaptureTransport::new(Queue::new().into(), Arc::new(FailingSink), JsonSerde);
+        capture.send(Command::Pulse).await.unwrap();
+        capture
+            .send_batch_blocking(vec![Command::Stop, Command::Restart])
+            .unwrap();
+        assert_eq!(capture.recv_avaliable().await.unwrap().len(), 3);
+        assert_eq!(capture.capture_errors(), 6);
+    }
+
+    #[tokio::test]
+    async fn threaded() {
+        let capture = Arc::new(CaptureTransport::new(
+            Queue::new().into(),
+            Queue::new().into(),
+            JsonSerde,
+        ));
+        let capture_clone = capture.clone();
+        let handle = tokio::spawn(async move {
+            assert_eq!(capture_clone.recv().await.unwrap(), Command::Stop);
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        capture.send(Command::Stop).await.unwrap();
+
+        handle.await.unwrap();
+        assert_eq!(capture.sink().recv_avaliable().await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,607 @@
+use crate::{
+    AsTaskState, ExtendedTaskState, SliceDebug, Task, Transport, TransportError,
+    TransportItemRequirements,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::time::Instant;
+
+type CoalesceTask<T> = Task<
+    (),
+    TransportError,
+    ExtendedTaskState<(), TransportError, (Arc<CoalesceBuffer<T>>, Arc<dyn Transport<T>>)>,
+>;
+
+/// Data waiting to be flushed, along with the deadline set when its first item arrived and the batch the delayed flush is sending
+#[derive(Debug)]
+struct CoalesceState<T> {
+    items: Vec<T>,
+    deadline: Option<Instant>,
+    in_flight: Vec<T>,
+    closed: bool,
+}
+
+/// Buffer shared between a `Coalesce` and its delayed flush `Task`
+struct CoalesceBuffer<T> {
+    state: Mutex<CoalesceState<T>>,
+    notifier: tokio::sync::Notify,
+    max_batch: usize,
+    max_delay: Duration,
+    flushes: AtomicUsize,
+    failed_flushes: AtomicUsize,
+}
+
+impl<T: Clone> CoalesceBuffer<T> {
+    /// Buffers `data`, returning every buffered item once `max_batch` is reached
+    fn push(&self, data: Vec<T>) -> Result<Vec<T>, TransportError> {
+        let mut state = self.state.lock()?;
+        if state.closed {
+            return Err(TransportError::Closed);
+        }
+        if state.items.is_empty() {
+            state.deadline = Some(Instant::now() + self.max_delay);
+            self.notifier.notify_one();
+        }
+        state.items.extend(data);
+        match state.items.len() >= self.max_batch {
+            true => Ok(Self::take(&mut state)),
+            false => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns every buffered item once the deadline has passed, keeping a copy as the in flight batch until the flush completes
+    fn take_expired(&self) -> Result<Vec<T>, TransportError> {
+        let mut state = self.state.lock()?;
+        match state.deadline {
+            Some(deadline) if deadline <= Instant::now() => {
+                let batch = Self::take(&mut state);
+                state.in_flight = batch.clone();
+                Ok(batch)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Completes the in flight batch, putting it back at the front of the buffer to retry after `max_delay` if the flush failed
+    fn complete_in_flight(&self, result: Result<(), TransportError>) -> Result<(), TransportError> {
+        let mut state = self.state.lock()?;
+        let mut batch = std::mem::take(&mut state.in_flight);
+        match result {
+            Ok(()) => self.flushes.fetch_add(1, Ordering::Relaxed),
+            Err(_) => {
+                batch.append(&mut state.items);
+                state.items = batch;
+                state.deadline = Some(Instant::now() + self.max_delay);
+                self.notifier.notify_one();
+                self.failed_flushes.fetch_add(1, Ordering::Relaxed)
+            }
+        };
+        Ok(())
+    }
+
+    fn take(state: &mut CoalesceState<T>) -> Vec<T> {
+        state.deadline = None;
+        std::mem::take(&mut state.items)
+    }
+}
+
+/// `Coalesce<T>` wraps a `Transport<T>`, buffering sent data and flushing it to the inner transport with `send_batch` once `max_batch` items are buffered or `max_delay` has passed since the first.
+/// Any data still buffered is flushed on `close()` or drop. Receiving is passed directly to the inner transport
+pub struct Coalesce<T: TransportItemRequirements> {
+    buffer: Arc<CoalesceBuffer<T>>,
+    inner: Arc<dyn Transport<T>>,
+    flush_task: CoalesceTask<T>,
+}
+
+impl<T: TransportItemRequirements> std::fmt::Debug for Coalesce<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("Coalesce");
+        match self.buffer.state.lock() {
+            Ok(state) => debug.field("buffer", &SliceDebug::with_len(3, &state.items)),
+            Err(e) => debug.field("buffer", &format!("<LockPoisoned>: {}", e)),
+        };
+        debug
+            .field("inner", &self.inner)
+            .field("max_batch", &self.buffer.max_batch)
+            .field("max_delay", &self.buffer.max_delay)
+            .finish()
+    }
+}
+
+impl<T: TransportItemRequirements> From<Coalesce<T>> for Arc<dyn Transport<T>> {
+    fn from(coalesce: Coalesce<T>) -> Self {
+        Arc::new(coalesce)
+    }
+}
+
+impl<T: TransportItemRequirements> Coalesce<T> {
+    /// Creates a new `Coalesce` with a `Task` flushing buffered data to `inner` once `max_delay` passes
+    pub fn new(inner: Arc<dyn Transport<T>>, max_batch: usize, max_delay: Duration) -> Self {
+        let buffer = Arc::new(CoalesceBuffer {
+            state: Mutex::new(CoalesceState {
+                items: Vec::new(),
+                deadline: None,
+                in_flight: Vec::new(),
+                closed: false,
+            }),
+            notifier: tokio::sync::Notify::new(),
+            max_batch: max_batch.max(1),
+            max_delay,
+            flushes: AtomicUsize::new(0),
+            failed_flushes: AtomicUsize::new(0),
+        });
+        Self {
+            buffer: buffer.clone(),
+            inner: inner.clone(),
+            flush_task: Task::infinite(
+                |_, state| {
+                    let state = state.clone();
+                    async move {
+                        let (buffer, inner) = state.read().await.inner_clone();
+                        // This tight inner loop waits for the first buffered item, then flushes once its deadline passes if `max_batch` didn't flush it first
+                        loop {
+                            let notified = buffer.notifier.notified();
+                            tokio::pin!(notified);
+                            notified.as_mut().enable();
+
+                            let deadline = buffer.state.lock()?.deadline;
+                            match deadline {
+                                Some(deadline) => {
+                                    tokio::time::sleep_until(deadline).await;
+                                    let data = buffer.take_expired()?;
+                                    if !data.is_empty() {
+                                        let result = inner.send_batch(data).await;
+                                        buffer.complete_in_flight(result)?;
+                                    }
+                                }
+                                None => notified.await,
+                            }
+                        }
+                    }
+                },
+                (buffer, inner).as_task_state(),
+            ),
+        }
+    }
+
+    /// Get the inner transport
+    pub fn inner(&self) -> &Arc<dyn Transport<T>> {
+        &self.inner
+    }
+
+    /// Returns the number of batches successfully flushed to the inner transport
+    pub fn flushed_batches(&self) -> usize {
+        self.buffer.flushes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of batches the inner transport failed to receive.
+    /// A failed delayed flush is put back in the buffer to retry, while any other failed flush returns the error to the caller
+    pub fn failed_flushes(&self) -> usize {
+        self.buffer.failed_flushes.load(Ordering::Relaxed)
+    }
+
+    /// Takes every buffered item, followed by `data`
+    fn take_with(&self, data: Vec<T>) -> Result<Vec<T>, TransportError> {
+        let mut state = self.buffer.state.lock()?;
+        let mut batch = CoalesceBuffer::take(&mut state);
+        batch.extend(data);
+        Ok(batch)
+    }
+
+    /// Counts the flush of a non empty batch as either flushed or failed
+    fn count_flush(&self, result: Result<(), TransportError>) -> Result<(), TransportError> {
+        match &result {
+            Ok(()) => self.buffer.flushes.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.buffer.failed_flushes.fetch_add(1, Ordering::Relaxed),
+        };
+        result
+    }
+
+    /// Synchronously send `data` to the inner transport, if there is any
+    fn flush_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.count_flush(self.inner.send_batch_blocking(data))
+    }
+
+    /// Asynchronously send `data` to the inner transport, if there is any
+    async fn flush_batch(&self, data: Vec<T>) -> Result<(), TransportError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.count_flush(self.inner.send_batch(data).await)
+    }
+
+    /// Synchronously flush every buffered item to the inner transport
+    pub fn flush_blocking(&self) -> Result<(), TransportError> {
+        self.flush_batch_blocking(self.take_with(Vec::new())?)
+    }
+
+    /// Asynchronously flush every buffered item to the inner transport
+    pub async fn flush(&self) -> Result<(), TransportError> {
+        self.flush_batch(self.take_with(Vec::new())?).await
+    }
+
+    /// Flushes every buffered item, after which any send returns `TransportError::Closed`
+    pub fn close(&self) -> Result<(), TransportError> {
+        self.buffer.state.lock()?.closed = true;
+        self.flush_blocking()
+    }
+}
+
+/// Flush any data still buffered on drop so it isn't lost.
+/// The delayed flush `Task` is aborted first, so a batch it was partway through sending is flushed again rather than dropped
+impl<T: TransportItemRequirements> Drop for Coalesce<T> {
+    fn drop(&mut self) {
+        self.flush_task.abort();
+        let data = match self.buffer.state.lock() {
+            Ok(mut state) => {
+                let mut data = std::mem::take(&mut state.in_flight);
+                data.extend(CoalesceBuffer::take(&mut state));
+                data
+            }
+            Err(_) => return,
+        };
+        let _ = self.flush_batch_blocking(data);
+    }
+}
+
+/// The `send()` and `send_blocking()` are buffered before reaching the inner `Transport<T>`, while `send_batch()` of at least `max_batch` items skips the buffer along with anything already buffered.
+/// The `recv()` and `recv_blocking()` go to the inner `Transport<T>`
+impl<T: TransportItemRequirements> Transport<T> for Coalesce<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        let batch = self.buffer.push(vec![data])?;
+        self.flush_batch_blocking(batch)
+    }
+
+    fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        let batch = match data.len() >= self.buffer.max_batch {
+            true if self.buffer.state.lock()?.closed => return Err(TransportError::Closed),
+            true => self.take_with(data)?,
+            false => self.buffer.push(data)?,
+        };
+        self.flush_batch_blocking(batch)
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        self.inner.recv_blocking()
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        self.inner.recv_avaliable_blocking()
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        self.inner.try_recv_blocking()
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async {
+            let batch = self.buffer.push(vec![data])?;
+            self.flush_batch(batch).await
+        })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async {
+            let batch = match data.len() >= self.buffer.max_batch {
+                true if self.buffer.state.lock()?.closed => return Err(TransportError::Closed),
+                true => self.take_with(data)?,
+                false => self.buffer.push(data)?,
+            };
+            self.flush_batch(batch).await
+        })
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.inner.recv()
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.inner.recv_avaliable()
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.inner.try_recv()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Coalesce, Queue, Transport, TransportError};
+    use std::{
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Transport failing the next `failures` sends before sending into its inner `Queue`.
+    /// While `stalled` is set its async sends never complete, so a delayed flush stays in flight
+    #[derive(Debug)]
+    struct Flaky {
+        failures: AtomicUsize,
+        stalled: AtomicBool,
+        inner: Queue<u8>,
+    }
+
+    impl Flaky {
+        fn new(failures: usize) -> Arc<Self> {
+            Arc::new(Self {
+                failures: AtomicUsize::new(failures),
+                stalled: AtomicBool::new(false),
+                inner: Queue::new(),
+            })
+        }
+
+        fn fail(&self) -> Result<(), TransportError> {
+            match self
+                .failures
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |f| f.checked_sub(1))
+            {
+                Ok(_) => Err(TransportError::Custom("Flaky".to_string())),
+                Err(_) => Ok(()),
+            }
+        }
+
+        async fn stall(&self) {
+            if self.stalled.load(Ordering::Relaxed) {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+
+    impl Transport<u8> for Flaky {
+        fn send_blocking(&self, data: u8) -> Result<(), TransportError> {
+            self.fail()?;
+            self.inner.send_blocking(data)
+        }
+        fn send_batch_blocking(&self, data: Vec<u8>) -> Result<(), TransportError> {
+            self.fail()?;
+            self.inner.send_batch_blocking(data)
+        }
+        fn recv_blocking(&self) -> Result<u8, TransportError> {
+            self.inner.recv_blocking()
+        }
+        fn recv_avaliable_blocking(&self) -> Result<Vec<u8>, TransportError> {
+            self.inner.recv_avaliable_blocking()
+        }
+        fn try_recv_blocking(&self) -> Result<Option<u8>, TransportError> {
+            self.inner.try_recv_blocking()
+        }
+        fn send(
+            &self,
+            data: u8,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), TransportError>> + Send + Sync + '_>,
+        > {
+            Box::pin(async move {
+                self.stall().await;
+                self.send_blocking(data)
+            })
+        }
+        fn send_batch(
+            &self,
+            data: Vec<u8>,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), TransportError>> + Send + Sync + '_>,
+        > {
+            Box::pin(async move {
+                self.stall().await;
+                self.send_batch_blocking(data)
+            })
+        }
+        fn recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<u8, TransportError>> + Send + Sync + '_>,
+        > {
+            self.inner.recv()
+        }
+        fn recv_avaliable(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<Vec<u8>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            self.inner.recv_avaliable()
+        }
+        fn try_recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<Option<u8>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            self.inner.try_recv()
+        }
+    }
+
+    #[tokio::test]
+    async fn debug() {
+        let coalesce = Coalesce::new(Queue::<u8>::new().into(), 4, Duration::from_secs(1));
+        coalesce.send(1).await.unwrap();
+        assert_eq!(
+            format!("{:?}", coalesce),
+            "Coalesce { buffer: [1], inner: Queue { queue: [] }, max_batch: 4, max_delay: 1s }"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        let coalesce = Coalesce::new(Queue::<u8>::new().into(), 3, Duration::from_secs(60));
+        coalesce.send(1).await.unwrap();
+        coalesce.send_blocking(2).unwrap();
+        assert_eq!(coalesce.try_recv().await.unwrap(), None);
+
+        // Reaching `max_batch` flushes the buffer as a single batch
+        coalesce.send_batch(vec![3]).await.unwrap();
+        assert_eq!(coalesce.flushed_batches(), 1);
+        assert_eq!(coalesce.recv().await.unwrap(), 1);
+        assert_eq!(coalesce.recv_avaliable_blocking().unwrap(), vec![2, 3]);
+
+        // Batches of at least `max_batch` skip the buffer, keeping anything already buffered ahead of them
+        coalesce.send_blocking(4).unwrap();
+        coalesce.send_batch_blocking(vec![5, 6, 7]).unwrap();
+        assert_eq!(coalesce.flushed_batches(), 2);
+        assert_eq!(coalesce.recv_avaliable().await.unwrap(), vec![4, 5, 6, 7]);
+
+        coalesce.send(8).await.unwrap();
+        coalesce.flush().await.unwrap();
+        assert_eq!(coalesce.try_recv_blocking().unwrap(), Some(8));
+    }
+
+    #[tokio::test]
+    async fn rapid_sends() {
+        let coalesce = Coalesce::new(Queue::<u16>::new().into(), 100, Duration::from_millis(5));
+        for i in 0..1000 {
+            coalesce.send(i).await.unwrap();
+        }
+        assert_eq!(coalesce.flushed_batches(), 10);
+        assert_eq!(
+            coalesce.recv_avaliable().await.unwrap(),
+            (0..1000).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn max_delay() {
+        let queue: Arc<dyn Transport<u8>> = Queue::new().into();
+        let coalesce = Coalesce::new(queue.clone(), 100, Duration::from_millis(20));
+        let start = tokio::time::Instant::now();
+        coalesce.send(1).await.unwrap();
+        assert_eq!(queue.try_recv().await.unwrap(), None);
+
+        // A lone send is flushed by the `Task` once `max_delay` passes
+        assert_eq!(queue.recv().await.unwrap(), 1);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(20));
+        assert!(elapsed < Duration::from_millis(200));
+        assert_eq!(coalesce.flushed_batches(), 1);
+
+        // The deadline restarts with the next buffered item
+        coalesce.send(2).await.unwrap();
+        assert_eq!(queue.recv().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn close() {
+        let queue: Arc<dyn Transport<u8>> = Queue::new().into();
+        let coalesce = Coalesce::new(queue.clone(), 100, Duration::from_secs(60));
+        coalesce.send_batch(vec![1, 2]).await.unwrap();
+        coalesce.close().unwrap();
+        assert_eq!(queue.recv_avaliable().await.unwrap(), vec![1, 2]);
+        assert!(matches!(
+            coalesce.send(3).await,
+            Err(TransportError::Closed)
+        ));
+        assert!(matches!(
+            coalesce.send_batch_blocking(vec![3; 100]),
+            Err(TransportError::Closed)
+        ));
+
+        // Dropping flushes the remaining buffered data
+        let coalesce = Coalesce::new(queue.clone(), 100, Duration::from_secs(60));
+        coalesce.send_blocking(4).unwrap();
+        coalesce.send(5).await.unwrap();
+        drop(coalesce);
+        assert_eq!(queue.recv_avaliable().await.unwrap(), vec![4, 5]);
+    }
+
+    #[tokio::test]
+    async fn inner_failure() {
+        let flaky = Flaky::new(1);
+        let coalesce = Coalesce::new(flaky.clone(), 100, Duration::from_millis(10));
+
+        // A failed flush returns the error to the caller and isn't counted as flushed
+        coalesce.send(1).await.unwrap();
+        assert!(coalesce.flush().await.is_err());
+        assert_eq!(coalesce.flushed_batches(), 0);
+        assert_eq!(coalesce.failed_flushes(), 1);
+
+        // A failed delayed flush puts the batch back in the buffer and retries after `max_delay`
+        flaky.failures.store(1, Ordering::Relaxed);
+        coalesce.send(2).await.unwrap();
+        assert_eq!(flaky.inner.recv().await.unwrap(), 2);
+        assert_eq!(coalesce.failed_flushes(), 2);
+        assert_eq!(coalesce.flushed_batches(), 1);
+    }
+
+    #[tokio::test]
+    async fn drop_mid_flush() {
+        let flaky = Flaky::new(0);
+        flaky.stalled.store(true, Ordering::Relaxed);
+        let coalesce = Coalesce::new(flaky.clone(), 100, Duration::from_millis(5));
+        coalesce.send(1).await.unwrap();
+
+        // Wait for the delayed flush to take the batch and stall sending it
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(coalesce.buffer.state.lock().unwrap().in_flight, vec![1]);
+        coalesce.send(2).await.unwrap();
+
+        // Dropping aborts the stalled flush and resends its batch ahead of the buffer
+        drop(coalesce);
+        assert_eq!(flaky.inner.recv_avaliable().await.unwrap(), vec![1, 2]);
+    }
+}
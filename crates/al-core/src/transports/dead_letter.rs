@@ -0,0 +1,503 @@
+use crate::{Transport, TransportError, TransportItemRequirements};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// How a `DeadLetter` retries sends, making up to `attempts` sends with `backoff` waited between each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    pub attempts: usize,
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(attempts: usize, backoff: Duration) -> Self {
+        Self { attempts, backoff }
+    }
+
+    /// Returns true if another attempt should be made after `attempt` failed, counting from `1`.
+    /// At least one attempt is always made, even with `attempts` of `0`
+    fn should_retry(&self, attempt: usize) -> bool {
+        attempt < self.attempts.max(1)
+    }
+}
+
+/// `DeadLetter<T>` wraps a `Transport<T>`, retrying failed sends with its `RetryPolicy` before diverting the data and final error to a dead letter sink.
+/// Receives pass through to the inner transport. Once diverted, the send returns `Ok` unless created `with_return_error(true)`.
+/// If the sink fails to receive the data, the send returns `TransportError::DivertFailed` with both errors and the data isn't counted as diverted
+pub struct DeadLetter<T: TransportItemRequirements> {
+    inner: Arc<dyn Transport<T>>,
+    policy: RetryPolicy,
+    sink: Arc<dyn Transport<(T, TransportError)>>,
+    return_error: bool,
+    retries: AtomicUsize,
+    diverted: AtomicUsize,
+}
+
+impl<T: TransportItemRequirements> std::fmt::Debug for DeadLetter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeadLetter")
+            .field("inner", &self.inner)
+            .field("policy", &self.policy)
+            .field("sink", &self.sink)
+            .field("retries", &self.retries())
+            .field("diverted", &self.diverted())
+            .finish()
+    }
+}
+
+impl<T: TransportItemRequirements> From<DeadLetter<T>> for Arc<dyn Transport<T>> {
+    fn from(value: DeadLetter<T>) -> Self {
+        Arc::new(value)
+    }
+}
+
+impl<T: TransportItemRequirements> DeadLetter<T> {
+    pub fn new(
+        inner: Arc<dyn Transport<T>>,
+        policy: RetryPolicy,
+        sink: Arc<dyn Transport<(T, TransportError)>>,
+    ) -> Self {
+        Self {
+            inner,
+            policy,
+            sink,
+            return_error: false,
+            retries: AtomicUsize::new(0),
+            diverted: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sets if sends diverted to the sink return the final error instead of `Ok`
+    pub fn with_return_error(mut self, return_error: bool) -> Self {
+        self.return_error = return_error;
+        self
+    }
+
+    pub fn inner(&self) -> &Arc<dyn Transport<T>> {
+        &self.inner
+    }
+
+    pub fn sink(&self) -> &Arc<dyn Transport<(T, TransportError)>> {
+        &self.sink
+    }
+
+    /// Returns the number of sends retried after failing
+    pub fn retries(&self) -> usize {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of items the sink received after they exhausted their retries
+    pub fn diverted(&self) -> usize {
+        self.diverted.load(Ordering::Relaxed)
+    }
+
+    /// Pairs each item with the final error for the sink
+    fn dead_letters(data: Vec<T>, error: &TransportError) -> Vec<(T, TransportError)> {
+        data.into_iter().map(|item| (item, error.clone())).collect()
+    }
+
+    /// Counts the `len` items as diverted if the sink received them, returning the final error if it should be returned, or both errors if the sink failed
+    fn diverted_result(
+        &self,
+        len: usize,
+        error: TransportError,
+        sink_result: Result<(), TransportError>,
+    ) -> Result<(), TransportError> {
+        if let Err(sink_error) = sink_result {
+            return Err(TransportError::DivertFailed {
+                error: Box::new(error),
+                sink_error: Box::new(sink_error),
+            });
+        }
+        self.diverted.fetch_add(len, Ordering::Relaxed);
+        match self.return_error {
+            true => Err(error),
+            false => Ok(()),
+        }
+    }
+
+    /// Synchronously sends the data with the final error to the sink
+    fn divert_blocking(&self, data: Vec<T>, error: TransportError) -> Result<(), TransportError> {
+        let len = data.len();
+        let sink_result = self
+            .sink
+            .send_batch_blocking(Self::dead_letters(data, &error));
+        self.diverted_result(len, error, sink_result)
+    }
+
+    /// Asynchronously sends the data with the final error to the sink
+    async fn divert(&self, data: Vec<T>, error: TransportError) -> Result<(), TransportError> {
+        let len = data.len();
+        let sink_result = self.sink.send_batch(Self::dead_letters(data, &error)).await;
+        self.diverted_result(len, error, sink_result)
+    }
+
+    fn send_retrying_blocking(
+        &self,
+        data: Vec<T>,
+        send: impl Fn(Vec<T>) -> Result<(), TransportError>,
+    ) -> Result<(), TransportError> {
+        let mut attempt = 1;
+        loop {
+            match send(data.clone()) {
+                Ok(()) => return Ok(()),
+                Err(e) if !self.policy.should_retry(attempt) => {
+                    return self.divert_blocking(data, e)
+                }
+                Err(_) => {
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    attempt += 1;
+                    std::thread::sleep(self.policy.backoff);
+                }
+            }
+        }
+    }
+
+    async fn send_retrying<'a, Fut>(
+        &'a self,
+        data: Vec<T>,
+        send: impl Fn(Vec<T>) -> Fut,
+    ) -> Result<(), TransportError>
+    where
+        Fut: std::future::Future<Output = Result<(), TransportError>> + 'a,
+    {
+        let mut attempt = 1;
+        loop {
+            match send(data.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(e) if !self.policy.should_retry(attempt) => return self.divert(data, e).await,
+                Err(_) => {
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    attempt += 1;
+                    tokio::time::sleep(self.policy.backoff).await;
+                }
+            }
+        }
+    }
+}
+
+/// Retries sends as a whole, with a failed batch diverting every item in it
+impl<T: TransportItemRequirements> Transport<T> for DeadLetter<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        self.send_retrying_blocking(vec![data], |mut data| {
+            self.inner.send_blocking(data.remove(0))
+        })
+    }
+
+    fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        self.send_retrying_blocking(data, |data| self.inner.send_batch_blocking(data))
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        self.inner.recv_blocking()
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        self.inner.recv_avaliable_blocking()
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        self.inner.try_recv_blocking()
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(self.send_retrying(vec![data], |mut data| self.inner.send(data.remove(0))))
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(self.send_retrying(data, |data| self.inner.send_batch(data)))
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.inner.recv()
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.inner.recv_avaliable()
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.inner.try_recv()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DeadLetter, Queue, RetryPolicy, Transport, TransportError};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Transport failing the next `failures` sends before sending into its inner `Queue`
+    #[derive(Debug)]
+    struct Flaky {
+        failures: AtomicUsize,
+        inner: Queue<u8>,
+    }
+
+    impl Flaky {
+        fn new(failures: usize) -> Arc<Self> {
+            Arc::new(Self {
+                failures: AtomicUsize::new(failures),
+                inner: Queue::new(),
+            })
+        }
+
+        fn fail(&self) -> Result<(), TransportError> {
+            match self
+                .failures
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |f| f.checked_sub(1))
+            {
+                Ok(_) => Err(TransportError::Custom("Flaky".to_string())),
+                Err(_) => Ok(()),
+            }
+        }
+    }
+
+    impl Transport<u8> for Flaky {
+        fn send_blocking(&self, data: u8) -> Result<(), TransportError> {
+            self.fail()?;
+            self.inner.send_blocking(data)
+        }
+        fn send_batch_blocking(&self, data: Vec<u8>) -> Result<(), TransportError> {
+            self.fail()?;
+            self.inner.send_batch_blocking(data)
+        }
+        fn recv_blocking(&self) -> Result<u8, TransportError> {
+            self.inner.recv_blocking()
+        }
+        fn recv_avaliable_blocking(&self) -> Result<Vec<u8>, TransportError> {
+            self.inner.recv_avaliable_blocking()
+        }
+        fn try_recv_blocking(&self) -> Result<Option<u8>, TransportError> {
+            self.inner.try_recv_blocking()
+        }
+        fn send(
+            &self,
+            data: u8,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), TransportError>> + Send + Sync + '_>,
+        > {
+            Box::pin(async move { self.send_blocking(data) })
+        }
+        fn send_batch(
+            &self,
+            data: Vec<u8>,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), TransportError>> + Send + Sync + '_>,
+        > {
+            Box::pin(async move { self.send_batch_blocking(data) })
+        }
+        fn recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<u8, TransportError>> + Send + Sync + '_>,
+        > {
+            self.inner.recv()
+        }
+        fn recv_avaliable(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<Vec<u8>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            self.inner.recv_avaliable()
+        }
+        fn try_recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<Option<u8>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            self.inner.try_recv()
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(3, Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn debug() {
+        let dead_letter = DeadLetter::<u8>::new(Queue::new().into(), policy(), Queue::new().into());
+        assert_eq!(
+            format!("{:?}", dead_letter),
+            "DeadLetter { inner: Queue { queue: [] }, policy: RetryPolicy { attempts: 3, backoff: 1ms }, sink: Queue { queue: [] }, retries: 0, diverted: 0 }"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        let dead_letter = DeadLetter::<u8>::new(Queue::new().into(), policy(), Queue::new().into());
+        dead_letter.send(1).await.unwrap();
+        dead_letter.send_batch(vec![2, 3]).await.unwrap();
+        dead_letter.send_blocking(4).unwrap();
+        assert_eq!(dead_letter.recv().await.unwrap(), 1);
+        assert_eq!(dead_letter.try_recv().await.unwrap(), Some(2));
+        assert_eq!(dead_letter.recv_avaliable_blocking().unwrap(), vec![3, 4]);
+        assert_eq!(dead_letter.retries(), 0);
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        // Failing the first 2 of 3 attempts still sends the data
+        let sink: Arc<dyn Transport<(u8, TransportError)>> = Queue::new().into();
+        let dead_letter = DeadLetter::new(Flaky::new(2), policy(), sink.clone());
+        dead_letter.send(1).await.unwrap();
+        assert_eq!(dead_letter.recv().await.unwrap(), 1);
+        assert_eq!(dead_letter.retries(), 2);
+
+        let dead_letter = DeadLetter::new(Flaky::new(2), policy(), sink.clone());
+        dead_letter.send_batch_blocking(vec![2, 3]).unwrap();
+        assert_eq!(dead_letter.recv_avaliable().await.unwrap(), vec![2, 3]);
+        assert_eq!(dead_letter.diverted(), 0);
+        assert!(sink.try_recv().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn divert() {
+        // Always failing diverts the data and final error to the sink, with the send returning `Ok`
+        let sink: Arc<dyn Transport<(u8, TransportError)>> = Queue::new().into();
+        let dead_letter = DeadLetter::new(Flaky::new(usize::MAX), policy(), sink.clone());
+        dead_letter.send(1).await.unwrap();
+        assert!(matches!(
+            sink.recv().await.unwrap(),
+            (1, TransportError::Custom(e)) if e == "Flaky"
+        ));
+        assert_eq!(dead_letter.retries(), 2);
+
+        // Every item of a failed batch is diverted
+        dead_letter.send_batch(vec![2, 3]).await.unwrap();
+        dead_letter.send_blocking(4).unwrap();
+        let diverted = sink.recv_avaliable().await.unwrap();
+        assert_eq!(
+            diverted.iter().map(|(item, _)| *item).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert_eq!(dead_letter.diverted(), 4);
+        assert!(dead_letter.try_recv().await.unwrap().is_none());
+
+        // Diverted sends can return the final error instead
+        let dead_letter =
+            DeadLetter::new(Flaky::new(usize::MAX), policy(), sink.clone()).with_return_error(true);
+        assert!(matches!(
+            dead_letter.send(5).await,
+            Err(TransportError::Custom(e)) if e == "Flaky"
+        ));
+        assert!(dead_letter.send_batch_blocking(vec![6]).is_err());
+        assert_eq!(sink.recv_avaliable().await.unwrap().len(), 2);
+
+        // A single attempt diverts without retrying
+        let dead_letter = DeadLetter::new(
+            Flaky::new(usize::MAX),
+            RetryPolicy::new(0, Duration::from_secs(1)),
+            sink.clone(),
+        );
+        dead_letter.send(7).await.unwrap();
+        assert_eq!(dead_letter.retries(), 0);
+        assert_eq!(dead_letter.diverted(), 1);
+    }
+
+    #[tokio::test]
+    async fn divert_failed() {
+        fn is_divert_failed(result: Result<(), TransportError>) -> bool {
+            matches!(
+                result,
+                Err(TransportError::DivertFailed { error, sink_error })
+                    if matches!(*error, TransportError::Custom(ref e) if e == "Flaky")
+                        && matches!(*sink_error, TransportError::Closed)
+            )
+        }
+
+        // A failing sink returns both errors, even without `with_return_error(true)`, and nothing counts as diverted
+        let sink = Queue::new();
+        sink.close().unwrap();
+        let dead_letter = DeadLetter::new(Flaky::new(usize::MAX), policy(), sink.into());
+        assert!(is_divert_failed(dead_letter.send(1).await));
+        assert!(is_divert_failed(dead_letter.send_batch(vec![2, 3]).await));
+        assert!(is_divert_failed(dead_letter.send_blocking(4)));
+        assert!(is_divert_failed(dead_letter.send_batch_blocking(vec![5])));
+        assert_eq!(dead_letter.diverted(), 0);
+        assert_eq!(dead_letter.retries(), 8);
+    }
+
+    #[tokio::test]
+    async fn threaded() {
+        let dead_letter = Arc::new(DeadLetter::<u8>::new(
+            Flaky::new(1),
+            policy(),
+            Queue::new().into(),
+        ));
+        let dead_letter_clone = dead_letter.clone();
+        let handle = std::thread::spawn(move || dead_letter_clone.recv_blocking().unwrap());
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        dead_letter.send_blocking(42).unwrap();
+        assert_eq!(handle.join().unwrap(), 42);
+    }
+}
@@ -0,0 +1,393 @@
+use crate::{SliceDebug, Transport, TransportError, TransportItemRequirements};
+use std::{
+    collections::BinaryHeap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Condvar, Mutex,
+    },
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// Ticket returned by `DelayQueue::send_delayed()`, allowing the delayed data to be cancelled before its deadline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DelayTicket(u64);
+
+/// Data held by a `DelayQueue` until its deadline
+struct Delayed<T> {
+    deadline: Instant,
+    id: u64,
+    data: T,
+}
+
+impl<T> PartialEq for Delayed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline && self.id == other.id
+    }
+}
+
+impl<T> Eq for Delayed<T> {}
+
+impl<T> PartialOrd for Delayed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Reversed ordering so the `BinaryHeap` max is the earliest deadline, with equal deadlines kept in FIFO order
+impl<T> Ord for Delayed<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .deadline
+            .cmp(&self.deadline)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+/// DelayQueue transport holding each item until its deadline, then receiving items in deadline order
+pub struct DelayQueue<T> {
+    queue: Mutex<BinaryHeap<Delayed<T>>>,
+    next_id: AtomicU64,
+    notifier: tokio::sync::Notify,
+    condvar: Condvar,
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for DelayQueue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.queue.lock() {
+            Ok(queue) => {
+                let mut items: Vec<&Delayed<T>> = queue.iter().collect();
+                items.sort_by(|a, b| b.cmp(a));
+                let items: Vec<&T> = items.into_iter().map(|item| &item.data).collect();
+                f.debug_struct("DelayQueue")
+                    .field("queue", &SliceDebug::with_len(3, items))
+                    .finish()
+            }
+            Err(e) => f
+                .debug_struct("DelayQueue")
+                .field("queue", &format!("<LockPoisoned>: {}", e))
+                .finish(),
+        }
+    }
+}
+
+impl<T: TransportItemRequirements> From<DelayQueue<T>> for std::sync::Arc<dyn Transport<T>> {
+    fn from(queue: DelayQueue<T>) -> Self {
+        std::sync::Arc::new(queue)
+    }
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DelayQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            queue: Mutex::new(BinaryHeap::new()),
+            next_id: AtomicU64::new(0),
+            notifier: tokio::sync::Notify::new(),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Sends the data to only become avaliable to receive once the delay has passed
+    pub fn send_delayed(&self, data: T, delay: Duration) -> Result<DelayTicket, TransportError> {
+        let id = self.push(vec![(data, Instant::now() + delay)])?;
+        Ok(DelayTicket(id))
+    }
+
+    /// Cancels delayed data before its deadline, returning false if it was already received or cancelled
+    pub fn cancel(&self, ticket: DelayTicket) -> Result<bool, TransportError> {
+        let mut queue = self.queue.lock()?;
+        let len = queue.len();
+        queue.retain(|item| item.id != ticket.0);
+        Ok(queue.len() != len)
+    }
+
+    /// Returns the number of held items, including those not yet past their deadline
+    pub fn len(&self) -> Result<usize, TransportError> {
+        Ok(self.queue.lock()?.len())
+    }
+
+    pub fn is_empty(&self) -> Result<bool, TransportError> {
+        Ok(self.queue.lock()?.is_empty())
+    }
+
+    /// Pushes each item with its deadline, waking all receivers to recheck the earliest deadline. Returns the id of the last item
+    fn push(&self, items: Vec<(T, Instant)>) -> Result<u64, TransportError> {
+        let mut queue = self.queue.lock()?;
+        let mut id = 0;
+        for (data, deadline) in items {
+            id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            queue.push(Delayed { deadline, id, data });
+        }
+        self.condvar.notify_all();
+        self.notifier.notify_waiters();
+        Ok(id)
+    }
+
+    /// Pops the earliest item if its deadline has passed, otherwise returning the earliest deadline if any
+    fn pop_ready(queue: &mut BinaryHeap<Delayed<T>>) -> Result<T, Option<Instant>> {
+        match queue.peek() {
+            Some(item) if item.deadline <= Instant::now() => {
+                queue.pop().map(|item| item.data).ok_or(None)
+            }
+            Some(item) => Err(Some(item.deadline)),
+            None => Err(None),
+        }
+    }
+
+    fn drain_ready(queue: &mut BinaryHeap<Delayed<T>>) -> Vec<T> {
+        let mut ready = Vec::new();
+        while let Ok(data) = Self::pop_ready(queue) {
+            ready.push(data);
+        }
+        ready
+    }
+}
+
+/// Impl transport for delay queue, where `send()` and `send_batch()` have no delay and `recv()` waits for the earliest deadline.
+/// `std::Mutex` is used rather than `tokio::Mutex` for lower overhead with the restriction of not holding locks across an `await`.
+impl<T: TransportItemRequirements> Transport<T> for DelayQueue<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        self.push(vec![(data, Instant::now())]).map(|_| ())
+    }
+
+    fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        let now = Instant::now();
+        self.push(data.into_iter().map(|data| (data, now)).collect())
+            .map(|_| ())
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        let mut guard = self.queue.lock()?;
+
+        loop {
+            guard = match Self::pop_ready(&mut guard) {
+                Ok(data) => return Ok(data),
+                Err(Some(deadline)) => {
+                    self.condvar
+                        .wait_timeout(guard, deadline.saturating_duration_since(Instant::now()))?
+                        .0
+                }
+                Err(None) => self.condvar.wait(guard)?,
+            };
+        }
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        Ok(Self::drain_ready(&mut *self.queue.lock()?))
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        Ok(Self::pop_ready(&mut *self.queue.lock()?).ok())
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_batch_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async {
+            loop {
+                // Register for notifications before checking the queue so a send between the check and the wait isn't missed
+                let notified = self.notifier.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                let next_deadline = match self.queue.lock() {
+                    Ok(mut queue) => match Self::pop_ready(&mut queue) {
+                        Ok(data) => return Ok(data),
+                        Err(next_deadline) => next_deadline,
+                    },
+                    Err(e) => return Err(e.into()),
+                };
+
+                match next_deadline {
+                    Some(deadline) => {
+                        tokio::select! {
+                            _ = tokio::time::sleep_until(deadline) => {}
+                            _ = notified => {}
+                        }
+                    }
+                    None => notified.await,
+                }
+            }
+        })
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.recv_avaliable_blocking();
+        Box::pin(async { result })
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.try_recv_blocking();
+        Box::pin(async { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DelayQueue, Transport};
+    use std::{sync::Arc, time::Duration};
+    use tokio::time::Instant;
+
+    /// Allowed lateness when checking a blocking receiver was woken at its deadline
+    const TOLERANCE: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn debug() {
+        let queue = DelayQueue::<u8>::new();
+        assert_eq!(format!("{:?}", queue), "DelayQueue { queue: [] }");
+        queue.send_delayed(3, Duration::from_secs(3)).unwrap();
+        queue.send_delayed(1, Duration::from_secs(1)).unwrap();
+        queue.send_batch_blocking(vec![0, 0]).unwrap();
+        assert_eq!(
+            format!("{:?}", queue),
+            "DelayQueue { queue: [0, 0, 1, +1 more...] }"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        let queue = DelayQueue::<u8>::new();
+        queue.send(1).await.unwrap();
+        queue.send_batch(vec![2, 3]).await.unwrap();
+        assert_eq!(queue.recv().await.unwrap(), 1);
+        assert_eq!(queue.try_recv().await.unwrap().unwrap(), 2);
+        assert_eq!(queue.recv_avaliable().await.unwrap(), vec![3]);
+
+        queue.send_blocking(1).unwrap();
+        queue.send_batch_blocking(vec![2, 3]).unwrap();
+        assert_eq!(queue.recv_blocking().unwrap(), 1);
+        assert_eq!(queue.try_recv_blocking().unwrap().unwrap(), 2);
+        assert_eq!(queue.recv_avaliable_blocking().unwrap(), vec![3]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn deadline_order() {
+        let queue = DelayQueue::<u64>::new();
+        let start = Instant::now();
+        for millis in [60, 20, 40] {
+            queue
+                .send_delayed(millis, Duration::from_millis(millis))
+                .unwrap();
+        }
+        // Nothing is avaliable before the deadlines
+        assert!(queue.try_recv().await.unwrap().is_none());
+        assert!(queue.recv_avaliable().await.unwrap().is_empty());
+
+        for millis in [20, 40] {
+            assert_eq!(queue.recv().await.unwrap(), millis);
+            assert_eq!(start.elapsed(), Duration::from_millis(millis));
+        }
+        tokio::time::advance(Duration::from_millis(19)).await;
+        assert!(queue.try_recv_blocking().unwrap().is_none());
+        tokio::time::advance(Duration::from_millis(1)).await;
+        assert_eq!(queue.recv_blocking().unwrap(), 60);
+        assert!(queue.is_empty().unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancel() {
+        let queue = DelayQueue::<u8>::new();
+        let ticket = queue.send_delayed(1, Duration::from_millis(10)).unwrap();
+        queue.send_delayed(2, Duration::from_millis(20)).unwrap();
+        assert!(queue.cancel(ticket).unwrap());
+        assert!(!queue.cancel(ticket).unwrap());
+        assert_eq!(queue.len().unwrap(), 1);
+        assert_eq!(queue.recv().await.unwrap(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn earlier_deadline() {
+        let queue = Arc::new(DelayQueue::<u8>::new());
+        queue.send_delayed(2, Duration::from_secs(5)).unwrap();
+
+        // An earlier item sent while waiting wakes the receiver before the first deadline
+        let queue_clone = queue.clone();
+        let start = Instant::now();
+        let handle = tokio::spawn(async move {
+            assert_eq!(queue_clone.recv().await.unwrap(), 1);
+            start.elapsed()
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        queue.send_delayed(1, Duration::from_millis(20)).unwrap();
+        assert_eq!(handle.await.unwrap(), Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn threaded() {
+        // An earlier item sent while waiting wakes a blocking receiver before the first deadline
+        let queue = Arc::new(DelayQueue::<u8>::new());
+        queue.send_delayed(2, Duration::from_secs(5)).unwrap();
+        let queue_clone = queue.clone();
+        let start = Instant::now();
+        let handle = std::thread::spawn(move || {
+            assert_eq!(queue_clone.recv_blocking().unwrap(), 3);
+            start.elapsed()
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        queue.send_delayed(3, Duration::from_millis(20)).unwrap();
+        let elapsed = handle.join().unwrap();
+        assert!(elapsed >= Duration::from_millis(30));
+        assert!(elapsed <= Duration::from_millis(30) + TOLERANCE);
+    }
+}
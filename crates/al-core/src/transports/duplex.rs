@@ -0,0 +1,370 @@
+use crate::{SliceDebug, Transport, TransportError, TransportItemRequirements};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex},
+};
+
+/// Items waiting to be received by one `DuplexEnd`, and whether the pair has been closed
+#[derive(Debug)]
+struct DuplexState<T> {
+    items: VecDeque<T>,
+    closed: bool,
+}
+
+impl<T> DuplexState<T> {
+    /// Pops the next item, returning `TransportError::Closed` only once the pair is closed and every item is drained
+    fn pop(&mut self) -> Result<Option<T>, TransportError> {
+        match self.items.pop_front() {
+            None if self.closed => Err(TransportError::Closed),
+            item => Ok(item),
+        }
+    }
+
+    fn drain(&mut self) -> Result<Vec<T>, TransportError> {
+        match self.items.is_empty() && self.closed {
+            true => Err(TransportError::Closed),
+            false => Ok(self.items.drain(..).collect()),
+        }
+    }
+}
+
+/// Receive buffer of a single `DuplexEnd`, written to by the opposite end
+struct DuplexBuffer<T> {
+    state: Mutex<DuplexState<T>>,
+    notifier: tokio::sync::Notify,
+    condvar: Condvar,
+}
+
+impl<T> DuplexBuffer<T> {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(DuplexState {
+                items: VecDeque::new(),
+                closed: false,
+            }),
+            notifier: tokio::sync::Notify::new(),
+            condvar: Condvar::new(),
+        }
+    }
+
+    fn push(&self, data: Vec<T>) -> Result<(), TransportError> {
+        let mut state = self.state.lock()?;
+        if state.closed {
+            return Err(TransportError::Closed);
+        }
+        state.items.extend(data);
+        self.condvar.notify_all();
+        self.notifier.notify_waiters();
+        Ok(())
+    }
+
+    fn close(&self) -> Result<(), TransportError> {
+        self.state.lock()?.closed = true;
+        self.condvar.notify_all();
+        self.notifier.notify_waiters();
+        Ok(())
+    }
+}
+
+/// Creates a connected pair of `DuplexEnd`s, where data sent through one end is received by the other
+pub fn duplex<T>() -> (DuplexEnd<T>, DuplexEnd<T>) {
+    let first = Arc::new(DuplexBuffer::new());
+    let second = Arc::new(DuplexBuffer::new());
+    (
+        DuplexEnd {
+            inbox: first.clone(),
+            outbox: second.clone(),
+        },
+        DuplexEnd {
+            inbox: second,
+            outbox: first,
+        },
+    )
+}
+
+/// One end of a bidirectional pair created by `duplex()`, sending into the opposite end and receiving from its own buffer.
+/// Closing or dropping either end closes the pair, after which both ends fail to send and receive `TransportError::Closed` once drained
+pub struct DuplexEnd<T> {
+    inbox: Arc<DuplexBuffer<T>>,
+    outbox: Arc<DuplexBuffer<T>>,
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for DuplexEnd<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.inbox.state.lock() {
+            Ok(mut state) => f
+                .debug_struct("DuplexEnd")
+                .field(
+                    "inbox",
+                    &SliceDebug::with_len(3, state.items.make_contiguous()),
+                )
+                .field("closed", &state.closed)
+                .finish(),
+            Err(e) => f
+                .debug_struct("DuplexEnd")
+                .field("inbox", &format!("<LockPoisoned>: {}", e))
+                .finish(),
+        }
+    }
+}
+
+impl<T: TransportItemRequirements> From<DuplexEnd<T>> for Arc<dyn Transport<T>> {
+    fn from(end: DuplexEnd<T>) -> Self {
+        Arc::new(end)
+    }
+}
+
+impl<T> DuplexEnd<T> {
+    /// Closes both directions of the pair, letting data already sent still be received by either end
+    pub fn close(&self) -> Result<(), TransportError> {
+        self.outbox.close()?;
+        self.inbox.close()
+    }
+
+    /// Returns true once either end of the pair has been closed
+    pub fn is_closed(&self) -> Result<bool, TransportError> {
+        Ok(self.inbox.state.lock()?.closed)
+    }
+}
+
+/// Close the pair once an end is dropped so the opposite end doesn't wait forever
+impl<T> Drop for DuplexEnd<T> {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+impl<T: TransportItemRequirements> Transport<T> for DuplexEnd<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        self.outbox.push(vec![data])
+    }
+
+    fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        self.outbox.push(data)
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        let mut state = self.inbox.state.lock()?;
+
+        loop {
+            if let Some(data) = state.pop()? {
+                return Ok(data);
+            }
+            state = self.inbox.condvar.wait(state)?;
+        }
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        self.inbox.state.lock()?.drain()
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        self.inbox.state.lock()?.pop()
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_batch_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async {
+            loop {
+                // Register for notifications before checking so a send or close between the check and the await isn't missed
+                let notified = self.inbox.notifier.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if let Some(data) = self.inbox.state.lock()?.pop()? {
+                    return Ok(data);
+                }
+
+                notified.await;
+            }
+        })
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.recv_avaliable_blocking();
+        Box::pin(async { result })
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.try_recv_blocking();
+        Box::pin(async { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{duplex, Transport, TransportError};
+
+    #[tokio::test]
+    async fn debug() {
+        let (first, second) = duplex::<u8>();
+        second.send(1).await.unwrap();
+        assert_eq!(
+            format!("{:?}", first),
+            "DuplexEnd { inbox: [1], closed: false }"
+        );
+        assert_eq!(
+            format!("{:?}", second),
+            "DuplexEnd { inbox: [], closed: false }"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        let (first, second) = duplex::<u8>();
+        first.send(1).await.unwrap();
+        first.send_batch(vec![2, 3]).await.unwrap();
+        second.send_blocking(4).unwrap();
+
+        // Each end only receives what the opposite end sent
+        assert_eq!(second.recv().await.unwrap(), 1);
+        assert_eq!(second.recv_avaliable().await.unwrap(), vec![2, 3]);
+        assert_eq!(second.try_recv().await.unwrap(), None);
+        assert_eq!(first.try_recv_blocking().unwrap(), Some(4));
+        assert!(first.recv_avaliable_blocking().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn ping_pong() {
+        let (first, second) = duplex::<u8>();
+        let tokio_handle = tokio::spawn(async move {
+            while let Ok(ping) = second.recv().await {
+                second.send(ping + 1).await.unwrap();
+            }
+        });
+
+        let mut value = 0;
+        for _ in 0..5 {
+            first.send(value).await.unwrap();
+            value = first.recv().await.unwrap() + 1;
+        }
+        assert_eq!(value, 10);
+
+        // Closing lets the other task's recv loop end
+        first.close().unwrap();
+        tokio_handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn close() {
+        let (first, second) = duplex::<u8>();
+        first.send_batch_blocking(vec![1, 2]).unwrap();
+        first.close().unwrap();
+        assert!(second.is_closed().unwrap());
+
+        // Both directions refuse new data
+        assert!(matches!(first.send(3).await, Err(TransportError::Closed)));
+        assert!(matches!(
+            second.send_blocking(3),
+            Err(TransportError::Closed)
+        ));
+
+        // Data sent before closing is drained before reporting closed
+        assert_eq!(second.recv().await.unwrap(), 1);
+        assert_eq!(second.recv_avaliable_blocking().unwrap(), vec![2]);
+        assert!(matches!(second.recv().await, Err(TransportError::Closed)));
+        assert!(matches!(
+            second.try_recv_blocking(),
+            Err(TransportError::Closed)
+        ));
+        assert!(matches!(
+            first.recv_avaliable().await,
+            Err(TransportError::Closed)
+        ));
+
+        // Dropping an end wakes a blocked receiver on the other end
+        let (first, second) = duplex::<u8>();
+        let handle = std::thread::spawn(move || second.recv_blocking());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        drop(first);
+        assert!(matches!(
+            handle.join().unwrap(),
+            Err(TransportError::Closed)
+        ));
+    }
+
+    #[cfg(feature = "task")]
+    #[tokio::test]
+    async fn link_consumer() {
+        let (first, second) = duplex::<u8>();
+        let link = crate::Link::new(crate::Queue::<u8>::new().into(), first.into());
+        link.send_batch(vec![1, 2]).await.unwrap();
+        assert_eq!(second.recv().await.unwrap(), 1);
+        assert_eq!(second.recv().await.unwrap(), 2);
+
+        // Data sent back through the opposite end is received from the `Link`
+        second.send(3).await.unwrap();
+        assert_eq!(link.recv().await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn threaded() {
+        let (first, second) = duplex::<u8>();
+        let handle = std::thread::spawn(move || {
+            assert_eq!(second.recv_blocking().unwrap(), 42);
+            second.send_blocking(43).unwrap();
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        first.send_blocking(42).unwrap();
+        handle.join().unwrap();
+        assert_eq!(first.recv().await.unwrap(), 43);
+    }
+}
@@ -1,9 +1,14 @@
 use crate::{
-    AsTaskState, ExtendedTaskState, Task, Transport, TransportError, TransportItemRequirements,
+    AsTaskState, Budget, ExtendedTaskState, Task, Transport, TransportError,
+    TransportItemRequirements,
 };
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::runtime::Handle;
 
-type LinkTask<T> = Arc<
+pub(crate) type LinkTask<T> = Arc<
     Task<
         (),
         TransportError,
@@ -11,11 +16,20 @@ type LinkTask<T> = Arc<
     >,
 >;
 
+/// Number of items the `Task` of a `Link` has received from the producer and forwarded to the consumer
+#[derive(Debug, Default)]
+struct LinkProgress {
+    received: AtomicUsize,
+    forwarded: AtomicUsize,
+}
+
 pub struct Link<T: TransportItemRequirements> {
     producer: Arc<dyn Transport<T>>,
     consumer: Arc<dyn Transport<T>>,
     #[allow(unused)]
     link_task: LinkTask<T>,
+    progress: Arc<LinkProgress>,
+    budget: usize,
 }
 
 impl<T: TransportItemRequirements> From<Link<T>> for Arc<dyn Transport<T>> {
@@ -25,6 +39,7 @@ impl<T: TransportItemRequirements> From<Link<T>> for Arc<dyn Transport<T>> {
 }
 
 impl<T: TransportItemRequirements> Link<T> {
+    /// Creates a new `Link` driven by `link_task`, which doesn't track the `forwarded()` and `in_flight()` progress
     pub fn with_task(
         producer: Arc<dyn Transport<T>>,
         consumer: Arc<dyn Transport<T>>,
@@ -34,24 +49,129 @@ impl<T: TransportItemRequirements> Link<T> {
             producer,
             consumer,
             link_task,
+            progress: Arc::new(LinkProgress::default()),
+            budget: Budget::DEFAULT_LIMIT,
         }
     }
 
     /// Creates a new `Link` with a `Task` handling the connection from the `producer` to the `consumer`
     pub fn new(producer: Arc<dyn Transport<T>>, consumer: Arc<dyn Transport<T>>) -> Self {
+        Self::new_on(Handle::current(), producer, consumer)
+    }
+
+    /// Creates a new `Link` with a `Task` spawned on the `runtime` handling the connection from the `producer` to the `consumer`
+    pub fn new_on(
+        runtime: Handle,
+        producer: Arc<dyn Transport<T>>,
+        consumer: Arc<dyn Transport<T>>,
+    ) -> Self {
+        Self::with_budget_on(runtime, producer, consumer, Budget::DEFAULT_LIMIT)
+    }
+
+    /// Creates a new `Link` whose `Task` yields to the runtime after every `budget` forwarded items, where a `budget` of 0 never yields
+    pub fn with_budget(
+        producer: Arc<dyn Transport<T>>,
+        consumer: Arc<dyn Transport<T>>,
+        budget: usize,
+    ) -> Self {
+        Self::with_budget_on(Handle::current(), producer, consumer, budget)
+    }
+
+    /// Creates a new `Link` with a `Task` spawned on the `runtime`, yielding to it after every `budget` forwarded items
+    pub fn with_budget_on(
+        runtime: Handle,
+        producer: Arc<dyn Transport<T>>,
+        consumer: Arc<dyn Transport<T>>,
+        budget: usize,
+    ) -> Self {
+        let progress = Arc::new(LinkProgress::default());
         Self {
             producer: producer.clone(),
             consumer: consumer.clone(),
-            link_task: Arc::new(Task::infinite(
-                |_, state| {
+            link_task: Self::forwarding_task(runtime, producer, consumer, progress.clone(), budget),
+            progress,
+            budget,
+        }
+    }
+
+    /// Creates the infinite `Task` of `Link::new` on the `runtime`, counting its progress into `progress`
+    fn forwarding_task(
+        runtime: Handle,
+        producer: Arc<dyn Transport<T>>,
+        consumer: Arc<dyn Transport<T>>,
+        progress: Arc<LinkProgress>,
+        budget: usize,
+    ) -> LinkTask<T> {
+        Arc::new(Task::infinite_on(
+            runtime,
+            move |_, state| {
+                let state = state.clone();
+                let progress = progress.clone();
+                async move {
+                    let (producer, consumer) = state.read().await.inner_clone();
+                    let mut budget = Budget::new(budget);
+                    // This tight inner loop ignores errors and never ends, meaning the above clones only happen on the first iteration
+                    // This means any `Task` a `Link` starts will only stop after `task.abort()`
+                    loop {
+                        if let Ok(data) = producer.recv().await {
+                            progress.received.fetch_add(1, Ordering::Relaxed);
+                            let _ = consumer.send(data).await;
+                            progress.forwarded.fetch_add(1, Ordering::Relaxed);
+                            // Yield now and then, as a producer that always has data never lets the loop yield otherwise
+                            budget.tick().await;
+                        }
+                    }
+                }
+            },
+            (producer, consumer).as_task_state(),
+        ))
+    }
+
+    /// Creates a new `Link` with a `Task` handling the connection from the `producer` to the `consumer`, which completes after forwarding the first data matching `is_last`
+    pub fn until(
+        producer: Arc<dyn Transport<T>>,
+        consumer: Arc<dyn Transport<T>>,
+        is_last: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self::until_on(Handle::current(), producer, consumer, is_last)
+    }
+
+    /// Creates a new `Link` with a `Task` spawned on the `runtime`, which completes after forwarding the first data matching `is_last`
+    pub fn until_on(
+        runtime: Handle,
+        producer: Arc<dyn Transport<T>>,
+        consumer: Arc<dyn Transport<T>>,
+        is_last: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let is_last = Arc::new(is_last);
+        let progress = Arc::new(LinkProgress::default());
+        let task_progress = progress.clone();
+        Self {
+            producer: producer.clone(),
+            consumer: consumer.clone(),
+            progress,
+            budget: Budget::DEFAULT_LIMIT,
+            link_task: Arc::new(Task::fixed_on(
+                runtime,
+                1,
+                move |_, state| {
                     let state = state.clone();
+                    let is_last = is_last.clone();
+                    let progress = task_progress.clone();
                     async move {
                         let (producer, consumer) = state.read().await.inner_clone();
-                        // This tight inner loop ignores errors and never ends, meaning the above clones only happen on the first iteration
-                        // This means any `Task` a `Link` starts will only stop after `task.abort()`
+                        let mut budget = Budget::default();
+                        // Only runs once, with the tight inner loop ending the `Task` after the last data is forwarded
                         loop {
                             if let Ok(data) = producer.recv().await {
+                                progress.received.fetch_add(1, Ordering::Relaxed);
+                                let last = is_last(&data);
                                 let _ = consumer.send(data).await;
+                                progress.forwarded.fetch_add(1, Ordering::Relaxed);
+                                if last {
+                                    return Ok(());
+                                }
+                                budget.tick().await;
                             }
                         }
                     }
@@ -75,6 +195,74 @@ impl<T: TransportItemRequirements> Link<T> {
     pub fn link_task(&self) -> &LinkTask<T> {
         &self.link_task
     }
+
+    /// Returns the number of forwarded items after which the `Task` yields to the runtime, where 0 never yields
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Returns the number of items the `Task` has forwarded to the consumer, which only ever increases
+    pub fn forwarded(&self) -> usize {
+        self.progress.forwarded.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of items the `Task` has received from the producer but not yet forwarded to the consumer
+    pub fn in_flight(&self) -> usize {
+        self.progress
+            .received
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.forwarded())
+    }
+
+    /// Returns the number of items queued in the producer waiting for the `Task`, or `None` if the producer isn't a `Queue` so its backlog is unknown
+    pub fn backlog(&self) -> Option<usize> {
+        self.producer
+            .as_ref()
+            .as_any()
+            .downcast_ref::<crate::Queue<T>>()
+            .and_then(|queue| queue.len().ok())
+    }
+
+    /// Stops the `Task` immediately, even while its `LinkTask` is shared
+    pub fn abort(&self) {
+        if let Some(handle) = self.link_task.abort_handle() {
+            handle.abort();
+        }
+    }
+
+    /// Aborts the `Task`, replacing it with one forwarding from the producer to `consumer` on the same runtime and budget, like `Link::with_budget`.
+    /// Any in-flight item of the aborted `Task` is dropped, while `forwarded()` keeps counting from where it was
+    pub fn restart(&mut self, consumer: Arc<dyn Transport<T>>) {
+        self.abort();
+        self.progress
+            .received
+            .store(self.forwarded(), Ordering::Relaxed);
+        self.link_task = Self::forwarding_task(
+            self.link_task.runtime().clone(),
+            self.producer.clone(),
+            consumer.clone(),
+            self.progress.clone(),
+            self.budget,
+        );
+        self.consumer = consumer;
+    }
+}
+
+#[cfg(feature = "command")]
+impl Link<crate::Command> {
+    /// Creates a new `Link`, which completes its `Task` after forwarding a `Command::Stop` when `stop_on_stop` is true
+    pub fn with_stop_on_stop(
+        producer: Arc<dyn Transport<crate::Command>>,
+        consumer: Arc<dyn Transport<crate::Command>>,
+        stop_on_stop: bool,
+    ) -> Self {
+        match stop_on_stop {
+            true => Self::until(producer, consumer, |command| {
+                matches!(command, crate::Command::Stop)
+            }),
+            false => Self::new(producer, consumer),
+        }
+    }
 }
 
 /// Impl Debug for link manually as `Fn()` doesn't support `Debug`
@@ -179,7 +367,7 @@ impl<T: TransportItemRequirements> Transport<T> for Link<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Link, Queue, Transport, TransportItemRequirements};
+    use crate::{Budget, Link, Queue, Transport, TransportItemRequirements};
     use std::sync::Arc;
 
     fn make_link<T: TransportItemRequirements>(
@@ -263,4 +451,89 @@ mod tests {
 
         handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn restart() {
+        let consumer: Arc<dyn Transport<u8>> = Queue::new().into();
+        let mut link = Link::new(Queue::new().into(), consumer.clone());
+        link.send_batch(vec![1, 2]).await.unwrap();
+        assert_eq!(consumer.recv().await.unwrap(), 1);
+        assert_eq!(consumer.recv().await.unwrap(), 2);
+        assert_eq!(link.forwarded(), 2);
+        assert_eq!(link.in_flight(), 0);
+
+        // The restarted `Task` forwards to the new consumer, continuing the progress count
+        let restarted: Arc<dyn Transport<u8>> = Queue::new().into();
+        link.restart(restarted.clone());
+        link.send(3).await.unwrap();
+        assert_eq!(restarted.recv().await.unwrap(), 3);
+        assert_eq!(link.forwarded(), 3);
+        assert!(consumer.recv_avaliable().await.unwrap().is_empty());
+
+        // Once aborted, nothing is forwarded
+        link.abort();
+        link.link_task().wait_stopped().await;
+        link.send(4).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(restarted.recv_avaliable().await.unwrap().is_empty());
+        assert_eq!(link.producer().recv().await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn budget() {
+        // Returns the most items a saturating `Link` forwards between polls of a sibling task on the single threaded test runtime
+        async fn max_gap(budget: usize) -> usize {
+            let producer: Arc<dyn Transport<u32>> = Queue::new().into();
+            producer.send_batch((0..10_000).collect()).await.unwrap();
+            let link = Arc::new(Link::with_budget(producer, Queue::new().into(), budget));
+            assert_eq!(link.budget(), budget);
+            let sibling_link = link.clone();
+            tokio::spawn(async move {
+                let (mut last, mut gap) = (0, 0);
+                while last < 10_000 {
+                    let forwarded = sibling_link.forwarded();
+                    gap = gap.max(forwarded - last);
+                    last = forwarded;
+                    tokio::task::yield_now().await;
+                }
+                gap
+            })
+            .await
+            .unwrap()
+        }
+
+        // With a budget the sibling keeps being polled, within two budgets as a yielded `Link` can be polled again first.
+        // Without a budget the sibling waits for the whole backlog
+        assert!(max_gap(100).await <= 2 * 100);
+        assert!(max_gap(Budget::DEFAULT_LIMIT).await <= 2 * Budget::DEFAULT_LIMIT);
+        assert_eq!(max_gap(0).await, 10_000);
+    }
+
+    #[test]
+    fn new_on() {
+        // The `Link` runs on the passed runtime without an ambient one
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap();
+        let consumer: Arc<dyn Transport<u8>> = Queue::new().into();
+        let link = Link::new_on(
+            runtime.handle().clone(),
+            Queue::new().into(),
+            consumer.clone(),
+        );
+        link.send_blocking(42).unwrap();
+        assert_eq!(consumer.recv_blocking().unwrap(), 42);
+
+        let link = Link::until_on(
+            runtime.handle().clone(),
+            link.into(),
+            consumer.clone(),
+            |i| *i == 0,
+        );
+        link.send_batch_blocking(vec![1, 0]).unwrap();
+        assert_eq!(consumer.recv_blocking().unwrap(), 1);
+        assert_eq!(consumer.recv_blocking().unwrap(), 0);
+    }
 }
@@ -3,12 +3,19 @@ use tokio::sync::Notify;
 use crate::{SliceDebug, Transport, TransportError, TransportItemRequirements};
 use std::sync::{Arc, Condvar, Mutex, MutexGuard};
 
+/// Sends data to every inner transport in parallel. Concurrent sends aren't sequenced, so inner transports can observe them in different orders
 pub struct List<T> {
     transports: Mutex<Vec<Arc<dyn Transport<T>>>>,
     notifier: Notify,
     condvar: Condvar,
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> List<T> {
     pub fn new() -> Self {
         Self {
@@ -19,6 +26,7 @@ impl<T> List<T> {
     }
 
     /// Returns a mutex guard for the inner Vec<Arc<dyn Transport<T>>>
+    #[allow(clippy::type_complexity)]
     pub fn as_mut(&'_ self) -> Result<MutexGuard<'_, Vec<Arc<dyn Transport<T>>>>, TransportError> {
         self.transports.lock().map_err(|e| e.into())
     }
@@ -29,8 +37,13 @@ impl<T> List<T> {
     }
 
     /// Extends the inner Vec<Arc<dyn Transport<T>>> with the provided transports
-    pub fn extend(&self, tansports: impl AsRef<[Arc<dyn Transport<T>>]>) -> Result<(), TransportError> {
-        self.transports.lock()?.extend_from_slice(tansports.as_ref());
+    pub fn extend(
+        &self,
+        tansports: impl AsRef<[Arc<dyn Transport<T>>]>,
+    ) -> Result<(), TransportError> {
+        self.transports
+            .lock()?
+            .extend_from_slice(tansports.as_ref());
         Ok(())
     }
 
@@ -38,15 +51,16 @@ impl<T> List<T> {
         Ok(self.transports.lock()?.len())
     }
 
+    pub fn is_empty(&self) -> Result<bool, TransportError> {
+        Ok(self.transports.lock()?.is_empty())
+    }
+
     /// Provides access to the inner Vec<Arc<dyn Transport<T>>> via a closure
     pub fn with<F, R>(&self, f: F) -> Result<R, TransportError>
     where
         F: FnOnce(&mut Vec<Arc<dyn Transport<T>>>) -> R,
     {
-        let mut guard = self
-            .transports
-            .lock()
-            .map_err(|e| TransportError::from(e))?;
+        let mut guard = self.transports.lock().map_err(TransportError::from)?;
         Ok(f(&mut *guard))
     }
 }
@@ -119,7 +133,7 @@ impl<T> std::fmt::Debug for List<T> {
                 .finish(),
             Err(e) => f
                 .debug_struct("List")
-                .field("transports", &format!("<LockPoisoned>: {}", e.to_string()))
+                .field("transports", &format!("<LockPoisoned>: {}", e))
                 .finish(),
         }
     }
@@ -225,8 +239,7 @@ impl<T: TransportItemRequirements> Transport<T> for List<T> {
         >,
     > {
         Box::pin(async move {
-            let transports =
-                self.with(|transports| transports.iter().cloned().collect::<Vec<_>>())?;
+            let transports = self.with(|transports| transports.to_vec())?;
 
             let mut err = vec![];
             for transport in transports.iter() {
@@ -262,8 +275,7 @@ impl<T: TransportItemRequirements> Transport<T> for List<T> {
         >,
     > {
         Box::pin(async move {
-            let transports =
-                self.with(|transports| transports.iter().cloned().collect::<Vec<_>>())?;
+            let transports = self.with(|transports| transports.to_vec())?;
 
             let mut err = vec![];
 
@@ -298,8 +310,7 @@ impl<T: TransportItemRequirements> Transport<T> for List<T> {
         >,
     > {
         Box::pin(async {
-            let transports =
-                self.with(|transports| transports.iter().cloned().collect::<Vec<_>>())?;
+            let transports = self.with(|transports| transports.to_vec())?;
             loop {
                 for transport in transports.iter() {
                     if let Ok(Some(data)) = transport.try_recv().await {
@@ -323,8 +334,7 @@ impl<T: TransportItemRequirements> Transport<T> for List<T> {
         >,
     > {
         Box::pin(async {
-            let transports =
-                self.with(|transports| transports.iter().cloned().collect::<Vec<_>>())?;
+            let transports = self.with(|transports| transports.to_vec())?;
 
             let mut data_vec = Vec::new();
 
@@ -348,8 +358,7 @@ impl<T: TransportItemRequirements> Transport<T> for List<T> {
         >,
     > {
         Box::pin(async {
-            let transports =
-                self.with(|transports| transports.iter().cloned().collect::<Vec<_>>())?;
+            let transports = self.with(|transports| transports.to_vec())?;
 
             for transport in transports.iter() {
                 if let Ok(Some(data)) = transport.try_recv().await {
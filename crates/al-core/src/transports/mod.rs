@@ -1,10 +1,26 @@
+pub mod ack;
 #[cfg(feature = "task")]
 pub mod buffered;
+#[cfg(all(feature = "command", feature = "serde"))]
+pub mod capture;
+#[cfg(feature = "task")]
+pub mod coalesce;
+pub mod dead_letter;
+pub mod delay_queue;
+pub mod duplex;
 #[cfg(feature = "task")]
 pub mod link;
 pub mod list;
+pub mod oneshot;
 pub mod publisher;
 pub mod queue;
+pub mod sharded_queue;
+#[cfg(all(feature = "task", feature = "command"))]
+pub mod shutdown;
 #[cfg(feature = "task")]
 pub mod splice;
+#[cfg(all(feature = "task", feature = "serde"))]
+pub mod topology;
 pub mod transform;
+#[cfg(all(feature = "task", feature = "command", feature = "event"))]
+pub mod watchdog;
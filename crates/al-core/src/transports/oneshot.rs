@@ -0,0 +1,303 @@
+use crate::{Transport, TransportError, TransportItemRequirements};
+use std::sync::{Condvar, Mutex};
+
+/// Current value of a `OneShot`, which only moves forward from `Empty` to `Full` to `Taken`
+#[derive(Debug)]
+enum OneShotState<T> {
+    Empty,
+    Full(T),
+    Taken,
+}
+
+impl<T> OneShotState<T> {
+    /// Takes the value if it has arrived, returning `None` while empty and `TransportError::Closed` once taken
+    fn take(&mut self) -> Result<Option<T>, TransportError> {
+        match self {
+            OneShotState::Empty => Ok(None),
+            OneShotState::Taken => Err(TransportError::Closed),
+            OneShotState::Full(_) => match std::mem::replace(self, OneShotState::Taken) {
+                OneShotState::Full(data) => Ok(Some(data)),
+                _ => Err(TransportError::NoData),
+            },
+        }
+    }
+}
+
+/// OneShot transport carrying a single value, closing after it is sent and received
+pub struct OneShot<T> {
+    state: Mutex<OneShotState<T>>,
+    notifier: tokio::sync::Notify,
+    condvar: Condvar,
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for OneShot<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.state.lock() {
+            Ok(state) => f.debug_struct("OneShot").field("state", &*state).finish(),
+            Err(e) => f
+                .debug_struct("OneShot")
+                .field("state", &format!("<LockPoisoned>: {}", e))
+                .finish(),
+        }
+    }
+}
+
+impl<T: TransportItemRequirements> From<OneShot<T>> for std::sync::Arc<dyn Transport<T>> {
+    fn from(oneshot: OneShot<T>) -> Self {
+        std::sync::Arc::new(oneshot)
+    }
+}
+
+impl<T> Default for OneShot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OneShot<T> {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(OneShotState::Empty),
+            notifier: tokio::sync::Notify::new(),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Returns true once a value has been sent, even if it was already received
+    pub fn is_completed(&self) -> Result<bool, TransportError> {
+        Ok(!matches!(*self.state.lock()?, OneShotState::Empty))
+    }
+
+    /// Returns true once the sent value has been received
+    pub fn is_closed(&self) -> Result<bool, TransportError> {
+        Ok(matches!(*self.state.lock()?, OneShotState::Taken))
+    }
+}
+
+/// Impl transport for oneshot, where only the first send succeeds and every recv after the value is received returns `TransportError::Closed`
+impl<T: TransportItemRequirements> Transport<T> for OneShot<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        let mut state = self.state.lock()?;
+        match *state {
+            OneShotState::Empty => {
+                *state = OneShotState::Full(data);
+                self.condvar.notify_all();
+                self.notifier.notify_waiters();
+                Ok(())
+            }
+            _ => Err(TransportError::Closed),
+        }
+    }
+
+    fn send_batch_blocking(&self, mut data: Vec<T>) -> Result<(), TransportError> {
+        match data.len() {
+            1 => self.send_blocking(data.remove(0)),
+            len => Err(TransportError::UnSupported(format!(
+                "OneShot transport only supports sending a single value, got a batch of {len}"
+            ))),
+        }
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        let mut state = self.state.lock()?;
+
+        loop {
+            if let Some(data) = state.take()? {
+                return Ok(data);
+            }
+            state = self.condvar.wait(state)?;
+        }
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        Ok(self.state.lock()?.take()?.into_iter().collect())
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        self.state.lock()?.take()
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_batch_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async {
+            loop {
+                // Register for notifications before checking so a send between the check and the await isn't missed
+                let notified = self.notifier.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                match self.state.lock() {
+                    Ok(mut state) => {
+                        if let Some(data) = state.take()? {
+                            return Ok(data);
+                        }
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+
+                notified.await;
+            }
+        })
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.recv_avaliable_blocking();
+        Box::pin(async { result })
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.try_recv_blocking();
+        Box::pin(async { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{OneShot, Transport, TransportError};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn debug() {
+        let oneshot = OneShot::<u8>::new();
+        assert_eq!(format!("{:?}", oneshot), "OneShot { state: Empty }");
+        oneshot.send(1).await.unwrap();
+        assert_eq!(format!("{:?}", oneshot), "OneShot { state: Full(1) }");
+        oneshot.recv().await.unwrap();
+        assert_eq!(format!("{:?}", oneshot), "OneShot { state: Taken }");
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        let oneshot = OneShot::<u8>::new();
+        // Try recv doesn't close the `OneShot` before the value arrives
+        assert_eq!(oneshot.try_recv().await.unwrap(), None);
+        assert!(oneshot.recv_avaliable().await.unwrap().is_empty());
+        assert!(!oneshot.is_completed().unwrap());
+
+        oneshot.send(1).await.unwrap();
+        assert!(oneshot.is_completed().unwrap());
+        assert!(!oneshot.is_closed().unwrap());
+        assert_eq!(oneshot.recv().await.unwrap(), 1);
+        assert!(oneshot.is_closed().unwrap());
+
+        // Every recv after the value is received reports closed
+        assert!(matches!(oneshot.recv().await, Err(TransportError::Closed)));
+        assert!(matches!(
+            oneshot.try_recv().await,
+            Err(TransportError::Closed)
+        ));
+        assert!(matches!(
+            oneshot.recv_avaliable_blocking(),
+            Err(TransportError::Closed)
+        ));
+        assert!(matches!(
+            oneshot.recv_blocking(),
+            Err(TransportError::Closed)
+        ));
+
+        let oneshot = OneShot::<u8>::new();
+        oneshot.send_batch_blocking(vec![2]).unwrap();
+        assert_eq!(oneshot.try_recv_blocking().unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn double_send() {
+        let oneshot = OneShot::<u8>::new();
+        oneshot.send_blocking(1).unwrap();
+        assert!(matches!(oneshot.send(2).await, Err(TransportError::Closed)));
+        assert_eq!(oneshot.recv_blocking().unwrap(), 1);
+        assert!(matches!(
+            oneshot.send_blocking(3),
+            Err(TransportError::Closed)
+        ));
+
+        // Batches other than a single value are rejected
+        let oneshot = OneShot::<u8>::new();
+        assert!(matches!(
+            oneshot.send_batch(vec![1, 2]).await,
+            Err(TransportError::UnSupported(_))
+        ));
+        assert!(!oneshot.is_completed().unwrap());
+    }
+
+    #[tokio::test]
+    async fn threaded() {
+        let oneshot = Arc::new(OneShot::<u8>::new());
+        let oneshot_clone = oneshot.clone();
+        let handle = std::thread::spawn(move || {
+            assert_eq!(oneshot_clone.recv_blocking().unwrap(), 42);
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        oneshot.send_blocking(42).unwrap();
+        handle.join().unwrap();
+
+        let oneshot = Arc::new(OneShot::<u8>::new());
+        let oneshot_clone = oneshot.clone();
+        let tokio_handle = tokio::spawn(async move {
+            assert_eq!(oneshot_clone.recv().await.unwrap(), 42);
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        oneshot.send(42).await.unwrap();
+        tokio_handle.await.unwrap();
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{SliceDebug, Transport, TransportError, TransportItemRequirements};
+use crate::{
+    AckTransport, DeliveryReceipt, SliceDebug, Transport, TransportError, TransportItemRequirements,
+};
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
@@ -53,11 +55,16 @@ impl<T: TransportItemRequirements, R: Transport<T>, S: AsRef<str>> From<(Arc<R>,
 /* ********************
   Publisher
 ******************** */
+/// Sends data to every subscriber. By default concurrent sends fan out independently, so subscribers can observe them in different orders.
+/// A `Publisher::new_ordered()` holds a send section over the full fan-out instead, so every subscriber observes the same total order
 pub struct Publisher<T> {
     subscribers: Mutex<Vec<Arc<dyn Transport<T>>>>,
     subscriber_channels: Mutex<HashMap<String, usize>>,
     filters: Mutex<Vec<Arc<dyn FilterFn<T>>>>,
+    #[allow(clippy::type_complexity)]
     channels: Mutex<Vec<Arc<Mutex<Vec<Arc<dyn Transport<T>>>>>>>,
+    acked: Mutex<Vec<Arc<AckTransport<T>>>>,
+    sequencer: Option<tokio::sync::Mutex<()>>,
 }
 
 impl<T> std::fmt::Debug for Publisher<T> {
@@ -70,7 +77,7 @@ impl<T> std::fmt::Debug for Publisher<T> {
                 .finish(),
             Err(e) => f
                 .debug_struct("Publisher")
-                .field("subscribers", &format!("<LockPoisoned>: {}", e.to_string()))
+                .field("subscribers", &format!("<LockPoisoned>: {}", e))
                 .finish(),
         }
     }
@@ -94,6 +101,12 @@ impl<T: TransportItemRequirements> From<crate::List<T>> for Publisher<T> {
     }
 }
 
+impl<T: TransportItemRequirements> Default for Publisher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: TransportItemRequirements> Publisher<T> {
     pub fn new() -> Self {
         Self {
@@ -101,7 +114,87 @@ impl<T: TransportItemRequirements> Publisher<T> {
             subscriber_channels: Mutex::new(HashMap::new()),
             filters: Mutex::new(Vec::new()),
             channels: Mutex::new(Vec::new()),
+            acked: Mutex::new(Vec::new()),
+            sequencer: None,
+        }
+    }
+
+    /// Creates a `Publisher` where each send holds a send section over the full fan-out, so every subscriber observes the same total order.
+    /// Concurrent sends wait for each other instead of interleaving, and a subscriber sending back into the `Publisher` while receiving will deadlock
+    /// A blocking send on a current-thread runtime returns an error instead of waiting while an async send holds the section
+    pub fn new_ordered() -> Self {
+        Self {
+            sequencer: Some(tokio::sync::Mutex::new(())),
+            ..Self::new()
+        }
+    }
+
+    /// Returns true if the `Publisher` was created with `new_ordered()`
+    pub fn is_ordered(&self) -> bool {
+        self.sequencer.is_some()
+    }
+
+    /// Waits for the send section of an ordered `Publisher`, returning `None` for the default mode
+    async fn sequence(&self) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        match &self.sequencer {
+            Some(sequencer) => Some(sequencer.lock().await),
+            None => None,
+        }
+    }
+
+    /// Waits for the send section of an ordered `Publisher` from a blocking send, returning `None` for the default mode.
+    /// Within a multi-thread runtime the wait is moved off the worker with `block_in_place()`. A current-thread runtime can't progress the async send holding the section while its thread is blocked, so a held section is an error there
+    fn sequence_blocking(&self) -> Result<Option<tokio::sync::MutexGuard<'_, ()>>, TransportError> {
+        let Some(sequencer) = &self.sequencer else {
+            return Ok(None);
+        };
+        if let Ok(guard) = sequencer.try_lock() {
+            return Ok(Some(guard));
         }
+        match tokio::runtime::Handle::try_current() {
+            Err(_) => Ok(Some(sequencer.blocking_lock())),
+            Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::CurrentThread => {
+                Err(TransportError::Transport(
+                    "Ordered Publisher send section is held, and a blocking send can't wait for it on a current-thread runtime".to_string(),
+                ))
+            }
+            Ok(_) => Ok(Some(tokio::task::block_in_place(|| {
+                sequencer.blocking_lock()
+            }))),
+        }
+    }
+
+    /// Subscribes an `AckTransport` to receive all data, with `.send_tracked()` data also reporting its ack outcome in a `DeliveryReceipt`
+    pub fn subscribe_acked(&self, transport: Arc<AckTransport<T>>) -> Result<(), TransportError> {
+        match self.acked.lock() {
+            Ok(mut guard) => {
+                guard.push(transport);
+                Ok(())
+            }
+            Err(e) => Err(TransportError::Transport(format!(
+                "Error acquiring acked subscribers lock: {}",
+                e
+            ))),
+        }
+    }
+
+    /// Sends data to every `AckTransport` subscriber, returning a `DeliveryReceipt` that resolves once each of them acked or dead lettered it. Other subscribers are not sent the data
+    pub fn send_tracked(&self, data: T) -> Result<DeliveryReceipt<T>, TransportError> {
+        let transports = match self.acked.lock() {
+            Ok(guard) => guard.clone(),
+            Err(e) => {
+                return Err(TransportError::Transport(format!(
+                    "Error acquiring acked subscribers lock: {}",
+                    e
+                )))
+            }
+        };
+        let receipt = DeliveryReceipt::new(transports.clone());
+        let _sequenced = self.sequence_blocking()?;
+        for transport in transports.iter() {
+            transport.enqueue_tracked(data.clone(), &receipt)?;
+        }
+        Ok(receipt)
     }
 
     pub fn subscribe(
@@ -114,12 +207,10 @@ impl<T: TransportItemRequirements> Publisher<T> {
                     guard.push(transport);
                     Ok(())
                 }
-                Err(e) => {
-                    return Err(TransportError::Transport(format!(
-                        "Error acquiring subscribers lock: {}",
-                        e.to_string()
-                    )))
-                }
+                Err(e) => Err(TransportError::Transport(format!(
+                    "Error acquiring subscribers lock: {}",
+                    e
+                ))),
             },
             SubscribeFormat::Channel(transport, channel) => {
                 let channel_index = match self.subscriber_channels.lock() {
@@ -132,7 +223,7 @@ impl<T: TransportItemRequirements> Publisher<T> {
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring subscriber_channels lock: {}",
-                            e.to_string()
+                            e
                         )))
                     }
                 };
@@ -143,7 +234,7 @@ impl<T: TransportItemRequirements> Publisher<T> {
                             Err(e) => {
                                 return Err(TransportError::Transport(format!(
                                     "Error acquiring channel transports lock: {}",
-                                    e.to_string()
+                                    e
                                 )))
                             }
                         },
@@ -157,7 +248,7 @@ impl<T: TransportItemRequirements> Publisher<T> {
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring channels lock: {}",
-                            e.to_string()
+                            e
                         )))
                     }
                 };
@@ -184,30 +275,206 @@ impl<T: TransportItemRequirements> Publisher<T> {
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring subscriber_channels lock: {}",
-                            e.to_string()
+                            e
                         )))
                     }
                 },
                 Err(e) => {
                     return Err(TransportError::Transport(format!(
                         "Error acquiring filters lock: {}",
-                        e.to_string()
+                        e
                     )))
                 }
             },
             Err(e) => {
                 return Err(TransportError::Transport(format!(
                     "Error acquiring channels lock: {}",
-                    e.to_string()
+                    e
                 )))
             }
         }
         Ok(index)
     }
+
+    /// Removes the transport from all subscribers and channels, returning false if it wasn't subscribed
+    pub fn unsubscribe(&self, transport: &Arc<dyn Transport<T>>) -> Result<bool, TransportError> {
+        let mut removed = false;
+        let mut remove = |transports: &mut Vec<Arc<dyn Transport<T>>>| {
+            let len = transports.len();
+            transports.retain(|subscriber| !Arc::ptr_eq(subscriber, transport));
+            removed |= transports.len() != len;
+        };
+        match self.subscribers.lock() {
+            Ok(mut guard) => remove(&mut guard),
+            Err(e) => {
+                return Err(TransportError::Transport(format!(
+                    "Error acquiring subscribers lock: {}",
+                    e
+                )))
+            }
+        }
+        match self.channels.lock() {
+            Ok(channels) => {
+                for channel_mutex in channels.iter() {
+                    match channel_mutex.lock() {
+                        Ok(mut channel_transports) => remove(&mut channel_transports),
+                        Err(e) => {
+                            return Err(TransportError::Transport(format!(
+                                "Error acquiring channel transports lock: {}",
+                                e
+                            )))
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                return Err(TransportError::Transport(format!(
+                    "Error acquiring channels lock: {}",
+                    e
+                )))
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Sends `data` to the subscribers and waits for the first data published back through the `Publisher` matching `is_reply`.
+    /// The reply is received by a `OneShot` that is only subscribed for the duration of the request, so `is_reply` should not match the request itself
+    pub async fn request(&self, data: T, is_reply: impl FilterFn<T>) -> Result<T, TransportError> {
+        let reply = Arc::new(crate::OneShot::new());
+        let subscriber: Arc<dyn Transport<T>> = Arc::new(ReplySubscriber {
+            reply: reply.clone(),
+            is_reply: Box::new(is_reply),
+        });
+        self.subscribe(subscriber.clone())?;
+
+        let result = match self.send(data).await {
+            Ok(_) => reply.recv().await,
+            Err(e) => Err(e),
+        };
+        self.unsubscribe(&subscriber)?;
+        result
+    }
+}
+
+/// Temporary subscriber used by `Publisher::request()`, forwarding only the first reply to its `OneShot` and ignoring all other data
+struct ReplySubscriber<T> {
+    reply: Arc<crate::OneShot<T>>,
+    is_reply: Box<dyn FilterFn<T>>,
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for ReplySubscriber<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplySubscriber")
+            .field("reply", &self.reply)
+            .field("is_reply", &"FilterFn")
+            .finish()
+    }
+}
+
+impl<T: TransportItemRequirements> Transport<T> for ReplySubscriber<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        if !self.is_reply.filter(&data) {
+            return Ok(());
+        }
+        // Ignore any replies after the first
+        match self.reply.send_blocking(data) {
+            Err(TransportError::Closed) => Ok(()),
+            result => result,
+        }
+    }
+
+    fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        for item in data {
+            self.send_blocking(item)?;
+        }
+        Ok(())
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        self.reply.recv_blocking()
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        self.reply.recv_avaliable_blocking()
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        self.reply.try_recv_blocking()
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_batch_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.reply.recv()
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.reply.recv_avaliable()
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        self.reply.try_recv()
+    }
 }
 
 impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
     fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        let _sequenced = self.sequence_blocking()?;
         let mut err = vec![];
         // Send to all subscribers
         if let Ok(guard) = self.subscribers.lock() {
@@ -217,6 +484,14 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                 }
             }
         }
+        // Send to acked subscribers
+        if let Ok(guard) = self.acked.lock() {
+            for transport in guard.iter() {
+                if let Err(e) = transport.send_blocking(data.clone()) {
+                    err.push(e);
+                }
+            }
+        }
         // Send to channel subscribers
         if let Ok(channels) = self.channels.lock() {
             if let Ok(filters) = self.filters.lock() {
@@ -244,6 +519,7 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
     }
 
     fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        let _sequenced = self.sequence_blocking()?;
         let mut err = vec![];
         // Send to all subscribers
         if let Ok(guard) = self.subscribers.lock() {
@@ -253,6 +529,14 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                 }
             }
         }
+        // Send to acked subscribers
+        if let Ok(guard) = self.acked.lock() {
+            for transport in guard.iter() {
+                if let Err(e) = transport.send_batch_blocking(data.clone()) {
+                    err.push(e);
+                }
+            }
+        }
         // Send to channel subscribers
         if let Ok(channels) = self.channels.lock() {
             if let Ok(filters) = self.filters.lock() {
@@ -314,13 +598,14 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
         >,
     > {
         Box::pin(async move {
+            let _sequenced = self.sequence().await;
             let transports = {
                 match self.subscribers.lock() {
                     Ok(guard) => guard.clone(),
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring subscribers lock: {}",
-                            e.to_string()
+                            e
                         )))
                     }
                 }
@@ -331,7 +616,18 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring channels lock: {}",
-                            e.to_string()
+                            e
+                        )))
+                    }
+                }
+            };
+            let acked = {
+                match self.acked.lock() {
+                    Ok(guard) => guard.clone(),
+                    Err(e) => {
+                        return Err(TransportError::Transport(format!(
+                            "Error acquiring acked subscribers lock: {}",
+                            e
                         )))
                     }
                 }
@@ -343,7 +639,7 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring filters lock: {}",
-                            e.to_string()
+                            e
                         )))
                     }
                 }
@@ -356,6 +652,12 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                     err.push(e);
                 }
             }
+            // Send to acked subscribers
+            for transport in acked.iter() {
+                if let Err(e) = transport.send(data.clone()).await {
+                    err.push(e);
+                }
+            }
             // Send to channel subscribers
             for (i, channel_mutex) in channels.iter().enumerate() {
                 if filters[i].filter(&data) {
@@ -365,7 +667,7 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                             Err(e) => {
                                 return Err(TransportError::Transport(format!(
                                     "Error acquiring channel transports lock: {}",
-                                    e.to_string()
+                                    e
                                 )))
                             }
                         }
@@ -400,13 +702,14 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
         >,
     > {
         Box::pin(async move {
+            let _sequenced = self.sequence().await;
             let transports = {
                 match self.subscribers.lock() {
                     Ok(guard) => guard.clone(),
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring subscribers lock: {}",
-                            e.to_string()
+                            e
                         )))
                     }
                 }
@@ -417,7 +720,18 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring channels lock: {}",
-                            e.to_string()
+                            e
+                        )))
+                    }
+                }
+            };
+            let acked = {
+                match self.acked.lock() {
+                    Ok(guard) => guard.clone(),
+                    Err(e) => {
+                        return Err(TransportError::Transport(format!(
+                            "Error acquiring acked subscribers lock: {}",
+                            e
                         )))
                     }
                 }
@@ -429,7 +743,7 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                     Err(e) => {
                         return Err(TransportError::Transport(format!(
                             "Error acquiring filters lock: {}",
-                            e.to_string()
+                            e
                         )))
                     }
                 }
@@ -442,6 +756,12 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                     err.push(e);
                 }
             }
+            // Send to acked subscribers
+            for transport in acked.iter() {
+                if let Err(e) = transport.send_batch(data.clone()).await {
+                    err.push(e);
+                }
+            }
             // Send to channel subscribers
             for (i, channel_mutex) in channels.iter().enumerate() {
                 let data = data
@@ -456,7 +776,7 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
                             Err(e) => {
                                 return Err(TransportError::Transport(format!(
                                     "Error acquiring channel transports lock: {}",
-                                    e.to_string()
+                                    e
                                 )))
                             }
                         }
@@ -522,8 +842,8 @@ impl<T: TransportItemRequirements> Transport<T> for Publisher<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Publisher, Queue, Transport};
-    use std::sync::Arc;
+    use crate::{Publisher, Queue, Transport, TransportError};
+    use std::{sync::Arc, time::Duration};
 
     #[tokio::test]
     async fn debug() {
@@ -667,4 +987,304 @@ mod tests {
             assert_eq!(format!("channel_{}", i), channels[i]);
         }
     }
+
+    #[tokio::test]
+    async fn unsubscribe() {
+        let publisher = Publisher::<u8>::new();
+        let subscriber: Arc<dyn Transport<u8>> = Arc::new(Queue::<u8>::new());
+        publisher
+            .add_channel("even", Arc::new(|i: &u8| i.is_multiple_of(2)))
+            .unwrap();
+        publisher.subscribe(subscriber.clone()).unwrap();
+        publisher.subscribe((subscriber.clone(), "even")).unwrap();
+        publisher.send(2).await.unwrap();
+        assert_eq!(subscriber.recv_avaliable().await.unwrap(), vec![2, 2]);
+
+        assert!(publisher.unsubscribe(&subscriber).unwrap());
+        assert!(!publisher.unsubscribe(&subscriber).unwrap());
+        publisher.send(4).await.unwrap();
+        assert!(subscriber.recv_avaliable().await.unwrap().is_empty());
+    }
+
+    /// Sends `producers` batches of tagged items from concurrent tasks, returning what each of the 3 subscribers received
+    async fn publish_concurrently(publisher: Publisher<u32>, producers: u32) -> Vec<Vec<u32>> {
+        let publisher = Arc::new(publisher);
+        let subscribers = (0..3)
+            .map(|_| {
+                let subscriber = Arc::new(Queue::<u32>::new());
+                publisher.subscribe(subscriber.clone()).unwrap();
+                subscriber
+            })
+            .collect::<Vec<_>>();
+
+        let handles = (0..producers)
+            .map(|producer| {
+                let publisher = publisher.clone();
+                tokio::spawn(async move {
+                    for i in 0..100 {
+                        publisher.send(producer * 1000 + i).await.unwrap();
+                        if i % 10 == 0 {
+                            tokio::task::yield_now().await;
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let mut received = vec![];
+        for subscriber in subscribers {
+            received.push(subscriber.recv_avaliable().await.unwrap());
+        }
+        received
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn ordered() {
+        let publisher = Publisher::<u32>::new_ordered();
+        assert!(publisher.is_ordered());
+        let received = publish_concurrently(publisher, 8).await;
+
+        // Every subscriber observes the identical total order
+        assert_eq!(received[0].len(), 800);
+        assert_eq!(received[0], received[1]);
+        assert_eq!(received[0], received[2]);
+
+        // Blocking and async sends share the same send section
+        let publisher = Arc::new(Publisher::<u32>::new_ordered());
+        let subscribers = (0..3)
+            .map(|_| {
+                let subscriber = Arc::new(Queue::<u32>::new());
+                publisher.subscribe(subscriber.clone()).unwrap();
+                subscriber
+            })
+            .collect::<Vec<_>>();
+        let blocking_publisher = publisher.clone();
+        let handle = std::thread::spawn(move || {
+            for i in 0..100 {
+                blocking_publisher.send_blocking(i).unwrap();
+            }
+        });
+        for i in 100..200 {
+            publisher.send_batch(vec![i]).await.unwrap();
+        }
+        handle.join().unwrap();
+        let first = subscribers[0].recv_avaliable().await.unwrap();
+        assert_eq!(first.len(), 200);
+        assert_eq!(subscribers[1].recv_avaliable().await.unwrap(), first);
+        assert_eq!(subscribers[2].recv_avaliable().await.unwrap(), first);
+
+        // A blocking send on a worker thread waits for an async send holding the section
+        let publisher = Arc::new(Publisher::<u32>::new_ordered());
+        let subscriber = Arc::new(Slow(Queue::new()));
+        publisher.subscribe(subscriber.clone()).unwrap();
+        let async_publisher = publisher.clone();
+        let handle = tokio::spawn(async move { async_publisher.send(1).await.unwrap() });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let blocking_publisher = publisher.clone();
+        tokio::spawn(async move { blocking_publisher.send_blocking(2).unwrap() })
+            .await
+            .unwrap();
+        handle.await.unwrap();
+        assert_eq!(subscriber.recv_avaliable().await.unwrap(), vec![1, 2]);
+    }
+
+    /// Subscriber whose async sends take a while, holding an ordered `Publisher` send section across the await
+    #[derive(Debug)]
+    struct Slow(Queue<u32>);
+
+    impl Transport<u32> for Slow {
+        fn send_blocking(&self, data: u32) -> Result<(), TransportError> {
+            self.0.send_blocking(data)
+        }
+
+        fn send_batch_blocking(&self, data: Vec<u32>) -> Result<(), TransportError> {
+            self.0.send_batch_blocking(data)
+        }
+
+        fn recv_blocking(&self) -> Result<u32, TransportError> {
+            self.0.recv_blocking()
+        }
+
+        fn recv_avaliable_blocking(&self) -> Result<Vec<u32>, TransportError> {
+            self.0.recv_avaliable_blocking()
+        }
+
+        fn try_recv_blocking(&self) -> Result<Option<u32>, TransportError> {
+            self.0.try_recv_blocking()
+        }
+
+        fn send(
+            &self,
+            data: u32,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                self.0.send(data).await
+            })
+        }
+
+        fn send_batch(
+            &self,
+            data: Vec<u32>,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                self.0.send_batch(data).await
+            })
+        }
+
+        fn recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<u32, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            self.0.recv()
+        }
+
+        fn recv_avaliable(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<Vec<u32>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            self.0.recv_avaliable()
+        }
+
+        fn try_recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<Option<u32>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            self.0.try_recv()
+        }
+    }
+
+    #[tokio::test]
+    async fn ordered_current_thread() {
+        let publisher = Arc::new(Publisher::<u32>::new_ordered());
+        let subscriber = Arc::new(Slow(Queue::new()));
+        publisher.subscribe(subscriber.clone()).unwrap();
+
+        // A blocking send on the runtime thread can't wait for an async send holding the section, and errors rather than spinning
+        let async_publisher = publisher.clone();
+        let handle = tokio::spawn(async move { async_publisher.send(1).await.unwrap() });
+        tokio::task::yield_now().await;
+        assert!(matches!(
+            publisher.send_blocking(2),
+            Err(TransportError::Transport(_))
+        ));
+        assert!(publisher.send_batch_blocking(vec![2]).is_err());
+
+        // A blocking send off the runtime waits for the section while the runtime completes the async send
+        let blocking_publisher = publisher.clone();
+        let thread = std::thread::spawn(move || blocking_publisher.send_blocking(3).unwrap());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!thread.is_finished());
+        handle.await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !thread.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+        thread.join().unwrap();
+
+        // Once the section is free, blocking sends go straight through
+        publisher.send_blocking(4).unwrap();
+        assert_eq!(subscriber.recv_avaliable().await.unwrap(), vec![1, 3, 4]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn unordered() {
+        let publisher = Publisher::<u32>::new();
+        assert!(!publisher.is_ordered());
+        let received = publish_concurrently(publisher, 8).await;
+
+        // The default mode still delivers everything, keeping the order of each producer
+        for subscriber in received {
+            assert_eq!(subscriber.len(), 800);
+            for producer in 0..8 {
+                let items = subscriber
+                    .iter()
+                    .filter(|item| **item / 1000 == producer)
+                    .copied()
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    items,
+                    (0..100).map(|i| producer * 1000 + i).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn request() {
+        use crate::Command;
+
+        // A responder task receives `Command::Pulse` requests and publishes `Command::Restart` replies
+        let publisher = Arc::new(Publisher::<Command>::new());
+        let requests = Arc::new(Queue::<Command>::new());
+        publisher
+            .add_channel("requests", Arc::new(|cmd: &Command| *cmd == Command::Pulse))
+            .unwrap();
+        publisher.subscribe((requests.clone(), "requests")).unwrap();
+        let publisher_clone = publisher.clone();
+        let responder = tokio::spawn(async move {
+            for _ in 0..2 {
+                if requests.recv().await.unwrap() == Command::Pulse {
+                    publisher_clone.send(Command::Restart).await.unwrap();
+                }
+            }
+        });
+
+        // Each request resolves to its reply, and the temporary reply subscriber is removed afterwards
+        for _ in 0..2 {
+            let requester = publisher.clone();
+            let reply = tokio::spawn(async move {
+                requester
+                    .request(Command::Pulse, |cmd: &Command| *cmd == Command::Restart)
+                    .await
+            })
+            .await
+            .unwrap();
+            assert_eq!(reply.unwrap(), Command::Restart);
+            assert_eq!(
+                format!("{:?}", publisher),
+                "Publisher { subscribers_count: 0, subscribers: [] }"
+            );
+        }
+        responder.await.unwrap();
+    }
 }
@@ -1,26 +1,66 @@
 use crate::{SliceDebug, Transport, TransportError, TransportItemRequirements};
 use std::{
     collections::VecDeque,
-    sync::{Condvar, Mutex},
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
 };
+use tokio::time::Instant;
+
+/// Queued items, with the enqueue `Instant` of each item at the same index when the `Queue` has a TTL, and whether the `Queue` has been closed
+struct QueueState<T> {
+    items: VecDeque<T>,
+    stamps: VecDeque<Instant>,
+    closed: bool,
+}
+
+impl<T> QueueState<T> {
+    fn check_open(&self) -> Result<(), TransportError> {
+        match self.closed {
+            true => Err(TransportError::Closed),
+            false => Ok(()),
+        }
+    }
+}
 
 /// Queue transport to implement FIFO transport
 pub struct Queue<T> {
-    queue: Mutex<VecDeque<T>>,
+    queue: Mutex<QueueState<T>>,
     notifier: tokio::sync::Notify,
     condvar: Condvar,
+    #[allow(clippy::type_complexity)]
+    priority: Option<Box<dyn Fn(&T) -> bool + Send + Sync>>,
+    ttl: Option<Duration>,
+    expired_sink: Option<Arc<dyn Transport<T>>>,
 }
 
 impl<T: std::fmt::Debug> std::fmt::Debug for Queue<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.queue.lock() {
-            Ok(mut queue) => f
-                .debug_struct("Queue")
-                .field("queue", &SliceDebug::with_len(3, queue.make_contiguous()))
-                .finish(),
+            Ok(mut queue) => match self.ttl {
+                Some(_) => {
+                    let now = Instant::now();
+                    let live = queue
+                        .items
+                        .iter()
+                        .zip(queue.stamps.iter())
+                        .filter(|(_, stamp)| !self.is_expired(**stamp, now))
+                        .map(|(item, _)| item)
+                        .collect::<Vec<_>>();
+                    f.debug_struct("Queue")
+                        .field("queue", &SliceDebug::with_len(3, &live))
+                        .finish()
+                }
+                None => f
+                    .debug_struct("Queue")
+                    .field(
+                        "queue",
+                        &SliceDebug::with_len(3, queue.items.make_contiguous()),
+                    )
+                    .finish(),
+            },
             Err(e) => f
                 .debug_struct("Queue")
-                .field("queue", &format!("<LockPoisoned>: {}", e.to_string()))
+                .field("queue", &format!("<LockPoisoned>: {}", e))
                 .finish(),
         }
     }
@@ -32,25 +72,233 @@ impl<T: TransportItemRequirements> From<Queue<T>> for std::sync::Arc<dyn Transpo
     }
 }
 
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Queue<T> {
     pub fn new() -> Self {
         Self {
-            queue: Mutex::new(VecDeque::new()),
+            queue: Mutex::new(QueueState {
+                items: VecDeque::new(),
+                stamps: VecDeque::new(),
+                closed: false,
+            }),
             notifier: tokio::sync::Notify::new(),
             condvar: Condvar::new(),
+            priority: None,
+            ttl: None,
+            expired_sink: None,
+        }
+    }
+
+    /// Creates a `Queue` where data matching `is_priority` is received before any other queued data.
+    /// Both the prioritized and other data keep FIFO order among themselves
+    pub fn with_priority(is_priority: impl Fn(&T) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            priority: Some(Box::new(is_priority)),
+            ..Self::new()
+        }
+    }
+
+    /// Creates a `Queue` where data queued for longer than `ttl` expires, being discarded instead of received
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            ttl: Some(ttl),
+            ..Self::new()
+        }
+    }
+
+    /// Sends data discarded after its TTL to `expired_sink` rather than dropping it
+    pub fn with_expired_sink(mut self, expired_sink: Arc<dyn Transport<T>>) -> Self {
+        self.expired_sink = Some(expired_sink);
+        self
+    }
+
+    fn is_expired(&self, stamp: Instant, now: Instant) -> bool {
+        self.ttl
+            .is_some_and(|ttl| now.saturating_duration_since(stamp) >= ttl)
+    }
+
+    /// Pushes the data to the back of the queue, or behind the last prioritized data if it is prioritized itself
+    fn push(&self, queue: &mut QueueState<T>, data: T) {
+        let index = match &self.priority {
+            Some(is_priority) if is_priority(&data) => {
+                queue.items.partition_point(|item| is_priority(item))
+            }
+            _ => queue.items.len(),
+        };
+        queue.items.insert(index, data);
+        if self.ttl.is_some() {
+            queue.stamps.insert(index, Instant::now());
+        }
+    }
+
+    fn extend(&self, queue: &mut QueueState<T>, data: Vec<T>) {
+        match (&self.priority, self.ttl) {
+            (None, None) => queue.items.extend(data),
+            _ => data.into_iter().for_each(|item| self.push(queue, item)),
+        }
+    }
+
+    /// Pops the front of the queue, moving any expired data ahead of it into `expired`
+    fn pop(&self, queue: &mut QueueState<T>, expired: &mut Vec<T>) -> Option<T> {
+        if self.ttl.is_some() {
+            let now = Instant::now();
+            while let Some(stamp) = queue.stamps.pop_front() {
+                let item = queue.items.pop_front();
+                match self.is_expired(stamp, now) {
+                    true => expired.extend(item),
+                    false => return item,
+                }
+            }
+        }
+        queue.items.pop_front()
+    }
+
+    /// Removes all expired data from the queue, keeping the order of the remaining data
+    fn take_expired(&self, queue: &mut QueueState<T>) -> Vec<T> {
+        let mut expired = Vec::new();
+        if self.ttl.is_some() {
+            let now = Instant::now();
+            let items = std::mem::take(&mut queue.items);
+            let stamps = std::mem::take(&mut queue.stamps);
+            for (item, stamp) in items.into_iter().zip(stamps) {
+                match self.is_expired(stamp, now) {
+                    true => expired.push(item),
+                    false => {
+                        queue.items.push_back(item);
+                        queue.stamps.push_back(stamp);
+                    }
+                }
+            }
+        }
+        expired
+    }
+
+    /// Drains all unexpired data from the queue, moving any expired data into `expired`
+    fn drain(&self, queue: &mut QueueState<T>, expired: &mut Vec<T>) -> Vec<T> {
+        expired.extend(self.take_expired(queue));
+        queue.stamps.clear();
+        queue.items.drain(..).collect()
+    }
+}
+
+impl<T: TransportItemRequirements> Queue<T> {
+    /// Closes the `Queue`, after which sends fail and receives return `TransportError::Closed` once the queued data is drained.
+    /// Receivers waiting on `recv_at_least` return the data queued so far
+    pub fn close(&self) -> Result<(), TransportError> {
+        self.queue.lock()?.closed = true;
+        self.condvar.notify_all();
+        self.notifier.notify_waiters();
+        Ok(())
+    }
+
+    /// Returns true once the `Queue` has been closed
+    pub fn is_closed(&self) -> Result<bool, TransportError> {
+        Ok(self.queue.lock()?.closed)
+    }
+
+    /// Hands expired data to the `expired_sink` if there is one, otherwise dropping it.
+    /// Called once the lock is released so a sink sending back into this queue can't deadlock
+    fn discard(&self, expired: Vec<T>) {
+        if let (Some(expired_sink), false) = (&self.expired_sink, expired.is_empty()) {
+            let _ = expired_sink.send_batch_blocking(expired);
+        }
+    }
+
+    /// Discards all expired data, returning how much was discarded
+    pub fn purge_expired(&self) -> Result<usize, TransportError> {
+        let expired = self.take_expired(&mut *self.queue.lock()?);
+        let count = expired.len();
+        self.discard(expired);
+        Ok(count)
+    }
+
+    /// Returns the amount of unexpired data in the queue
+    pub fn len(&self) -> Result<usize, TransportError> {
+        let queue = self.queue.lock()?;
+        match self.ttl {
+            Some(_) => {
+                let now = Instant::now();
+                Ok(queue
+                    .stamps
+                    .iter()
+                    .filter(|stamp| !self.is_expired(**stamp, now))
+                    .count())
+            }
+            None => Ok(queue.items.len()),
+        }
+    }
+
+    pub fn is_empty(&self) -> Result<bool, TransportError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Pops the front of the queue, returning `TransportError::Closed` only once the `Queue` is closed and every item is drained
+    fn pop_now(&self) -> Result<Option<T>, TransportError> {
+        let mut expired = Vec::new();
+        let (item, closed) = {
+            let mut queue = self.queue.lock()?;
+            (self.pop(&mut queue, &mut expired), queue.closed)
+        };
+        self.discard(expired);
+        match item {
+            None if closed => Err(TransportError::Closed),
+            item => Ok(item),
+        }
+    }
+
+    fn drain_now(&self) -> Result<Vec<T>, TransportError> {
+        let mut expired = Vec::new();
+        let (items, closed) = {
+            let mut queue = self.queue.lock()?;
+            (self.drain(&mut queue, &mut expired), queue.closed)
+        };
+        self.discard(expired);
+        match items.is_empty() && closed {
+            true => Err(TransportError::Closed),
+            false => Ok(items),
+        }
+    }
+
+    /// Drains the queue once it holds at least `min` items or is closed, returning `TransportError::Closed` if it is closed and empty
+    fn drain_at_least(
+        &self,
+        queue: &mut QueueState<T>,
+        min: usize,
+        expired: &mut Vec<T>,
+    ) -> Option<Result<Vec<T>, TransportError>> {
+        expired.extend(self.take_expired(queue));
+        match (queue.items.len() >= min, queue.closed) {
+            (_, true) if queue.items.is_empty() => Some(Err(TransportError::Closed)),
+            (true, _) | (_, true) => Some(Ok(self.drain(queue, expired))),
+            (false, false) => None,
         }
     }
 }
 
+#[cfg(feature = "command")]
+impl Queue<crate::Command> {
+    /// Creates a `Queue<Command>` where control commands, any non `Command::Event`, are received before queued events
+    pub fn with_control_priority() -> Self {
+        Self::with_priority(crate::Command::is_control)
+    }
+}
+
 /// Impl transport for queue in FIFO order, handling the inner mutex for synchronization.
 /// `std::Mutex` is used rather than `tokio::Mutex` for lower overhead with the restriction of not holding locks across an `await`.
 impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
     fn send_blocking(&self, data: T) -> Result<(), TransportError> {
         match self.queue.lock() {
             Ok(mut guard) => {
-                guard.push_back(data);
-                self.condvar.notify_one();
-                self.notifier.notify_one();
+                guard.check_open()?;
+                self.push(&mut guard, data);
+                // Wake every receiver, as one waiting for more items than are queued would otherwise take the only wakeup
+                self.condvar.notify_all();
+                self.notifier.notify_waiters();
                 Ok(())
             }
             Err(e) => Err(e.into()),
@@ -60,7 +308,8 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
     fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
         match self.queue.lock() {
             Ok(mut guard) => {
-                guard.extend(data);
+                guard.check_open()?;
+                self.extend(&mut guard, data);
                 self.condvar.notify_all();
                 self.notifier.notify_waiters();
                 Ok(())
@@ -71,26 +320,45 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
 
     fn recv_blocking(&self) -> Result<T, TransportError> {
         let mut guard = self.queue.lock()?;
+        let mut expired = Vec::new();
 
-        while guard.is_empty() {
+        let item = loop {
+            if let Some(item) = self.pop(&mut guard, &mut expired) {
+                break Ok(item);
+            }
+            if guard.closed {
+                break Err(TransportError::Closed);
+            }
             guard = self.condvar.wait(guard)?;
-        }
+        };
 
-        guard.pop_front().ok_or_else(|| TransportError::NoData)
+        drop(guard);
+        self.discard(expired);
+        item
     }
 
     fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
-        match self.queue.lock() {
-            Ok(mut guard) => Ok(guard.drain(..).collect()),
-            Err(e) => Err(e.into()),
-        }
+        self.drain_now()
     }
 
     fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
-        match self.queue.lock() {
-            Ok(mut guard) => Ok(guard.pop_front()),
-            Err(e) => Err(e.into()),
-        }
+        self.pop_now()
+    }
+
+    fn recv_at_least_blocking(&self, min: usize) -> Result<Vec<T>, TransportError> {
+        let mut guard = self.queue.lock()?;
+        let mut expired = Vec::new();
+
+        let items = loop {
+            if let Some(items) = self.drain_at_least(&mut guard, min, &mut expired) {
+                break items;
+            }
+            guard = self.condvar.wait(guard)?;
+        };
+
+        drop(guard);
+        self.discard(expired);
+        items
     }
 
     fn send(
@@ -104,14 +372,17 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
                 + '_,
         >,
     > {
-        if let Err(e) = match self.queue.lock() {
-            Ok(mut guard) => Ok(guard.push_back(data)),
-            Err(e) => Err(e.into()),
-        } {
-            return Box::pin(async { Err(e) });
+        match self.queue.lock() {
+            Ok(guard) if guard.closed => return Box::pin(async { Err(TransportError::Closed) }),
+            Ok(mut guard) => self.push(&mut guard, data),
+            Err(e) => {
+                let e = TransportError::from(e);
+                return Box::pin(async { Err(e) });
+            }
         }
-        self.condvar.notify_one();
-        self.notifier.notify_one();
+        // Wake every receiver, as one waiting for more items than are queued would otherwise take the only wakeup
+        self.condvar.notify_all();
+        self.notifier.notify_waiters();
         Box::pin(async { Ok(()) })
     }
 
@@ -126,11 +397,13 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
                 + '_,
         >,
     > {
-        if let Err(e) = match self.queue.lock() {
-            Ok(mut guard) => Ok(guard.extend(data)),
-            Err(e) => Err(e.into()),
-        } {
-            return Box::pin(async { Err(e) });
+        match self.queue.lock() {
+            Ok(guard) if guard.closed => return Box::pin(async { Err(TransportError::Closed) }),
+            Ok(mut guard) => self.extend(&mut guard, data),
+            Err(e) => {
+                let e = TransportError::from(e);
+                return Box::pin(async { Err(e) });
+            }
         }
         self.condvar.notify_all();
         self.notifier.notify_waiters();
@@ -149,16 +422,17 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
     > {
         Box::pin(async {
             loop {
-                match self.queue.lock() {
-                    Ok(mut queue) => {
-                        if let Some(item) = queue.pop_front() {
-                            return Ok(item);
-                        }
-                    }
-                    Err(e) => return Err(e.into()),
+                // Register for notifications before checking the queue so a send between the check and the wait isn't missed.
+                // Sends use `notify_waiters()`, which only reaches registered receivers
+                let notified = self.notifier.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if let Some(item) = self.pop_now()? {
+                    return Ok(item);
                 }
 
-                self.notifier.notified().await;
+                notified.await;
             }
         })
     }
@@ -173,12 +447,7 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
                 + '_,
         >,
     > {
-        Box::pin(async {
-            match self.queue.lock() {
-                Ok(mut queue) => Ok(queue.drain(..).collect()),
-                Err(e) => Err(e.into()),
-            }
-        })
+        Box::pin(async { self.drain_now() })
     }
 
     fn try_recv(
@@ -191,10 +460,35 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
                 + '_,
         >,
     > {
-        Box::pin(async {
-            match self.queue.lock() {
-                Ok(mut queue) => Ok(queue.pop_front()),
-                Err(e) => Err(e.into()),
+        Box::pin(async { self.pop_now() })
+    }
+
+    fn recv_at_least(
+        &self,
+        min: usize,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async move {
+            loop {
+                // Register for notifications before checking the length so a send between the check and the wait isn't missed
+                let notified = self.notifier.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                let mut expired = Vec::new();
+                let items = self.drain_at_least(&mut *self.queue.lock()?, min, &mut expired);
+                self.discard(expired);
+                if let Some(items) = items {
+                    return items;
+                }
+
+                notified.await;
             }
         })
     }
@@ -202,7 +496,7 @@ impl<T: TransportItemRequirements> Transport<T> for Queue<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Queue, Transport};
+    use crate::{Queue, Transport, TransportError};
 
     #[tokio::test]
     async fn debug() {
@@ -273,4 +567,286 @@ mod tests {
 
         tokio_handle.await.unwrap();
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_receivers() {
+        use std::{sync::Arc, time::Duration};
+
+        // Receivers forward everything to `received`, racing each send with their check of the queue
+        let queue = Arc::new(Queue::<u32>::new());
+        let received = Arc::new(Queue::<u32>::new());
+        let receivers = (0..2)
+            .map(|_| {
+                let queue = queue.clone();
+                let received = received.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let item = queue.recv().await.unwrap();
+                        received.send(item).await.unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Each item is sent alone, so a receiver missing its notification leaves it queued with no later send to wake them.
+        // Mostly batches are sent, as their `notify_waiters()` only reaches receivers already registered
+        for i in 0..10_000 {
+            match i % 4 == 0 {
+                true => queue.send(i).await.unwrap(),
+                false => queue.send_batch(vec![i]).await.unwrap(),
+            }
+            let item = tokio::time::timeout(Duration::from_secs(1), received.recv())
+                .await
+                .expect("No receiver woke up while data was queued")
+                .unwrap();
+            assert_eq!(item, i);
+        }
+        for receiver in receivers {
+            receiver.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn priority() {
+        let queue = Queue::<u8>::with_priority(|i| *i >= 100);
+        queue.send_batch(vec![1, 2, 100, 3]).await.unwrap();
+        queue.send(101).await.unwrap();
+        queue.send_blocking(4).unwrap();
+        assert_eq!(
+            queue.recv_avaliable().await.unwrap(),
+            vec![100, 101, 1, 2, 3, 4]
+        );
+    }
+
+    #[cfg(all(feature = "command", feature = "event"))]
+    #[tokio::test]
+    async fn control_priority() {
+        use crate::{event, Command, Event};
+
+        #[event]
+        struct Numbered(usize);
+
+        let queue = Queue::<Command>::with_control_priority();
+        for i in 0..100 {
+            queue.send(Numbered(i).to_cmd()).await.unwrap();
+        }
+        queue.send(Command::Stop).await.unwrap();
+
+        // The `Stop` jumps the 100 queued events
+        assert_eq!(queue.recv().await.unwrap(), Command::Stop);
+
+        // Events keep their FIFO order
+        for i in 0..100 {
+            assert_eq!(
+                queue
+                    .try_recv()
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .downcast_event::<Numbered>()
+                    .unwrap(),
+                Numbered(i)
+            );
+        }
+        assert!(queue.try_recv().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn recv_at_least() {
+        let queue = std::sync::Arc::new(Queue::<u8>::new());
+        let queue_clone = queue.clone();
+        let tokio_handle = tokio::spawn(async move { queue_clone.recv_at_least(3).await.unwrap() });
+
+        // Items arriving one at a time don't complete the wait early
+        for i in 1..=2 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            queue.send(i).await.unwrap();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!tokio_handle.is_finished());
+
+        // All items present once the minimum is reached are received, including extras
+        queue.send_batch(vec![3, 4]).await.unwrap();
+        assert_eq!(tokio_handle.await.unwrap(), vec![1, 2, 3, 4]);
+
+        let queue_clone = queue.clone();
+        let handle = std::thread::spawn(move || queue_clone.recv_at_least_blocking(3).unwrap());
+        for i in 1..=2 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            queue.send_blocking(i).unwrap();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!handle.is_finished());
+        queue.send_batch_blocking(vec![3, 4]).unwrap();
+        assert_eq!(handle.join().unwrap(), vec![1, 2, 3, 4]);
+
+        // A minimum of `0` returns immediately
+        assert!(queue.recv_at_least(0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn recv_at_least_mixed_waiters() {
+        // A single item wakes a plain receiver even while a larger-minimum receiver is also waiting
+        let queue = std::sync::Arc::new(Queue::<u8>::new());
+        let queue_clone = queue.clone();
+        let at_least_handle =
+            tokio::spawn(async move { queue_clone.recv_at_least(2).await.unwrap() });
+        let queue_clone = queue.clone();
+        let recv_handle = tokio::spawn(async move { queue_clone.recv().await.unwrap() });
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        queue.send(1).await.unwrap();
+        let received = tokio::time::timeout(std::time::Duration::from_secs(1), recv_handle).await;
+        assert_eq!(received.expect("recv() missed the wakeup").unwrap(), 1);
+        assert!(!at_least_handle.is_finished());
+        queue.send_batch(vec![2, 3]).await.unwrap();
+        assert_eq!(at_least_handle.await.unwrap(), vec![2, 3]);
+
+        // The same holds for blocking receivers
+        let queue_clone = queue.clone();
+        let at_least_handle =
+            std::thread::spawn(move || queue_clone.recv_at_least_blocking(2).unwrap());
+        let queue_clone = queue.clone();
+        let recv_handle = std::thread::spawn(move || queue_clone.recv_blocking().unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        queue.send_blocking(1).unwrap();
+        assert_eq!(recv_handle.join().unwrap(), 1);
+        assert!(!at_least_handle.is_finished());
+        queue.send_batch_blocking(vec![2, 3]).unwrap();
+        assert_eq!(at_least_handle.join().unwrap(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn recv_at_least_closed() {
+        // Closing returns the partial batch to a waiting receiver, then `TransportError::Closed` once drained
+        let queue = std::sync::Arc::new(Queue::<u8>::new());
+        let queue_clone = queue.clone();
+        let tokio_handle = tokio::spawn(async move { queue_clone.recv_at_least(3).await });
+        queue.send_batch(vec![1, 2]).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!tokio_handle.is_finished());
+        queue.close().unwrap();
+        assert_eq!(tokio_handle.await.unwrap().unwrap(), vec![1, 2]);
+        assert!(queue.is_closed().unwrap());
+        assert!(matches!(
+            queue.recv_at_least(1).await,
+            Err(TransportError::Closed)
+        ));
+        assert!(matches!(queue.recv().await, Err(TransportError::Closed)));
+        assert!(matches!(queue.send(3).await, Err(TransportError::Closed)));
+
+        // Blocking receivers are woken by the close
+        let queue = std::sync::Arc::new(Queue::<u8>::new());
+        queue.send_blocking(1).unwrap();
+        let queue_clone = queue.clone();
+        let handle = std::thread::spawn(move || queue_clone.recv_at_least_blocking(3));
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!handle.is_finished());
+        queue.close().unwrap();
+        assert_eq!(handle.join().unwrap().unwrap(), vec![1]);
+        assert!(matches!(queue.recv_blocking(), Err(TransportError::Closed)));
+        assert!(matches!(
+            queue.send_batch_blocking(vec![2]),
+            Err(TransportError::Closed)
+        ));
+
+        // The default implementation also returns the partial batch once the transport is closed
+        let (first, second) = crate::duplex::<u8>();
+        let second = std::sync::Arc::new(second);
+        let second_clone = second.clone();
+        let tokio_handle = tokio::spawn(async move { second_clone.recv_at_least(3).await });
+        first.send_batch(vec![1, 2]).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!tokio_handle.is_finished());
+        first.close().unwrap();
+        assert_eq!(tokio_handle.await.unwrap().unwrap(), vec![1, 2]);
+        assert!(matches!(
+            second.recv_at_least_blocking(1),
+            Err(TransportError::Closed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn recv_at_least_default() {
+        // `Transform` uses the default `Transport` methods built on `recv` and `recv_avaliable`
+        let queue = std::sync::Arc::new(
+            crate::Transform::new(std::sync::Arc::new(Queue::<u8>::new())).build(),
+        );
+        let queue_clone = queue.clone();
+        let tokio_handle = tokio::spawn(async move { queue_clone.recv_at_least(3).await.unwrap() });
+
+        for i in 1..=2 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            queue.send(i).await.unwrap();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!tokio_handle.is_finished());
+
+        queue.send_batch(vec![3, 4]).await.unwrap();
+        assert_eq!(tokio_handle.await.unwrap(), vec![1, 2, 3, 4]);
+
+        let queue_clone = queue.clone();
+        let handle = std::thread::spawn(move || queue_clone.recv_at_least_blocking(1).unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        queue.send_batch_blocking(vec![5, 6]).unwrap();
+        assert_eq!(handle.join().unwrap(), vec![5, 6]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ttl() {
+        use std::time::Duration;
+
+        let queue = Queue::<u8>::with_ttl(Duration::from_millis(10));
+        queue.send_batch(vec![1, 2]).await.unwrap();
+        tokio::time::advance(Duration::from_millis(5)).await;
+        queue.send(3).await.unwrap();
+        assert_eq!(queue.len().unwrap(), 3);
+
+        // Expired data is excluded from `Debug` and `len` before being discarded
+        tokio::time::advance(Duration::from_millis(5)).await;
+        assert_eq!(format!("{:?}", queue), "Queue { queue: [3] }");
+        assert_eq!(queue.len().unwrap(), 1);
+
+        // Fresh data behind expired data is still received
+        assert_eq!(queue.try_recv().await.unwrap(), Some(3));
+        assert!(queue.is_empty().unwrap());
+
+        queue.send_batch_blocking(vec![4, 5]).unwrap();
+        tokio::time::advance(Duration::from_millis(10)).await;
+        queue.send_blocking(6).unwrap();
+        assert_eq!(queue.recv().await.unwrap(), 6);
+
+        queue.send_batch_blocking(vec![7, 8]).unwrap();
+        tokio::time::advance(Duration::from_millis(10)).await;
+        queue.send_blocking(9).unwrap();
+        assert_eq!(queue.recv_avaliable_blocking().unwrap(), vec![9]);
+
+        // Nothing expires before the TTL
+        queue.send_batch_blocking(vec![10, 11]).unwrap();
+        tokio::time::advance(Duration::from_millis(9)).await;
+        assert_eq!(queue.purge_expired().unwrap(), 0);
+        tokio::time::advance(Duration::from_millis(1)).await;
+        assert_eq!(queue.purge_expired().unwrap(), 2);
+        assert!(queue.try_recv_blocking().unwrap().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_sink() {
+        use std::{sync::Arc, time::Duration};
+
+        let expired: Arc<dyn Transport<u8>> = Queue::new().into();
+        let queue =
+            Queue::<u8>::with_ttl(Duration::from_millis(10)).with_expired_sink(expired.clone());
+        queue.send_batch(vec![1, 2]).await.unwrap();
+        tokio::time::advance(Duration::from_millis(10)).await;
+        queue.send(3).await.unwrap();
+
+        // Each expired item is sent to the sink instead of being dropped
+        assert_eq!(queue.recv().await.unwrap(), 3);
+        assert_eq!(expired.recv_avaliable().await.unwrap(), vec![1, 2]);
+
+        queue.send_batch(vec![4, 5]).await.unwrap();
+        tokio::time::advance(Duration::from_millis(10)).await;
+        assert_eq!(queue.purge_expired().unwrap(), 2);
+        assert_eq!(expired.recv_avaliable().await.unwrap(), vec![4, 5]);
+    }
 }
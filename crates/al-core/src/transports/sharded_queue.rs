@@ -0,0 +1,349 @@
+use crate::{Queue, SliceDebug, Transport, TransportError, TransportItemRequirements};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+};
+
+/// Key function used by a `ShardedQueue` to pick the shard of each item
+pub type ShardKeyFn<T> = Arc<dyn Fn(&T) -> u64 + Send + Sync>;
+
+/// ShardedQueue transport routing each item to one of several internal `Queue`s by key, preserving FIFO order per key
+pub struct ShardedQueue<T> {
+    shards: Vec<Arc<Queue<T>>>,
+    key: ShardKeyFn<T>,
+    next_shard: AtomicUsize,
+    lock: Mutex<()>,
+    notifier: tokio::sync::Notify,
+    condvar: Condvar,
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for ShardedQueue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardedQueue")
+            .field("shards", &SliceDebug::new(&self.shards))
+            .field("key", &"ShardKeyFn")
+            .finish()
+    }
+}
+
+impl<T: TransportItemRequirements> From<ShardedQueue<T>> for Arc<dyn Transport<T>> {
+    fn from(queue: ShardedQueue<T>) -> Self {
+        Arc::new(queue)
+    }
+}
+
+impl<T: TransportItemRequirements> ShardedQueue<T> {
+    /// Creates a `ShardedQueue` with `shards` internal `Queue`s, a value of `0` is treated as `1`
+    pub fn new(shards: usize, key: ShardKeyFn<T>) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| Arc::new(Queue::new())).collect(),
+            key,
+            next_shard: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            notifier: tokio::sync::Notify::new(),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Returns the number of shards
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the `i`th shard for a dedicated consumer, if it exists
+    pub fn shard(&self, i: usize) -> Option<Arc<dyn Transport<T>>> {
+        self.shards
+            .get(i)
+            .map(|shard| shard.clone() as Arc<dyn Transport<T>>)
+    }
+
+    /// Returns the index of the shard the item is routed to, from the hash of its key
+    pub fn shard_index(&self, data: &T) -> usize {
+        let mut hasher = DefaultHasher::new();
+        (self.key)(data).hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// Groups the items by shard index, keeping their relative order
+    fn group(&self, data: Vec<T>) -> Vec<Vec<T>> {
+        let mut groups: Vec<Vec<T>> = (0..self.shards.len()).map(|_| Vec::new()).collect();
+        for item in data {
+            groups[self.shard_index(&item)].push(item);
+        }
+        groups
+    }
+
+    /// Wakes any fallback receivers waiting on the `ShardedQueue` itself
+    fn notify(&self) -> Result<(), TransportError> {
+        let _guard = self.lock.lock()?;
+        self.condvar.notify_all();
+        self.notifier.notify_waiters();
+        Ok(())
+    }
+
+    /// Returns the shards in order, starting from the next shard in a round robin to avoid starving later shards
+    fn rotated_shards(&self) -> impl Iterator<Item = &Arc<Queue<T>>> {
+        let start = self.next_shard.fetch_add(1, Ordering::Relaxed) % self.shards.len();
+        self.shards
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(self.shards.len())
+    }
+
+    fn try_recv_any(&self) -> Result<Option<T>, TransportError> {
+        for shard in self.rotated_shards() {
+            if let Some(data) = shard.try_recv_blocking()? {
+                return Ok(Some(data));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Impl transport for sharded queue, sending to the keyed shard while receiving from any non-empty shard as a single consumer fallback
+impl<T: TransportItemRequirements> Transport<T> for ShardedQueue<T> {
+    fn send_blocking(&self, data: T) -> Result<(), TransportError> {
+        self.shards[self.shard_index(&data)].send_blocking(data)?;
+        self.notify()
+    }
+
+    fn send_batch_blocking(&self, data: Vec<T>) -> Result<(), TransportError> {
+        for (shard, group) in self.shards.iter().zip(self.group(data)) {
+            if !group.is_empty() {
+                shard.send_batch_blocking(group)?;
+            }
+        }
+        self.notify()
+    }
+
+    fn recv_blocking(&self) -> Result<T, TransportError> {
+        let mut guard = self.lock.lock()?;
+
+        loop {
+            if let Some(data) = self.try_recv_any()? {
+                return Ok(data);
+            }
+            guard = self.condvar.wait(guard)?;
+        }
+    }
+
+    fn recv_avaliable_blocking(&self) -> Result<Vec<T>, TransportError> {
+        let mut data = Vec::new();
+        for shard in self.shards.iter() {
+            data.extend(shard.recv_avaliable_blocking()?);
+        }
+        Ok(data)
+    }
+
+    fn try_recv_blocking(&self) -> Result<Option<T>, TransportError> {
+        self.try_recv_any()
+    }
+
+    fn send(
+        &self,
+        data: T,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn send_batch(
+        &self,
+        data: Vec<T>,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.send_batch_blocking(data);
+        Box::pin(async { result })
+    }
+
+    fn recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<T, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        Box::pin(async {
+            loop {
+                // Register for notifications before checking the shards so a send between the check and the wait isn't missed
+                let notified = self.notifier.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if let Some(data) = self.try_recv_any()? {
+                    return Ok(data);
+                }
+                notified.await;
+            }
+        })
+    }
+
+    fn recv_avaliable(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Vec<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.recv_avaliable_blocking();
+        Box::pin(async { result })
+    }
+
+    fn try_recv(
+        &self,
+    ) -> std::pin::Pin<
+        Box<
+            dyn std::prelude::rust_2024::Future<Output = Result<Option<T>, TransportError>>
+                + Send
+                + Sync
+                + '_,
+        >,
+    > {
+        let result = self.try_recv_blocking();
+        Box::pin(async { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ShardedQueue, Transport};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn debug() {
+        let queue = ShardedQueue::<u8>::new(2, Arc::new(|i| *i as u64));
+        assert_eq!(
+            format!("{:?}", queue),
+            "ShardedQueue { shards: [Queue { queue: [] }, Queue { queue: [] }], key: \"ShardKeyFn\" }"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_recv() {
+        // A single shard keeps the FIFO order of a `Queue`
+        let queue = ShardedQueue::<u8>::new(1, Arc::new(|i| *i as u64));
+        queue.send(1).await.unwrap();
+        queue.send_batch(vec![2, 3]).await.unwrap();
+        assert_eq!(queue.recv().await.unwrap(), 1);
+        assert_eq!(queue.try_recv().await.unwrap().unwrap(), 2);
+        assert_eq!(queue.recv_avaliable().await.unwrap(), vec![3]);
+
+        queue.send_blocking(1).unwrap();
+        queue.send_batch_blocking(vec![2, 3]).unwrap();
+        assert_eq!(queue.recv_blocking().unwrap(), 1);
+        assert_eq!(queue.try_recv_blocking().unwrap().unwrap(), 2);
+        assert_eq!(queue.recv_avaliable_blocking().unwrap(), vec![3]);
+
+        // The fallback consumer receives from every shard
+        let queue = ShardedQueue::<u8>::new(4, Arc::new(|i| *i as u64));
+        queue
+            .send_batch(vec![0, 1, 2, 3, 4, 5, 6, 7])
+            .await
+            .unwrap();
+        let mut received = queue.recv_avaliable().await.unwrap();
+        received.sort();
+        assert_eq!(received, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[tokio::test]
+    async fn pinned_consumers() {
+        const KEYS: u64 = 16;
+        const PER_KEY: usize = 20;
+
+        // Items are `(key, sequence)`, sent with interleaved keys
+        let queue = Arc::new(ShardedQueue::<(u64, usize)>::new(
+            2,
+            Arc::new(|(key, _)| *key),
+        ));
+        let mut handles = Vec::new();
+        for i in 0..queue.shard_count() {
+            let shard = queue.shard(i).unwrap();
+            handles.push(tokio::spawn(async move {
+                let mut received = Vec::new();
+                while let Ok(item) =
+                    tokio::time::timeout(std::time::Duration::from_millis(50), shard.recv()).await
+                {
+                    received.push(item.unwrap());
+                }
+                received
+            }));
+        }
+        for sequence in 0..PER_KEY {
+            let batch = (0..KEYS / 2).map(|key| (key, sequence)).collect();
+            queue.send_batch(batch).await.unwrap();
+            for key in KEYS / 2..KEYS {
+                queue.send((key, sequence)).await.unwrap();
+            }
+        }
+
+        let total = KEYS as usize * PER_KEY;
+        let mut received_total = 0;
+        for (i, handle) in handles.into_iter().enumerate() {
+            let received = handle.await.unwrap();
+            // Each consumer only sees its own shard, with each key in order
+            for key in 0..KEYS {
+                let sequences: Vec<usize> = received
+                    .iter()
+                    .filter(|(k, _)| *k == key)
+                    .map(|(_, sequence)| *sequence)
+                    .collect();
+                if queue.shard_index(&(key, 0)) == i {
+                    assert_eq!(sequences, (0..PER_KEY).collect::<Vec<_>>());
+                } else {
+                    assert!(sequences.is_empty());
+                }
+            }
+            // Roughly balanced between the shards
+            assert!(received.len() >= total / 4);
+            received_total += received.len();
+        }
+        assert_eq!(received_total, total);
+    }
+
+    #[tokio::test]
+    async fn threaded() {
+        let queue = Arc::new(ShardedQueue::<u8>::new(3, Arc::new(|i| *i as u64)));
+        let queue_clone = queue.clone();
+        let handle = std::thread::spawn(move || {
+            assert_eq!(queue_clone.recv_blocking().unwrap(), 42);
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        queue.send_blocking(42).unwrap();
+        handle.join().unwrap();
+
+        let queue_clone = queue.clone();
+        let tokio_handle = tokio::spawn(async move {
+            assert_eq!(queue_clone.recv().await.unwrap(), 42);
+        });
+
+        // Wait to ensure the other thread is receiving the data
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        queue.send(42).await.unwrap();
+        tokio_handle.await.unwrap();
+    }
+}
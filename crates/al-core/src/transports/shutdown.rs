@@ -0,0 +1,289 @@
+use crate::{
+    transports::link::LinkTask, Command, Link, Splice, Transport, TransportError,
+    TransportItemRequirements,
+};
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+
+/// Checks if the `Task` of a registered stage is still running
+type StageRunningFn = Box<dyn Fn() -> Pin<Box<dyn Future<Output = bool> + Send>> + Send + Sync>;
+
+/// Waits for the `Task` of a registered stage to stop
+type StageStoppedFn = Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// A registered stage, with its name and the checks on its `Task`
+struct Stage {
+    name: String,
+    is_running: StageRunningFn,
+    wait_stopped: StageStoppedFn,
+}
+
+/// `PipelineShutdown` tracks the stage `Task`s of a pipeline, sending a `Command::Stop` to its head and waiting for every stage to forward it and complete.
+/// Stages should complete after forwarding a `Command::Stop`, such as a `Link::with_stop_on_stop` or a `Splice::with_control_passthrough`
+#[derive(Default)]
+pub struct PipelineShutdown {
+    stages: Vec<Stage>,
+}
+
+/// Impl Debug for pipeline shutdown manually as `Fn()` doesn't support `Debug`
+impl std::fmt::Debug for PipelineShutdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PipelineShutdown")
+            .field("stages", &self.stages().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl PipelineShutdown {
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Registers the `Task` of a `Link` as a stage named `name`
+    pub fn register<T: TransportItemRequirements>(
+        &mut self,
+        name: impl AsRef<str>,
+        task: &LinkTask<T>,
+    ) -> &mut Self {
+        let running_task = task.clone();
+        let stopped_task = task.clone();
+        self.stages.push(Stage {
+            name: name.as_ref().to_string(),
+            is_running: Box::new(move || {
+                let task = running_task.clone();
+                Box::pin(async move { task.is_running().await })
+            }),
+            wait_stopped: Box::new(move || {
+                let task = stopped_task.clone();
+                Box::pin(async move { task.wait_stopped().await })
+            }),
+        });
+        self
+    }
+
+    /// Registers the `Task` of `link` as a stage named `name`
+    pub fn register_link<T: TransportItemRequirements>(
+        &mut self,
+        name: impl AsRef<str>,
+        link: &Link<T>,
+    ) -> &mut Self {
+        self.register(name, link.link_task())
+    }
+
+    /// Registers the `Task` of the internal `Link` of `splice` as a stage named `name`
+    pub fn register_splice<F: TransportItemRequirements, T: TransportItemRequirements>(
+        &mut self,
+        name: impl AsRef<str>,
+        splice: &Splice<F, T>,
+    ) -> Result<&mut Self, TransportError> {
+        let task = splice.link_task().ok_or_else(|| {
+            TransportError::UnSupported(format!(
+                "The producer of the `Splice` registered as '{}' isn't a `Link`",
+                name.as_ref()
+            ))
+        })?;
+        Ok(self.register(name, task))
+    }
+
+    /// Returns the names of the registered stages, in registration order
+    pub fn stages(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|stage| stage.name.as_str())
+    }
+
+    /// Returns the names of the registered stages whose `Task` is still running
+    pub async fn running(&self) -> Vec<String> {
+        let mut running = Vec::new();
+        for stage in &self.stages {
+            if (stage.is_running)().await {
+                running.push(stage.name.clone());
+            }
+        }
+        running
+    }
+
+    /// Waits for every registered stage to complete, returning the names of the stages still running once `timeout` passes.
+    /// Every stage shares the same deadline, so stages that already stopped are still checked once it has passed
+    pub async fn wait(&self, timeout: Duration) -> Vec<String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut running = Vec::new();
+        for stage in &self.stages {
+            if tokio::time::timeout_at(deadline, (stage.wait_stopped)())
+                .await
+                .is_err()
+            {
+                running.push(stage.name.clone());
+            }
+        }
+        running
+    }
+
+    /// Sends a `Command::Stop` to `pipeline_head`, then waits for every registered stage to complete.
+    /// Returns the names of the stages that didn't complete before `timeout`
+    pub async fn shutdown(
+        &self,
+        pipeline_head: &Arc<dyn Transport<Command>>,
+        timeout: Duration,
+    ) -> Result<Vec<String>, TransportError> {
+        pipeline_head.send(Command::Stop).await?;
+        Ok(self.wait(timeout).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Command, Link, PipelineShutdown, Queue, Splice, Transport};
+    use std::{sync::Arc, time::Duration};
+
+    #[cfg(feature = "event")]
+    #[crate::event]
+    struct Item(u32);
+
+    /// The `Splice` consumer type, with control commands mapped to `Stage::Control`
+    #[derive(Debug, Clone, PartialEq)]
+    enum Stage {
+        #[cfg(feature = "event")]
+        Item(u32),
+        Control(Command),
+    }
+
+    fn is_stop(stage: &Stage) -> bool {
+        matches!(stage, Stage::Control(Command::Stop))
+    }
+
+    #[tokio::test]
+    async fn debug() {
+        let link =
+            Link::<Command>::with_stop_on_stop(Queue::new().into(), Queue::new().into(), true);
+        let mut shutdown = PipelineShutdown::new();
+        shutdown
+            .register_link("first", &link)
+            .register_link("second", &link);
+        assert_eq!(
+            format!("{:?}", shutdown),
+            "PipelineShutdown { stages: [\"first\", \"second\"] }"
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_on_stop() {
+        let consumer: Arc<dyn Transport<Command>> = Queue::new().into();
+        let link = Link::with_stop_on_stop(Queue::new().into(), consumer.clone(), true);
+        let mut shutdown = PipelineShutdown::new();
+        shutdown.register_link("link", &link);
+
+        // The `Link` keeps forwarding control commands other than `Command::Stop`
+        link.send_batch(vec![Command::Pulse, Command::Restart])
+            .await
+            .unwrap();
+        assert_eq!(shutdown.wait(Duration::from_millis(10)).await, vec!["link"]);
+
+        let head: Arc<dyn Transport<Command>> = Arc::new(link);
+        assert!(shutdown
+            .shutdown(&head, Duration::from_secs(1))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            consumer.recv_avaliable().await.unwrap(),
+            vec![Command::Pulse, Command::Restart, Command::Stop]
+        );
+
+        // Without `stop_on_stop`, the `Link` is reported as not completing
+        let link = Link::with_stop_on_stop(Queue::new().into(), Queue::new().into(), false);
+        let mut shutdown = PipelineShutdown::new();
+        shutdown.register_link("link", &link);
+        let head: Arc<dyn Transport<Command>> = Arc::new(link);
+        assert_eq!(
+            shutdown
+                .shutdown(&head, Duration::from_millis(20))
+                .await
+                .unwrap(),
+            vec!["link"]
+        );
+    }
+
+    #[tokio::test]
+    async fn control_passthrough() {
+        let consumer: Arc<dyn Transport<Stage>> = Queue::new().into();
+        let splice = Splice::with_control_passthrough(
+            Queue::new().into(),
+            consumer.clone(),
+            Arc::new(|_| Err(crate::TransportError::Custom("Not an event".to_string()))),
+            Arc::new(|_| async { Err(crate::TransportError::Custom("Not an event".to_string())) }),
+            |command: &Command| Stage::Control(command.clone()),
+        );
+        splice.send(Command::Pulse).await.unwrap();
+        assert_eq!(
+            consumer.recv().await.unwrap(),
+            Stage::Control(Command::Pulse)
+        );
+
+        let mut shutdown = PipelineShutdown::new();
+        shutdown.register_splice("splice", &splice).unwrap();
+        let head: Arc<dyn Transport<Command>> = Arc::new(splice);
+        assert!(shutdown
+            .shutdown(&head, Duration::from_secs(1))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(is_stop(&consumer.recv().await.unwrap()));
+    }
+
+    #[cfg(feature = "event")]
+    #[tokio::test]
+    async fn pipeline() {
+        use crate::Event;
+
+        // Stage 1 forwards commands, stage 2 splices events into `Stage` and stage 3 forwards `Stage`
+        let head: Arc<dyn Transport<Command>> = Queue::new().into();
+        let middle: Arc<dyn Transport<Command>> = Queue::new().into();
+        let spliced: Arc<dyn Transport<Stage>> = Queue::new().into();
+        let tail: Arc<dyn Transport<Stage>> = Queue::new().into();
+
+        let first = Link::with_stop_on_stop(head.clone(), middle.clone(), true);
+        let second = Splice::with_control_passthrough(
+            middle,
+            spliced.clone(),
+            Arc::new(|command: Command| {
+                Ok(Stage::Item(
+                    command
+                        .downcast_event::<Item>()
+                        .map_err(crate::TransportError::Custom)?
+                        .0,
+                ))
+            }),
+            Arc::new(|command: Command| async move {
+                // Slow stage to ensure items are still in-flight when the shutdown starts
+                tokio::time::sleep(Duration::from_micros(100)).await;
+                Ok(Stage::Item(
+                    command
+                        .downcast_event::<Item>()
+                        .map_err(crate::TransportError::Custom)?
+                        .0,
+                ))
+            }),
+            |command: &Command| Stage::Control(command.clone()),
+        );
+        let third = Link::until(spliced, tail.clone(), is_stop);
+
+        let mut shutdown = PipelineShutdown::new();
+        shutdown
+            .register_link("first", &first)
+            .register_splice("second", &second)
+            .unwrap()
+            .register_link("third", &third);
+
+        for i in 0..100 {
+            head.send(Item(i).to_cmd()).await.unwrap();
+        }
+        assert!(shutdown
+            .shutdown(&head, Duration::from_secs(5))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Every item sent before the `Command::Stop` arrives in order ahead of it
+        let mut received = tail.recv_avaliable().await.unwrap();
+        assert!(is_stop(&received.pop().unwrap()));
+        assert_eq!(received, (0..100).map(Stage::Item).collect::<Vec<_>>());
+    }
+}
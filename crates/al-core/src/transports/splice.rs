@@ -1,6 +1,10 @@
 use std::{future::Future, marker::PhantomData, sync::Arc};
 
-use crate::{Link, Transport, TransportError, TransportItemRequirements};
+#[cfg(feature = "command")]
+use crate::Command;
+use crate::{
+    transports::link::LinkTask, Link, Transport, TransportError, TransportItemRequirements,
+};
 
 pub trait SpliceFn<F: TransportItemRequirements, T: TransportItemRequirements>:
     Fn(F) -> Result<T, TransportError> + Send + Sync + 'static
@@ -78,6 +82,44 @@ impl<F: TransportItemRequirements, T: TransportItemRequirements> Splice<F, T> {
         splice_fn: Arc<SpliceFnImpl>,
         async_splice_fn: Arc<AsyncSpliceFnImpl>,
     ) -> Self
+    where
+        SpliceFnImpl: SpliceFn<F, T>,
+        AsyncSpliceFnImpl: AsyncSpliceFn<F, T, Fut>,
+        Fut: SpliceFnFuture<F, T>,
+    {
+        Self::with_link(producer, consumer, splice_fn, async_splice_fn, Link::new)
+    }
+
+    /// Returns a new `Splice` joining `producer<F>` into `consumer<T>`, with the `Task` of the internal `Link` spawned on the `runtime`
+    pub fn new_on<SpliceFnImpl, AsyncSpliceFnImpl, Fut>(
+        runtime: tokio::runtime::Handle,
+        producer: Arc<dyn Transport<F>>,
+        consumer: Arc<dyn Transport<T>>,
+        splice_fn: Arc<SpliceFnImpl>,
+        async_splice_fn: Arc<AsyncSpliceFnImpl>,
+    ) -> Self
+    where
+        SpliceFnImpl: SpliceFn<F, T>,
+        AsyncSpliceFnImpl: AsyncSpliceFn<F, T, Fut>,
+        Fut: SpliceFnFuture<F, T>,
+    {
+        Self::with_link(
+            producer,
+            consumer,
+            splice_fn,
+            async_splice_fn,
+            |producer, splice_transport| Link::new_on(runtime, producer, splice_transport),
+        )
+    }
+
+    /// Returns a new `Splice` joining `producer<F>` into `consumer<T>`, using `link` to create the internal `Link` from the producer
+    fn with_link<SpliceFnImpl, AsyncSpliceFnImpl, Fut>(
+        producer: Arc<dyn Transport<F>>,
+        consumer: Arc<dyn Transport<T>>,
+        splice_fn: Arc<SpliceFnImpl>,
+        async_splice_fn: Arc<AsyncSpliceFnImpl>,
+        link: impl FnOnce(Arc<dyn Transport<F>>, Arc<dyn Transport<F>>) -> Link<F>,
+    ) -> Self
     where
         SpliceFnImpl: SpliceFn<F, T>,
         AsyncSpliceFnImpl: AsyncSpliceFn<F, T, Fut>,
@@ -128,7 +170,7 @@ impl<F: TransportItemRequirements, T: TransportItemRequirements> Splice<F, T> {
         ));
 
         // Set up a `Link` from the producer to the `SpliceTransport<F>`
-        let link = Link::new(producer, splice_transport);
+        let link = link(producer, splice_transport);
 
         // setting the new `Link` as the producer in the `Splice`
         Self(link.into(), consumer)
@@ -145,6 +187,54 @@ impl<F: TransportItemRequirements, T: TransportItemRequirements> Splice<F, T> {
     pub fn consumer(&self) -> &Arc<dyn Transport<T>> {
         &self.1
     }
+
+    /// Returns the `Task` of the `Link` moving data from the producer into the consumer
+    pub fn link_task(&self) -> Option<&LinkTask<F>> {
+        self.0
+            .as_any()
+            .downcast_ref::<Link<F>>()
+            .map(|link| link.link_task())
+    }
+}
+
+#[cfg(feature = "command")]
+impl<T: TransportItemRequirements> Splice<Command, T> {
+    /// Returns a new `Splice` joining `producer<Command>` into `consumer<T>`, where control commands skip the splice functions and are mapped by `control_fn` instead.
+    /// The internal `Link` completes its `Task` after forwarding a `Command::Stop`, letting the `Splice` take part in a pipeline shutdown
+    pub fn with_control_passthrough<SpliceFnImpl, AsyncSpliceFnImpl, Fut>(
+        producer: Arc<dyn Transport<Command>>,
+        consumer: Arc<dyn Transport<T>>,
+        splice_fn: Arc<SpliceFnImpl>,
+        async_splice_fn: Arc<AsyncSpliceFnImpl>,
+        control_fn: impl Fn(&Command) -> T + Send + Sync + 'static,
+    ) -> Self
+    where
+        SpliceFnImpl: SpliceFn<Command, T>,
+        AsyncSpliceFnImpl: AsyncSpliceFn<Command, T, Fut>,
+        Fut: SpliceFnFuture<Command, T>,
+    {
+        let control_fn = Arc::new(control_fn);
+        let async_control_fn = control_fn.clone();
+        Self::with_link(
+            producer,
+            consumer,
+            Arc::new(move |command: Command| match command.is_control() {
+                true => Ok(control_fn(&command)),
+                false => splice_fn(command),
+            }),
+            Arc::new(move |command: Command| {
+                let async_control_fn = async_control_fn.clone();
+                let async_splice_fn = async_splice_fn.clone();
+                async move {
+                    match command.is_control() {
+                        true => Ok(async_control_fn(&command)),
+                        false => async_splice_fn(command).await,
+                    }
+                }
+            }),
+            |producer, splice_transport| Link::with_stop_on_stop(producer, splice_transport, true),
+        )
+    }
 }
 
 /// On `send`, calls `send` on the internal `splice.producer<F>()`.
@@ -0,0 +1,434 @@
+use crate::{
+    Link, List, NoOp, Publisher, Queue, Registry, Transform, TransformFn, Transport,
+    TransportError, TransportItemRequirements,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc};
+
+/// Constructor for a named transport registered with a `TopologyRegistry`
+pub type TransportFactory<T> = Arc<dyn Fn() -> Arc<dyn Transport<T>> + Send + Sync>;
+
+/// Error type for building a `TopologySpec`
+#[derive(Debug, Clone)]
+pub enum TopologyError {
+    /// Two nodes share the same name
+    DuplicateNode(String),
+    /// An edge references a node that isn't in the spec
+    UnknownNode(String),
+    /// A transform node references a transform that isn't registered
+    UnknownTransform(String),
+    /// A custom node references a transport factory that isn't registered
+    UnknownFactory(String),
+    /// The edges form a cycle, holding the node names along it
+    Cycle(Vec<String>),
+    /// A node other than a `publisher` or `list` has more than one outgoing edge, which would split its data between competing `Link`s
+    FanOut(String),
+    Transport(TransportError),
+}
+
+impl std::fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TopologyError::DuplicateNode(name) => write!(f, "Duplicate node {}", name),
+            TopologyError::UnknownNode(name) => write!(f, "Unknown node {}", name),
+            TopologyError::UnknownTransform(name) => write!(f, "Unknown transform {}", name),
+            TopologyError::UnknownFactory(name) => write!(f, "Unknown transport factory {}", name),
+            TopologyError::Cycle(cycle) => write!(f, "Edges form a cycle: {}", cycle.join(" -> ")),
+            TopologyError::FanOut(name) => write!(
+                f,
+                "Node {} has more than one outgoing edge, only publisher and list nodes can fan out",
+                name
+            ),
+            TopologyError::Transport(e) => write!(f, "Transport error: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for TopologyError {}
+
+impl From<TransportError> for TopologyError {
+    fn from(err: TransportError) -> Self {
+        TopologyError::Transport(err)
+    }
+}
+
+/// Kind of transport a `NodeSpec` builds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NodeKind {
+    Queue,
+    Publisher,
+    List,
+    /// A `Queue` wrapped in a `Transform` applying the registered transforms by name, `NoOp` if not set
+    Transform {
+        #[serde(default)]
+        send: Option<String>,
+        #[serde(default)]
+        recv: Option<String>,
+    },
+    /// A transport built by the registered `TransportFactory` by name
+    Custom {
+        factory: String,
+    },
+}
+
+/// A named node of a `TopologySpec`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeSpec {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: NodeKind,
+}
+
+/// Data flow from one node to another.
+///
+/// From a `Publisher` or `List` node the target is subscribed or pushed, for any other node a `Link` moves the data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeSpec {
+    pub from: String,
+    pub to: String,
+}
+
+/// Declarative description of a pipeline of transports, deserializable from any serde format
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TopologySpec {
+    pub nodes: Vec<NodeSpec>,
+    #[serde(default)]
+    pub edges: Vec<EdgeSpec>,
+}
+
+/// Named transforms and transport factories a `TopologySpec` can reference
+pub struct TopologyRegistry<T: TransportItemRequirements> {
+    transforms: Registry<String, TransformFn<T>>,
+    factories: Registry<String, TransportFactory<T>>,
+}
+
+impl<T: TransportItemRequirements> std::fmt::Debug for TopologyRegistry<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut transforms = self.transforms.keys().collect::<Vec<_>>();
+        let mut factories = self.factories.keys().collect::<Vec<_>>();
+        transforms.sort();
+        factories.sort();
+        f.debug_struct("TopologyRegistry")
+            .field("transforms", &transforms)
+            .field("factories", &factories)
+            .finish()
+    }
+}
+
+impl<T: TransportItemRequirements> Default for TopologyRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TransportItemRequirements> TopologyRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            transforms: Registry::new(),
+            factories: Registry::new(),
+        }
+    }
+
+    /// Registers a transform that `Transform` nodes can reference by `name`
+    pub fn with_transform(
+        mut self,
+        name: impl AsRef<str>,
+        transform: impl Into<TransformFn<T>>,
+    ) -> Self {
+        self.transforms
+            .insert(name.as_ref().to_string(), transform.into());
+        self
+    }
+
+    /// Registers a transport constructor that `Custom` nodes can reference by `name`
+    pub fn with_factory(
+        mut self,
+        name: impl AsRef<str>,
+        factory: impl Fn() -> Arc<dyn Transport<T>> + Send + Sync + 'static,
+    ) -> Self {
+        self.factories
+            .insert(name.as_ref().to_string(), Arc::new(factory));
+        self
+    }
+
+    fn transform(&self, name: &Option<String>) -> Result<TransformFn<T>, TopologyError> {
+        match name {
+            Some(name) => self
+                .transforms
+                .get(name)
+                .cloned()
+                .ok_or_else(|| TopologyError::UnknownTransform(name.clone())),
+            None => Ok(NoOp.into()),
+        }
+    }
+}
+
+/// A built node, keeping `Publisher` and `List` typed so edges from them can subscribe their targets
+enum BuiltNode<T: TransportItemRequirements> {
+    Publisher(Arc<Publisher<T>>),
+    List(Arc<List<T>>),
+    Transport(Arc<dyn Transport<T>>),
+}
+
+impl<T: TransportItemRequirements> BuiltNode<T> {
+    fn transport(&self) -> Arc<dyn Transport<T>> {
+        match self {
+            BuiltNode::Publisher(publisher) => publisher.clone(),
+            BuiltNode::List(list) => list.clone(),
+            BuiltNode::Transport(transport) => transport.clone(),
+        }
+    }
+}
+
+/// Transports built from a `TopologySpec` by name, owning every `Link` task so dropping it stops the data flow
+pub struct BuiltTopology<T: TransportItemRequirements> {
+    nodes: Registry<String, Arc<dyn Transport<T>>>,
+    links: Vec<Link<T>>,
+}
+
+impl<T: TransportItemRequirements> std::fmt::Debug for BuiltTopology<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut nodes = self.nodes.keys().collect::<Vec<_>>();
+        nodes.sort();
+        f.debug_struct("BuiltTopology")
+            .field("nodes", &nodes)
+            .field("links", &self.links.len())
+            .finish()
+    }
+}
+
+impl<T: TransportItemRequirements> BuiltTopology<T> {
+    /// Get the transport of the node by name
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&Arc<dyn Transport<T>>> {
+        self.nodes.get(name.as_ref())
+    }
+
+    /// Get the `Link`s moving data along the edges
+    pub fn links(&self) -> &[Link<T>] {
+        &self.links
+    }
+}
+
+/// Returns the node names along the first cycle found in the edges, if any
+fn find_cycle(spec: &TopologySpec) -> Option<Vec<String>> {
+    fn visit<'a>(
+        node: &'a str,
+        spec: &'a TopologySpec,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|n| *n == node) {
+            let mut cycle = path[start..]
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>();
+            cycle.push(node.to_string());
+            return Some(cycle);
+        }
+        if !done.insert(node) {
+            return None;
+        }
+        path.push(node);
+        for edge in spec.edges.iter().filter(|edge| edge.from == node) {
+            if let Some(cycle) = visit(&edge.to, spec, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        None
+    }
+
+    let mut done = HashSet::new();
+    spec.nodes
+        .iter()
+        .find_map(|node| visit(&node.name, spec, &mut Vec::new(), &mut done))
+}
+
+/// Builds every node of the `spec` then connects its edges, checking for unknown names and cycles before any `Link` task is started
+pub fn build<T: TransportItemRequirements>(
+    spec: &TopologySpec,
+    registry: &TopologyRegistry<T>,
+) -> Result<BuiltTopology<T>, TopologyError> {
+    let mut built: Registry<String, BuiltNode<T>> = Registry::new();
+    for node in spec.nodes.iter() {
+        let transport = match &node.kind {
+            NodeKind::Queue => BuiltNode::Transport(Arc::new(Queue::new())),
+            NodeKind::Publisher => BuiltNode::Publisher(Arc::new(Publisher::new())),
+            NodeKind::List => BuiltNode::List(Arc::new(List::new())),
+            NodeKind::Transform { send, recv } => BuiltNode::Transport(Arc::new(Transform::from(
+                Arc::new(Queue::new()),
+                registry.transform(send)?,
+                registry.transform(recv)?,
+            ))),
+            NodeKind::Custom { factory } => BuiltNode::Transport(registry
+                .factories
+                .get(factory)
+                .ok_or_else(|| {
+                TopologyError::UnknownFactory(factory.clone())
+            })?()),
+        };
+        if built.insert(node.name.clone(), transport).is_some() {
+            return Err(TopologyError::DuplicateNode(node.name.clone()));
+        }
+    }
+
+    for edge in spec.edges.iter() {
+        for name in [&edge.from, &edge.to] {
+            if !built.contains_key(name) {
+                return Err(TopologyError::UnknownNode(name.clone()));
+            }
+        }
+    }
+    // Each edge from any other node is a `Link` receiving from it, so a second one would compete for its data
+    let mut linked = HashSet::new();
+    for edge in spec.edges.iter() {
+        if matches!(built[&edge.from], BuiltNode::Transport(_)) && !linked.insert(&edge.from) {
+            return Err(TopologyError::FanOut(edge.from.clone()));
+        }
+    }
+    if let Some(cycle) = find_cycle(spec) {
+        return Err(TopologyError::Cycle(cycle));
+    }
+
+    let mut links = Vec::new();
+    for edge in spec.edges.iter() {
+        let target = built[&edge.to].transport();
+        match &built[&edge.from] {
+            BuiltNode::Publisher(publisher) => publisher.subscribe(target)?,
+            BuiltNode::List(list) => list.push(target)?,
+            BuiltNode::Transport(source) => links.push(Link::new(source.clone(), target)),
+        }
+    }
+
+    Ok(BuiltTopology {
+        nodes: built
+            .into_iter()
+            .map(|(name, node)| (name, node.transport()))
+            .collect(),
+        links,
+    })
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use crate::{build_topology as build, TopologyError, TopologyRegistry, TopologySpec};
+    use std::sync::Arc;
+
+    const SPEC: &str = r#"{
+        "nodes": [
+            { "name": "input", "kind": "queue" },
+            { "name": "double", "kind": "transform", "send": "double" },
+            { "name": "fan_out", "kind": "publisher" },
+            { "name": "left", "kind": "queue" },
+            { "name": "right", "kind": "custom", "factory": "queue" }
+        ],
+        "edges": [
+            { "from": "input", "to": "double" },
+            { "from": "double", "to": "fan_out" },
+            { "from": "fan_out", "to": "left" },
+            { "from": "fan_out", "to": "right" }
+        ]
+    }"#;
+
+    fn registry() -> TopologyRegistry<u8> {
+        TopologyRegistry::new()
+            .with_transform("double", |i: u8| i * 2)
+            .with_factory("queue", || Arc::new(crate::Queue::<u8>::new()))
+    }
+
+    #[tokio::test]
+    async fn debug() {
+        let spec: TopologySpec = serde_json::from_str(SPEC).unwrap();
+        assert_eq!(
+            format!("{:?}", registry()),
+            "TopologyRegistry { transforms: [\"double\"], factories: [\"queue\"] }"
+        );
+        assert_eq!(
+            format!("{:?}", build(&spec, &registry()).unwrap()),
+            "BuiltTopology { nodes: [\"double\", \"fan_out\", \"input\", \"left\", \"right\"], links: 2 }"
+        );
+    }
+
+    #[tokio::test]
+    async fn data_flow() {
+        let spec: TopologySpec = serde_json::from_str(SPEC).unwrap();
+        let topology = build(&spec, &registry()).unwrap();
+
+        let input = topology.get("input").unwrap();
+        for i in 1..=3 {
+            input.send(i).await.unwrap();
+        }
+        for leaf in ["left", "right"] {
+            let leaf = topology.get(leaf).unwrap();
+            for i in 1..=3 {
+                assert_eq!(leaf.recv().await.unwrap(), i * 2);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid() {
+        let registry = registry();
+        let spec = |json: &str| serde_json::from_str::<TopologySpec>(json).unwrap();
+
+        assert!(matches!(
+            build(
+                &spec(r#"{ "nodes": [{ "name": "a", "kind": "queue" }], "edges": [{ "from": "a", "to": "b" }] }"#),
+                &registry
+            ),
+            Err(TopologyError::UnknownNode(name)) if name == "b"
+        ));
+        assert!(matches!(
+            build(
+                &spec(r#"{ "nodes": [{ "name": "a", "kind": "transform", "recv": "triple" }] }"#),
+                &registry
+            ),
+            Err(TopologyError::UnknownTransform(name)) if name == "triple"
+        ));
+        assert!(matches!(
+            build(
+                &spec(r#"{ "nodes": [{ "name": "a", "kind": "custom", "factory": "tcp" }] }"#),
+                &registry
+            ),
+            Err(TopologyError::UnknownFactory(name)) if name == "tcp"
+        ));
+        assert!(matches!(
+            build(
+                &spec(r#"{ "nodes": [{ "name": "a", "kind": "queue" }, { "name": "a", "kind": "list" }] }"#),
+                &registry
+            ),
+            Err(TopologyError::DuplicateNode(name)) if name == "a"
+        ));
+        assert!(matches!(
+            build(
+                &spec(
+                    r#"{
+                        "nodes": [{ "name": "a", "kind": "queue" }, { "name": "b", "kind": "publisher" }, { "name": "c", "kind": "list" }],
+                        "edges": [{ "from": "a", "to": "b" }, { "from": "b", "to": "c" }, { "from": "c", "to": "a" }]
+                    }"#
+                ),
+                &registry
+            ),
+            Err(TopologyError::Cycle(cycle)) if cycle == vec!["a", "b", "c", "a"]
+        ));
+        for kind in [r#""queue""#, r#""transform""#] {
+            assert!(matches!(
+                build(
+                    &spec(&format!(
+                        r#"{{
+                            "nodes": [{{ "name": "a", "kind": {kind} }}, {{ "name": "b", "kind": "queue" }}, {{ "name": "c", "kind": "queue" }}],
+                            "edges": [{{ "from": "a", "to": "b" }}, {{ "from": "a", "to": "c" }}]
+                        }}"#
+                    )),
+                    &registry
+                ),
+                Err(TopologyError::FanOut(name)) if name == "a"
+            ));
+        }
+        assert_eq!(
+            TopologyError::Cycle(vec!["a".into(), "b".into(), "a".into()]).to_string(),
+            "Edges form a cycle: a -> b -> a"
+        );
+    }
+}
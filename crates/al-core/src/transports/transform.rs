@@ -144,6 +144,7 @@ pub struct Transform<T: TransportItemRequirements> {
 
 impl<T: TransportItemRequirements> Transform<T> {
     /// Returns a new `TransformBuilder` to allow `Transform` configuration
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(transport: Arc<dyn Transport<T>>) -> TransformBuilder<T> {
         TransformBuilder::no_op(transport)
     }
@@ -189,7 +190,7 @@ impl<T: TransportItemRequirements> Transport<T> for Transform<T> {
         Ok(self
             .transport
             .try_recv_blocking()?
-            .and_then(|data| Some(self.transform_recv.apply(data))))
+            .map(|data| self.transform_recv.apply(data)))
     }
 
     fn send(
@@ -273,7 +274,7 @@ impl<T: TransportItemRequirements> Transport<T> for Transform<T> {
                 .transport
                 .try_recv()
                 .await?
-                .and_then(|data| Some(self.transform_recv.apply(data))))
+                .map(|data| self.transform_recv.apply(data)))
         })
     }
 }
@@ -483,7 +484,7 @@ mod tests {
     async fn builder() {
         Transform::<u8>::new(Queue::new().into())
             .with_send(TestStruct)
-            .with_recv(|x| { x + 1 })
+            .with_recv(|x| x + 1)
             .build();
     }
 }
@@ -0,0 +1,553 @@
+use crate::{
+    transports::link::LinkTask, AsTaskState, Command, Event, ExtendedTaskState, Link, Task,
+    Transport, TransportError, TransportItemRequirements,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+type WatchdogTask<T> =
+    Task<(), TransportError, ExtendedTaskState<(), TransportError, Arc<WatchdogStages<T>>>>;
+
+/// `ProgressProbe` exposes how far a stage has got, allowing a `Watchdog` to detect it stalling
+pub trait ProgressProbe: Send + Sync {
+    /// Returns the number of items the stage has forwarded, which only ever increases
+    fn progress(&self) -> usize;
+
+    /// Returns true if the stage has items waiting to be forwarded
+    fn has_pending(&self) -> bool;
+}
+
+/// A `Link` is pending while its producer has a queued backlog, falling back to its in-flight items when the producer isn't a `Queue`
+impl<T: TransportItemRequirements> ProgressProbe for Link<T> {
+    fn progress(&self) -> usize {
+        self.forwarded()
+    }
+
+    fn has_pending(&self) -> bool {
+        match self.backlog() {
+            Some(backlog) => backlog > 0,
+            None => self.in_flight() > 0,
+        }
+    }
+}
+
+/// Event sent to the monitor of a `Watchdog` when a stage with pending items shows no progress for `intervals` checks
+#[crate::event]
+pub struct StageStalled {
+    pub name: String,
+    pub progress: usize,
+    pub intervals: usize,
+}
+
+/// What a `Watchdog` does with a stalled `Link`, after sending a `StageStalled` event to its monitor
+pub enum StallAction<T: TransportItemRequirements> {
+    /// Only report the stall
+    Report,
+    /// Restart the `Link` onto the consumer with `link.restart(consumer)`
+    Restart(Arc<dyn Transport<T>>),
+    /// Abort the `Link`, after which it's no longer checked until it's restarted elsewhere
+    Abort,
+}
+
+impl<T: TransportItemRequirements> std::fmt::Debug for StallAction<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StallAction::Report => write!(f, "Report"),
+            StallAction::Restart(consumer) => f.debug_tuple("Restart").field(consumer).finish(),
+            StallAction::Abort => write!(f, "Abort"),
+        }
+    }
+}
+
+/// A stage checked by a `Watchdog`, either a shared `Link` it can act on or a `ProgressProbe` it reports on
+enum WatchTarget<T: TransportItemRequirements> {
+    Link(Arc<Mutex<Link<T>>>, StallAction<T>),
+    Probe(Arc<dyn ProgressProbe>),
+}
+
+struct WatchedStage<T: TransportItemRequirements> {
+    name: String,
+    target: WatchTarget<T>,
+    last_progress: usize,
+    idle_intervals: usize,
+    /// The `LinkTask` the `Watchdog` aborted, which isn't checked until the `Link` is restarted onto another `Task`
+    aborted: Option<LinkTask<T>>,
+}
+
+impl<T: TransportItemRequirements> WatchedStage<T> {
+    /// Returns the progress of the stage and if it has pending items, or `None` if the stage isn't being checked
+    fn sample(&self) -> Result<Option<(usize, bool)>, TransportError> {
+        match &self.target {
+            WatchTarget::Link(link, _) => {
+                let link = link.lock()?;
+                let aborted = self
+                    .aborted
+                    .as_ref()
+                    .is_some_and(|task| Arc::ptr_eq(task, link.link_task()));
+                match aborted {
+                    true => Ok(None),
+                    false => Ok(Some((link.progress(), link.has_pending()))),
+                }
+            }
+            WatchTarget::Probe(probe) => Ok(Some((probe.progress(), probe.has_pending()))),
+        }
+    }
+}
+
+/// Stages shared between a `Watchdog` and its checking `Task`
+struct WatchdogStages<T: TransportItemRequirements> {
+    stages: Mutex<Vec<WatchedStage<T>>>,
+    stall_intervals: usize,
+    stalls: AtomicUsize,
+    monitor: Option<Arc<dyn Transport<Command>>>,
+}
+
+impl<T: TransportItemRequirements> WatchdogStages<T> {
+    /// Checks the progress of every stage, acting on those stalled for `stall_intervals` checks
+    fn check(&self) -> Result<Vec<StageStalled>, TransportError> {
+        let mut stalled = Vec::new();
+        for stage in self.stages.lock()?.iter_mut() {
+            let Some((progress, pending)) = stage.sample()? else {
+                continue;
+            };
+            if progress != stage.last_progress || !pending {
+                stage.last_progress = progress;
+                stage.idle_intervals = 0;
+                continue;
+            }
+            stage.idle_intervals += 1;
+            if stage.idle_intervals < self.stall_intervals {
+                continue;
+            }
+
+            stalled.push(StageStalled {
+                name: stage.name.clone(),
+                progress,
+                intervals: stage.idle_intervals,
+            });
+            stage.idle_intervals = 0;
+            if let WatchTarget::Link(link, action) = &stage.target {
+                let mut link = link.lock()?;
+                match action {
+                    StallAction::Report => {}
+                    StallAction::Restart(consumer) => link.restart(consumer.clone()),
+                    StallAction::Abort => {
+                        link.abort();
+                        stage.aborted = Some(link.link_task().clone());
+                    }
+                }
+            }
+        }
+        self.stalls.fetch_add(stalled.len(), Ordering::Relaxed);
+        Ok(stalled)
+    }
+
+    /// Checks every stage, sending a `StageStalled` event to the monitor for each stalled one.
+    /// The monitor is sent to after the stages are released so a slow monitor doesn't hold up watching
+    async fn check_and_report(&self) -> Result<Vec<StageStalled>, TransportError> {
+        let stalled = self.check()?;
+        if let Some(monitor) = &self.monitor {
+            for event in stalled.iter() {
+                let _ = monitor.send(event.clone().to_cmd()).await;
+            }
+        }
+        Ok(stalled)
+    }
+}
+
+/// `Watchdog` runs a `Task` checking the progress of named stages every interval.
+/// A stage with pending items and no progress for `stall_intervals` checks is reported as a `StageStalled` event to the monitor, with watched `Link`s also handled by their `StallAction`
+pub struct Watchdog<T: TransportItemRequirements> {
+    stages: Arc<WatchdogStages<T>>,
+    interval: Duration,
+    #[allow(unused)]
+    watch_task: WatchdogTask<T>,
+}
+
+impl<T: TransportItemRequirements> std::fmt::Debug for Watchdog<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("Watchdog");
+        match self.stages() {
+            Ok(stages) => debug.field("stages", &stages),
+            Err(e) => debug.field("stages", &format!("<LockPoisoned>: {:?}", e)),
+        };
+        debug
+            .field("interval", &self.interval)
+            .field("stall_intervals", &self.stages.stall_intervals)
+            .finish()
+    }
+}
+
+impl<T: TransportItemRequirements> Watchdog<T> {
+    /// Creates a new `Watchdog` with a `Task` checking every `interval`, treating stages without progress for `stall_intervals` checks as stalled.
+    /// `StageStalled` events are sent to the `monitor`, if there is one
+    pub fn new(
+        interval: Duration,
+        stall_intervals: usize,
+        monitor: Option<Arc<dyn Transport<Command>>>,
+    ) -> Self {
+        let stages = Arc::new(WatchdogStages {
+            stages: Mutex::new(Vec::new()),
+            stall_intervals: stall_intervals.max(1),
+            stalls: AtomicUsize::new(0),
+            monitor,
+        });
+        Self {
+            stages: stages.clone(),
+            interval,
+            watch_task: Task::infinite(
+                move |_, state| {
+                    let state = state.clone();
+                    async move {
+                        let stages = state.read().await.inner_clone();
+                        // This tight inner loop never ends, so the `Task` only stops once the `Watchdog` is dropped
+                        loop {
+                            tokio::time::sleep(interval).await;
+                            let _ = stages.check_and_report().await;
+                        }
+                    }
+                },
+                stages.as_task_state(),
+            ),
+        }
+    }
+
+    /// Watches the shared `link` as a stage named `name`, handling it with `action` once stalled.
+    /// The caller keeps its own handle to the `link`, so it can still restart or abort it
+    pub fn watch_link(
+        &self,
+        name: impl AsRef<str>,
+        link: Arc<Mutex<Link<T>>>,
+        action: StallAction<T>,
+    ) -> Result<(), TransportError> {
+        self.watch_target(name, WatchTarget::Link(link, action))
+    }
+
+    /// Watches `probe` as a stage named `name`, only reporting once it's stalled
+    pub fn watch(
+        &self,
+        name: impl AsRef<str>,
+        probe: Arc<dyn ProgressProbe>,
+    ) -> Result<(), TransportError> {
+        self.watch_target(name, WatchTarget::Probe(probe))
+    }
+
+    fn watch_target(
+        &self,
+        name: impl AsRef<str>,
+        target: WatchTarget<T>,
+    ) -> Result<(), TransportError> {
+        let mut stage = WatchedStage {
+            name: name.as_ref().to_string(),
+            target,
+            last_progress: 0,
+            idle_intervals: 0,
+            aborted: None,
+        };
+        if let Some((progress, _)) = stage.sample()? {
+            stage.last_progress = progress;
+        }
+        self.stages.stages.lock()?.push(stage);
+        Ok(())
+    }
+
+    /// Returns the names of the watched stages, in the order they were added
+    pub fn stages(&self) -> Result<Vec<String>, TransportError> {
+        Ok(self
+            .stages
+            .stages
+            .lock()?
+            .iter()
+            .map(|stage| stage.name.clone())
+            .collect())
+    }
+
+    /// Runs a check immediately rather than waiting for the next interval, returning the stalled stages
+    pub async fn check(&self) -> Result<Vec<StageStalled>, TransportError> {
+        self.stages.check_and_report().await
+    }
+
+    /// Returns the number of stalls detected across every stage
+    pub fn stalls(&self) -> usize {
+        self.stages.stalls.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Command, Link, ProgressProbe, Queue, StageStalled, StallAction, Transport, TransportError,
+        Watchdog,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    /// Consumer whose sends never complete, like a downstream deadlock
+    #[derive(Debug)]
+    struct Wedged;
+
+    impl Transport<u8> for Wedged {
+        fn send_blocking(&self, _: u8) -> Result<(), TransportError> {
+            Err(TransportError::UnSupported("Wedged".to_string()))
+        }
+
+        fn send_batch_blocking(&self, _: Vec<u8>) -> Result<(), TransportError> {
+            Err(TransportError::UnSupported("Wedged".to_string()))
+        }
+
+        fn recv_blocking(&self) -> Result<u8, TransportError> {
+            Err(TransportError::UnSupported("Wedged".to_string()))
+        }
+
+        fn recv_avaliable_blocking(&self) -> Result<Vec<u8>, TransportError> {
+            Err(TransportError::UnSupported("Wedged".to_string()))
+        }
+
+        fn try_recv_blocking(&self) -> Result<Option<u8>, TransportError> {
+            Err(TransportError::UnSupported("Wedged".to_string()))
+        }
+
+        fn send(
+            &self,
+            _: u8,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(std::future::pending())
+        }
+
+        fn send_batch(
+            &self,
+            _: Vec<u8>,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<(), TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(std::future::pending())
+        }
+
+        fn recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<u8, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async { self.recv_blocking() })
+        }
+
+        fn recv_avaliable(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<Vec<u8>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async { self.recv_avaliable_blocking() })
+        }
+
+        fn try_recv(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::prelude::rust_2024::Future<Output = Result<Option<u8>, TransportError>>
+                    + Send
+                    + Sync
+                    + '_,
+            >,
+        > {
+            Box::pin(async { self.try_recv_blocking() })
+        }
+    }
+
+    /// Probe with manually set progress
+    #[derive(Default)]
+    struct Counter {
+        progress: AtomicUsize,
+        pending: AtomicUsize,
+    }
+
+    impl ProgressProbe for Counter {
+        fn progress(&self) -> usize {
+            self.progress.load(Ordering::Relaxed)
+        }
+
+        fn has_pending(&self) -> bool {
+            self.pending.load(Ordering::Relaxed) > 0
+        }
+    }
+
+    #[tokio::test]
+    async fn debug() {
+        let watchdog = Watchdog::<u8>::new(Duration::from_secs(60), 3, None);
+        watchdog
+            .watch_link(
+                "link",
+                Arc::new(Mutex::new(Link::new(
+                    Queue::new().into(),
+                    Queue::new().into(),
+                ))),
+                StallAction::Report,
+            )
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", watchdog),
+            "Watchdog { stages: [\"link\"], interval: 60s, stall_intervals: 3 }"
+        );
+        assert_eq!(
+            format!("{:?}", StallAction::<u8>::Restart(Queue::new().into())),
+            "Restart(Queue { queue: [] })"
+        );
+    }
+
+    #[tokio::test]
+    async fn probe() {
+        let watchdog = Watchdog::<u8>::new(Duration::from_secs(60), 2, None);
+        let counter = Arc::new(Counter::default());
+        watchdog.watch("counter", counter.clone()).unwrap();
+
+        // Without pending items, an idle stage isn't stalled
+        for _ in 0..3 {
+            assert!(watchdog.check().await.unwrap().is_empty());
+        }
+
+        // Progress resets the idle checks, so it takes two checks without progress to stall
+        counter.pending.store(1, Ordering::Relaxed);
+        assert!(watchdog.check().await.unwrap().is_empty());
+        counter.progress.store(1, Ordering::Relaxed);
+        assert!(watchdog.check().await.unwrap().is_empty());
+        assert!(watchdog.check().await.unwrap().is_empty());
+        assert_eq!(
+            watchdog.check().await.unwrap(),
+            vec![StageStalled {
+                name: "counter".to_string(),
+                progress: 1,
+                intervals: 2,
+            }]
+        );
+        assert_eq!(watchdog.stalls(), 1);
+    }
+
+    #[tokio::test]
+    async fn restart() {
+        let producer: Arc<dyn Transport<u8>> = Queue::new().into();
+        let working: Arc<dyn Transport<u8>> = Queue::new().into();
+        let monitor: Arc<dyn Transport<Command>> = Queue::new().into();
+        let watchdog = Watchdog::new(Duration::from_millis(5), 3, Some(monitor.clone()));
+        watchdog
+            .watch_link(
+                "wedged",
+                Arc::new(Mutex::new(Link::new(producer.clone(), Arc::new(Wedged)))),
+                StallAction::Restart(working.clone()),
+            )
+            .unwrap();
+
+        // The first item wedges the `Link`, until the stall is detected and it's restarted onto the working consumer
+        producer.send_batch(vec![1, 2, 3]).await.unwrap();
+        let stalled = tokio::time::timeout(Duration::from_secs(1), monitor.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .downcast_event::<StageStalled>()
+            .unwrap();
+        assert_eq!(stalled.name, "wedged");
+        assert_eq!(stalled.progress, 0);
+        assert_eq!(stalled.intervals, 3);
+        assert_eq!(watchdog.stalls(), 1);
+
+        // The in-flight item is dropped, with the rest forwarded to the working consumer
+        assert_eq!(working.recv().await.unwrap(), 2);
+        assert_eq!(working.recv().await.unwrap(), 3);
+        producer.send(4).await.unwrap();
+        assert_eq!(working.recv().await.unwrap(), 4);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(watchdog.stalls(), 1);
+    }
+
+    #[tokio::test]
+    async fn abort() {
+        let producer: Arc<dyn Transport<u8>> = Queue::new().into();
+        let watchdog = Watchdog::new(Duration::from_secs(60), 1, None);
+        let link = Arc::new(Mutex::new(Link::new(producer.clone(), Arc::new(Wedged))));
+        watchdog
+            .watch_link("wedged", link.clone(), StallAction::Abort)
+            .unwrap();
+        producer.send_batch(vec![1, 2]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // An aborted `Link` is reported once and no longer checked
+        assert_eq!(watchdog.check().await.unwrap().len(), 1);
+        assert!(watchdog.check().await.unwrap().is_empty());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(watchdog.check().await.unwrap().is_empty());
+        assert_eq!(producer.recv_avaliable().await.unwrap(), vec![2]);
+
+        // The caller still holds the `Link`, so restarting it onto another wedged consumer is checked again
+        link.lock().unwrap().restart(Arc::new(Wedged));
+        producer.send_batch(vec![3, 4]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(watchdog.check().await.unwrap().len(), 1);
+        assert_eq!(watchdog.stalls(), 2);
+    }
+
+    #[tokio::test]
+    async fn backlog() {
+        let producer: Arc<dyn Transport<u8>> = Queue::new().into();
+        let watchdog = Watchdog::new(Duration::from_secs(60), 1, None);
+        let link = Arc::new(Mutex::new(Link::new(producer.clone(), Arc::new(Wedged))));
+        watchdog
+            .watch_link("wedged", link.clone(), StallAction::Report)
+            .unwrap();
+
+        // A single item held by the wedged consumer leaves no queued backlog, so the `Link` isn't pending
+        producer.send(1).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(link.lock().unwrap().in_flight(), 1);
+        assert_eq!(link.lock().unwrap().backlog(), Some(0));
+        assert!(watchdog.check().await.unwrap().is_empty());
+
+        // Data queueing behind it is pending without progress
+        producer.send(2).await.unwrap();
+        assert_eq!(link.lock().unwrap().backlog(), Some(1));
+        assert_eq!(watchdog.check().await.unwrap().len(), 1);
+
+        // A `Link` whose `Task` stopped while data kept queueing is stalled too
+        let producer: Arc<dyn Transport<u8>> = Queue::new().into();
+        let link = Link::new(producer.clone(), Queue::new().into());
+        link.abort();
+        watchdog
+            .watch_link("stopped", Arc::new(Mutex::new(link)), StallAction::Report)
+            .unwrap();
+        producer.send(3).await.unwrap();
+        let stalled = watchdog.check().await.unwrap();
+        assert_eq!(
+            stalled.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["wedged", "stopped"]
+        );
+    }
+}
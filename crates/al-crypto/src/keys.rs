@@ -0,0 +1,89 @@
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Compares two byte slices in constant time for equal lengths, only returning early if the lengths differ
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a
+        .iter()
+        .zip(b)
+        .fold(0u8, |diff, (a, b)| diff | std::hint::black_box(a ^ b));
+    std::hint::black_box(diff) == 0
+}
+
+/// Overwrites the buffer with zeros using volatile writes the compiler can't optimize away
+pub fn wipe(buf: &mut [u8]) {
+    for byte in buf.iter_mut() {
+        // SAFETY: `byte` is a valid and aligned `&mut u8`
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Wipes the borrowed buffer once dropped, including while unwinding from a panic
+struct WipeGuard<'a>(&'a mut [u8]);
+
+impl Drop for WipeGuard<'_> {
+    fn drop(&mut self) {
+        wipe(self.0);
+    }
+}
+
+/// Passes the buffer to `f`, wiping it before it is freed even if `f` panics
+pub fn with_wiped<R>(mut buf: Vec<u8>, f: impl FnOnce(&[u8]) -> R) -> R {
+    let guard = WipeGuard(&mut buf);
+    f(guard.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ct_eq, wipe, with_wiped, WipeGuard};
+
+    #[test]
+    fn ct_eq_matrix() {
+        let cases: [(&[u8], &[u8], bool); 7] = [
+            (b"", b"", true),
+            (b"secret", b"secret", true),
+            (b"secret", b"secreT", false),
+            (b"secret", b"Secret", false),
+            (b"secret", b"secrets", false),
+            (b"", b"s", false),
+            (&[0xFF; 32], &[0xFF; 32], true),
+        ];
+        for (a, b, equal) in cases {
+            assert_eq!(ct_eq(a, b), equal, "{a:?} == {b:?}");
+            assert_eq!(ct_eq(b, a), equal, "{b:?} == {a:?}");
+        }
+    }
+
+    #[test]
+    fn wipe_buffer() {
+        let mut buf = *b"password";
+        wipe(&mut buf);
+        assert_eq!(buf, [0; 8]);
+        wipe(&mut []);
+    }
+
+    #[test]
+    fn wipe_on_panic() {
+        assert!(with_wiped(b"token".to_vec(), |buf| ct_eq(buf, b"token")));
+
+        // The guard used by `with_wiped` still wipes while unwinding
+        let mut buf = b"token".to_vec();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let guard = WipeGuard(&mut buf);
+            assert_eq!(guard.0, b"token");
+            panic!("Panic while holding the secret");
+        }));
+        assert!(result.is_err());
+        assert_eq!(buf, [0; 5]);
+
+        let result = std::panic::catch_unwind(|| {
+            with_wiped(b"token".to_vec(), |_| {
+                panic!("Panic while holding the secret")
+            })
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod keys;
+
+pub use keys::{ct_eq, wipe, with_wiped};
@@ -55,4 +55,120 @@ mod tests {
         has_impl_marker::<GenericEvent2<u128, String>>();
         has_impl_marker::<GenericEvent2<String, u128>>();
     }
+
+    /// Test events holding borrowed `'static` data through `Cow<'static, str>` rather than a lifetime parameter
+    #[test]
+    fn static_borrowed_event() {
+        use al_core::{register_event, Event, JsonSerde, SerdeFormat};
+        use std::borrow::Cow;
+
+        // Events with lifetime parameters such as `struct BorrowedEvent<'a>(&'a str)` fail to compile, checked by the `compile_fail` doctests on `al_core::EventMarker`
+        #[event]
+        struct CowEvent {
+            label: Cow<'static, str>,
+            count: u8,
+        }
+        has_impl_marker::<CowEvent>();
+        register_event!(CowEvent);
+
+        // Both borrowed and owned data serialize the same, deserializing as owned data
+        let borrowed = CowEvent {
+            label: Cow::Borrowed("static label"),
+            count: 1,
+        };
+        let owned = CowEvent {
+            label: Cow::Owned("static label".to_string()),
+            count: 1,
+        };
+        let json = JsonSerde.serialize_event(&borrowed).unwrap();
+        assert_eq!(json, JsonSerde.serialize_event(&owned).unwrap());
+        let deserialized: CowEvent = JsonSerde.deserialize_event(&json).unwrap();
+        assert_eq!(deserialized, borrowed);
+
+        // The event downcasts back out of a `Command`
+        let command = borrowed.clone().to_cmd();
+        assert_eq!(command.downcast_event::<CowEvent>().unwrap(), borrowed);
+        let command = JsonSerde
+            .deserialize_command(&JsonSerde.serialize_command(&command).unwrap())
+            .unwrap();
+        assert_eq!(
+            command.downcast_event::<CowEvent>().unwrap().label,
+            "static label"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "transport"))]
+mod transport_tests {
+    use al_core::{Link, Queue, Splice, Transport, TransportError};
+    use al_derive::transport_item;
+    use std::sync::Arc;
+
+    /// Helper function to ensure a type implements TransportItemRequirements
+    fn has_impl_requirements<T: al_core::TransportItemRequirements>() {}
+
+    #[transport_item]
+    #[derive(PartialEq)]
+    struct Payload {
+        id: u32,
+        name: String,
+    }
+
+    #[transport_item]
+    #[derive(PartialEq)]
+    struct Summary(u32);
+
+    /// Test `transport_item` attribute macro for simple, existing derive, omitted and generic structs
+    #[test]
+    fn transport_item_derive() {
+        // Using `transport_item` attribute macro with existing derive
+        #[transport_item]
+        #[derive(Clone)]
+        struct TestItemA(Vec<u8>);
+
+        // Using the `transport_item` attribute macro with omitting derives
+        #[transport_item(Debug)]
+        struct TestItemB;
+        impl std::fmt::Debug for TestItemB {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "TestItemB")
+            }
+        }
+
+        #[transport_item]
+        struct GenericItem<T>(T);
+
+        has_impl_requirements::<Payload>();
+        has_impl_requirements::<TestItemA>();
+        has_impl_requirements::<TestItemB>();
+        has_impl_requirements::<GenericItem<u128>>();
+    }
+
+    /// Test `transport_item` types passing through a `Queue`, `Link` and `Splice`
+    #[tokio::test]
+    async fn transport_item_transports() {
+        let payload = Payload {
+            id: 1,
+            name: "first".to_string(),
+        };
+
+        let queue = Queue::new();
+        queue.send(payload.clone()).await.unwrap();
+        assert_eq!(queue.recv().await.unwrap(), payload);
+
+        let consumer: Arc<dyn Transport<Payload>> = Queue::new().into();
+        let link = Link::new(Queue::new().into(), consumer.clone());
+        link.send(payload.clone()).await.unwrap();
+        assert_eq!(consumer.recv().await.unwrap(), payload);
+
+        let consumer: Arc<dyn Transport<Summary>> = Queue::new().into();
+        let splice = Splice::new(
+            Queue::new().into(),
+            consumer.clone(),
+            Arc::new(|payload: Payload| Ok::<_, TransportError>(Summary(payload.id))),
+            Arc::new(|payload: Payload| async move { Ok(Summary(payload.id)) }),
+        );
+        splice.send(payload).await.unwrap();
+        assert_eq!(consumer.recv().await.unwrap(), Summary(1));
+    }
 }
@@ -25,6 +25,9 @@ pub fn show_item(_: TokenStream, item: TokenStream) -> TokenStream {
 #[proc_macro_derive(EventMarker)]
 pub fn event_marker_derive(input: TokenStream) -> TokenStream {
     let mut input = parse_macro_input!(input as DeriveInput);
+    if let Some(error) = reject_lifetimes(&input) {
+        return error;
+    }
 
     for param in &mut input.generics.params {
         if let GenericParam::Type(type_param) = param {
@@ -36,6 +39,19 @@ pub fn event_marker_derive(input: TokenStream) -> TokenStream {
     derive_event_marker(input)
 }
 
+/// Helper function returning a compile error for the first lifetime parameter, as events must be `'static` to be boxed into a `Command`
+fn reject_lifetimes(input: &DeriveInput) -> Option<TokenStream> {
+    input.generics.lifetimes().next().map(|lifetime| {
+        syn::Error::new_spanned(
+            lifetime,
+            "events can't have lifetime parameters, as they must be `'static` to be boxed into a `Command`. \
+            Use owned types such as `String`, or `Cow<'static, str>` to hold borrowed `'static` data",
+        )
+        .to_compile_error()
+        .into()
+    })
+}
+
 /// Generate the implementation of EventMarker
 /// type name concats module path with the name for 'path::to::module::TypeName'
 fn derive_event_marker(input: DeriveInput) -> TokenStream {
@@ -59,7 +75,9 @@ pub fn event_requirements(attrs: TokenStream, item: TokenStream) -> TokenStream
 }
 
 /// Helper function to add required `Event` traits to a DeriveInput
-fn add_event_traits(mut item: DeriveInput, attrs: Punctuated<Meta, Comma>) -> TokenStream {
+fn add_event_traits(item: DeriveInput, attrs: Punctuated<Meta, Comma>) -> TokenStream {
+    // Only extended with the serde traits when the `serde` feature is enabled
+    #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
     let mut required_traits: Vec<Path> = vec![
         parse_quote!(Clone),
         parse_quote!(Default),
@@ -74,6 +92,30 @@ fn add_event_traits(mut item: DeriveInput, attrs: Punctuated<Meta, Comma>) -> To
         parse_quote!(serde::Deserialize),
     ]);
 
+    add_traits(item, attrs, required_traits)
+}
+
+/// Helper function to add required `TransportItemRequirements` traits to a DeriveInput
+fn add_transport_item_traits(item: DeriveInput, attrs: Punctuated<Meta, Comma>) -> TokenStream {
+    // Only extended with the serde traits when the `serde` feature is enabled
+    #[cfg_attr(not(feature = "serde"), allow(unused_mut))]
+    let mut required_traits: Vec<Path> = vec![parse_quote!(Clone), parse_quote!(Debug)];
+
+    #[cfg(feature = "serde")]
+    required_traits.extend(vec![
+        parse_quote!(serde::Serialize),
+        parse_quote!(serde::Deserialize),
+    ]);
+
+    add_traits(item, attrs, required_traits)
+}
+
+/// Helper function to derive the `required_traits` on a DeriveInput, skipping any omitted in `attrs` or already derived
+fn add_traits(
+    mut item: DeriveInput,
+    attrs: Punctuated<Meta, Comma>,
+    mut required_traits: Vec<Path>,
+) -> TokenStream {
     // Remove any traits specified in the attribute arguments
     for arg in attrs {
         match arg {
@@ -115,9 +157,13 @@ fn add_event_traits(mut item: DeriveInput, attrs: Punctuated<Meta, Comma>) -> To
 /// Attribute macro to mark a struct as an event, automatically implementing `EventMarker` and required traits.
 ///
 /// Will cause conflicting implementations if placed after any `#derive(...)]` attributes that implement any super traits of `EventRequirements`.
+/// Lifetime parameters aren't supported, with `Cow<'static, str>` and other owned types holding borrowed data instead.
 #[proc_macro_attribute]
 pub fn event(attrs: TokenStream, item: TokenStream) -> TokenStream {
     let mut item = parse_macro_input!(item as DeriveInput);
+    if let Some(error) = reject_lifetimes(&item) {
+        return error;
+    }
 
     // Add the `EventMarker` derive if not already present
     if !item.attrs.iter().any(|attr| {
@@ -142,6 +188,17 @@ pub fn event(attrs: TokenStream, item: TokenStream) -> TokenStream {
     )
 }
 
+/// Attribute macro to add the required traits for passing a non `Event` type through a `Transport`, satisfying `TransportItemRequirements`.
+///
+/// Traits can be omitted in the same way as `#[event(...)]`, and will also cause conflicting implementations if placed after any `#derive(...)]` attributes that implement them.
+#[proc_macro_attribute]
+pub fn transport_item(attrs: TokenStream, item: TokenStream) -> TokenStream {
+    add_transport_item_traits(
+        parse_macro_input!(item as DeriveInput),
+        parse_macro_input!(attrs with Punctuated<Meta, Comma>::parse_terminated),
+    )
+}
+
 /// Helper attribute macro to add specific common bounds to functions to have a single place to edit the trait bounds
 #[proc_macro_attribute]
 pub fn with_bounds(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -151,10 +208,12 @@ pub fn with_bounds(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Check args to see which bounds to add
     let add_f = args.iter().any(|ident| ident == "F");
     let add_c = args.iter().any(|ident| ident == "C");
+    let add_i = args.iter().any(|ident| ident == "I");
+    let add_fr = args.iter().any(|ident| ident == "FR");
 
-    if add_f || add_c {
+    if add_f || add_c || add_i || add_fr {
         // get generics for processing and init where clause if missing
-        let mut generics = &mut input_fn.sig.generics;
+        let generics = &mut input_fn.sig.generics;
         {
             let _ = generics
                 .where_clause
@@ -166,19 +225,34 @@ pub fn with_bounds(attr: TokenStream, item: TokenStream) -> TokenStream {
 
         // Add to generics and where clause
         if add_f {
-            add_generic(&mut generics, "F");
-            add_generic(&mut generics, "Fut");
+            add_generic(generics, "F");
+            add_generic(generics, "Fut");
             if let Some(where_clause) = &mut generics.where_clause {
                 add_f_bound(where_clause);
             }
         }
         if add_c {
-            add_generic(&mut generics, "C");
-            add_generic(&mut generics, "FutC");
+            add_generic(generics, "C");
+            add_generic(generics, "FutC");
             if let Some(where_clause) = &mut generics.where_clause {
                 add_c_bound(where_clause);
             }
         }
+        if add_i {
+            add_generic(generics, "I");
+            add_generic(generics, "FutI");
+            add_generic(generics, "R");
+            if let Some(where_clause) = &mut generics.where_clause {
+                add_i_bound(where_clause);
+            }
+        }
+        if add_fr {
+            add_generic(generics, "F");
+            add_generic(generics, "Fut");
+            if let Some(where_clause) = &mut generics.where_clause {
+                add_fr_bound(where_clause);
+            }
+        }
     }
 
     quote! { #input_fn }.into()
@@ -212,3 +286,24 @@ fn add_c_bound(where_clause: &mut syn::WhereClause) {
         FutC: Future<Output = bool> + Send + Sync + 'static
     });
 }
+
+fn add_i_bound(where_clause: &mut syn::WhereClause) {
+    where_clause.predicates.push(parse_quote! {
+        I: FnOnce(&Arc<RwLock<S>>) -> FutI + Send + Sync + 'static
+    });
+    where_clause.predicates.push(parse_quote! {
+        FutI: Future<Output = R> + Send + Sync + 'static
+    });
+    where_clause.predicates.push(parse_quote! {
+        R: Send + 'static
+    });
+}
+
+fn add_fr_bound(where_clause: &mut syn::WhereClause) {
+    where_clause.predicates.push(parse_quote! {
+        F: FnMut(usize, &Arc<RwLock<S>>, &mut R) -> Fut + Send + Sync + 'static
+    });
+    where_clause.predicates.push(parse_quote! {
+        Fut: Future<Output = Result<T, E>> + Send + Sync + 'static
+    });
+}